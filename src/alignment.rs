@@ -0,0 +1,187 @@
+//! Implements aligning the cues of two versions of a subtitle, e.g. in different languages.
+//!
+//! It describes the [CueAligner] struct, which pairs the cues of a source subtitle with the cues
+//! of a target subtitle, and the [CuePair] struct, which is a single row of the resulting pairing
+//! table.
+//!
+//! The alignment is monotonic: cues are paired in chronological order, like a sentence alignment
+//! of two translations. Besides 1:1 pairs, a cue may be paired with two consecutive cues of the
+//! other subtitle (1:2 and 2:1), which covers cues that were split or merged during translation.
+
+use serde::{Deserialize, Serialize};
+use time::Time;
+
+use crate::cue::{Cue, Subtitle};
+use crate::metrics::count_characters;
+use crate::util::millis_between;
+
+/// Describes how cues are aligned.
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct CueAligner {
+    /// Minimum score (between `0` and `1`) a pair must reach. Cues that can't be paired with this
+    /// score are reported as unmatched.
+    pub min_score: f64,
+    /// Expected ratio of target to source characters, e.g. `1.2` if the target language is
+    /// usually 20% longer. If set, the score of a pair is the mean of its time score and how well
+    /// the text lengths match this ratio. Otherwise only time overlap is considered.
+    pub length_ratio: Option<f64>,
+}
+
+impl Default for CueAligner {
+    fn default() -> Self {
+        Self {
+            min_score: 0.3,
+            length_ratio: None,
+        }
+    }
+}
+
+/// A row of the pairing table. Either side is empty for unmatched cues.
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct CuePair {
+    /// Indices of the source cues in [Subtitle::cues].
+    pub source: Vec<usize>,
+    /// Indices of the target cues in [Subtitle::cues].
+    pub target: Vec<usize>,
+    /// How well the cues match, between `0` and `1`. `0` for unmatched cues.
+    pub score: f64,
+}
+
+impl CuePair {
+    /// Whether cues of both subtitles were paired.
+    pub fn is_matched(&self) -> bool {
+        !self.source.is_empty() && !self.target.is_empty()
+    }
+}
+
+/// Number of source and target cues per step of the alignment.
+const STEPS: [(usize, usize); 5] = [(1, 0), (0, 1), (1, 1), (1, 2), (2, 1)];
+
+impl CueAligner {
+    /// Pairs the cues of `source` and `target` and returns the pairing table in chronological
+    /// order.
+    ///
+    /// Every cue that is part of a pair costs `1 - score`, every unmatched cue costs
+    /// `1 - min_score`. The alignment with the lowest total cost is returned.
+    pub fn align<S: Subtitle, T: Subtitle>(&self, source: &S, target: &T) -> Vec<CuePair> {
+        let source = AlignCue::collect(source);
+        let target = AlignCue::collect(target);
+        let (n, m) = (source.len(), target.len());
+        let index = |i: usize, j: usize| i * (m + 1) + j;
+
+        // steps[index(i, j)] is the index in STEPS of the step leading to the cheapest alignment
+        // of the first i source and j target cues. Steps reach at most two rows ahead, so only
+        // the costs of three rows (costs[i % 3] for row i) are kept.
+        let mut steps = vec![0u8; (n + 1) * (m + 1)];
+        let mut costs = vec![vec![f64::INFINITY; m + 1]; 3];
+        costs[0][0] = 0.0;
+        for i in 0..=n {
+            // the row two ahead still holds the costs of the previous row
+            costs[(i + 2) % 3].fill(f64::INFINITY);
+            for j in 0..=m {
+                let cost = costs[i % 3][j];
+                if cost.is_infinite() {
+                    continue;
+                }
+                for (step, (a, b)) in STEPS.into_iter().enumerate() {
+                    if i + a > n || j + b > m {
+                        continue;
+                    }
+                    let step_cost = if a == 0 || b == 0 {
+                        1.0 - self.min_score
+                    } else {
+                        let score = self.score(&source[i..i + a], &target[j..j + b]);
+                        if score < self.min_score || score == 0.0 {
+                            continue;
+                        }
+                        (a + b) as f64 * (1.0 - score)
+                    };
+                    if cost + step_cost < costs[(i + a) % 3][j + b] {
+                        costs[(i + a) % 3][j + b] = cost + step_cost;
+                        steps[index(i + a, j + b)] = step as u8
+                    }
+                }
+            }
+        }
+
+        let mut pairs = vec![];
+        let (mut i, mut j) = (n, m);
+        while i > 0 || j > 0 {
+            let (a, b) = STEPS[steps[index(i, j)] as usize];
+            let (source, target) = (&source[i - a..i], &target[j - b..j]);
+            pairs.push(CuePair {
+                source: source.iter().map(|c| c.index).collect(),
+                target: target.iter().map(|c| c.index).collect(),
+                score: if a == 0 || b == 0 {
+                    0.0
+                } else {
+                    self.score(source, target)
+                },
+            });
+            (i, j) = (i - a, j - b)
+        }
+        pairs.reverse();
+
+        pairs
+    }
+
+    fn score(&self, source: &[AlignCue], target: &[AlignCue]) -> f64 {
+        let (source_start, source_end) = AlignCue::span(source);
+        let (target_start, target_end) = AlignCue::span(target);
+        let overlap = millis_between(source_start.max(target_start), source_end.min(target_end));
+        let union = millis_between(source_start.min(target_start), source_end.max(target_end));
+        if overlap <= 0 || union <= 0 {
+            return 0.0;
+        }
+        let time_score = overlap as f64 / union as f64;
+
+        let Some(length_ratio) = self.length_ratio else {
+            return time_score;
+        };
+        let source_chars = source.iter().map(|c| c.characters).sum::<usize>() as f64;
+        let target_chars = target.iter().map(|c| c.characters).sum::<usize>() as f64;
+        let length_score = if source_chars == 0.0 || target_chars == 0.0 {
+            (source_chars == target_chars) as u8 as f64
+        } else {
+            let ratio = target_chars / source_chars;
+            ratio.min(length_ratio) / ratio.max(length_ratio)
+        };
+        (time_score + length_score) / 2.0
+    }
+}
+
+/// The parts of a cue needed for the alignment.
+struct AlignCue {
+    index: usize,
+    start: Time,
+    end: Time,
+    characters: usize,
+}
+
+impl AlignCue {
+    /// Returns the cues of `subtitle` in chronological order.
+    fn collect<S: Subtitle>(subtitle: &S) -> Vec<AlignCue> {
+        let mut cues: Vec<AlignCue> = subtitle
+            .cues()
+            .iter()
+            .enumerate()
+            .map(|(index, cue)| AlignCue {
+                index,
+                start: cue.start(),
+                end: cue.end(),
+                characters: cue.plain_text().lines().map(count_characters).sum(),
+            })
+            .collect();
+        cues.sort_by_key(|c| c.start);
+        cues
+    }
+
+    /// Returns the earliest start and latest end of `cues`.
+    fn span(cues: &[AlignCue]) -> (Time, Time) {
+        (
+            cues.iter().map(|c| c.start).min().unwrap(),
+            cues.iter().map(|c| c.end).max().unwrap(),
+        )
+    }
+}
@@ -0,0 +1,208 @@
+//! Implements composing two subtitles into one bilingual subtitle.
+//!
+//! [bilingual_ssa] and [bilingual_vtt] show the cues of one subtitle at the top and the cues of
+//! another one at the bottom of the screen. Cues of both subtitles which overlap for at least half
+//! of the shorter cue are aligned, so that they appear and disappear together. Every cue is paired
+//! with at most one cue of the other subtitle, and cues left over only join a pair they mostly lie
+//! in (e.g. two short cues translated as one long one), so subtitles which are slightly offset
+//! don't collapse into a few long cues.
+//!
+//! Only the plain text (see [Cue::plain_text]) of the cues is used, as markup can't be converted
+//! between all formats.
+
+use std::cmp::Reverse;
+use std::collections::HashMap;
+use time::Time;
+
+use crate::cue::{Cue, Subtitle};
+use crate::ssa::{SSAEvent, SSAStyle, SSA};
+use crate::util::{millis_between, Alignment};
+use crate::vtt::{VTTLine, VTT};
+
+/// Composes `top` and `bottom` into an [SSA] with a `Top` and a `Bottom` style.
+pub fn bilingual_ssa<T: Subtitle, B: Subtitle>(top: &T, bottom: &B) -> SSA {
+    let style = |name: &str, alignment| SSAStyle {
+        name: name.to_string(),
+        fontname: "Arial".to_string(),
+        fontsize: 20.0,
+        alignment,
+        ..Default::default()
+    };
+
+    let mut events = vec![];
+    for cue in align(top, bottom) {
+        for (style, text) in [("Top", cue.top), ("Bottom", cue.bottom)] {
+            let Some(text) = text else { continue };
+            events.push(SSAEvent {
+                start: cue.start,
+                end: cue.end,
+                style: style.to_string(),
+                text: text.replace('\n', "\\N"),
+                ..Default::default()
+            })
+        }
+    }
+
+    SSA {
+        styles: vec![
+            style("Top", Alignment::TopCenter),
+            style("Bottom", Alignment::BottomCenter),
+        ],
+        events,
+        ..Default::default()
+    }
+}
+
+/// Composes `top` and `bottom` into a [VTT]. Top cues are placed on the first line (`line:0`),
+/// bottom cues on the last line (`line:-1`) of the video.
+pub fn bilingual_vtt<T: Subtitle, B: Subtitle>(top: &T, bottom: &B) -> VTT {
+    let mut lines = vec![];
+    for cue in align(top, bottom) {
+        for (line, text) in [("0", cue.top), ("-1", cue.bottom)] {
+            let Some(text) = text else { continue };
+            lines.push(VTTLine {
+                start: cue.start,
+                end: cue.end,
+                settings: HashMap::from([("line".to_string(), Some(line.to_string()))]),
+                text: text
+                    .replace('&', "&amp;")
+                    .replace('<', "&lt;")
+                    .replace('>', "&gt;"),
+                ..Default::default()
+            })
+        }
+    }
+
+    VTT {
+        lines,
+        ..Default::default()
+    }
+}
+
+/// The plain text of aligned top and bottom cues.
+struct BilingualCue {
+    start: Time,
+    end: Time,
+    top: Option<String>,
+    bottom: Option<String>,
+}
+
+/// Groups aligned cues and returns the groups in chronological order.
+///
+/// Aligned cues are paired one to one, those overlapping most first. A cue which is left over
+/// joins the pair of the cue it overlaps most, if it overlaps that cue for at least half of its
+/// own duration.
+fn align<T: Subtitle, B: Subtitle>(top: &T, bottom: &B) -> Vec<BilingualCue> {
+    let top_times: Vec<(Time, Time)> = top.cues().iter().map(|c| (c.start(), c.end())).collect();
+    let bottom_times: Vec<(Time, Time)> =
+        bottom.cues().iter().map(|c| (c.start(), c.end())).collect();
+
+    // union find over all cues, bottom cues are indexed after the top cues
+    let n = top_times.len();
+    let mut parents: Vec<usize> = (0..n + bottom_times.len()).collect();
+    fn root(parents: &mut [usize], mut i: usize) -> usize {
+        while parents[i] != i {
+            parents[i] = parents[parents[i]];
+            i = parents[i]
+        }
+        i
+    }
+    fn union(parents: &mut [usize], a: usize, b: usize) {
+        let (a, b) = (root(parents, a), root(parents, b));
+        parents[a] = b
+    }
+
+    let mut pairs = vec![];
+    for (i, t) in top_times.iter().enumerate() {
+        for (j, b) in bottom_times.iter().enumerate() {
+            if aligned(*t, *b) {
+                pairs.push((overlap(*t, *b), i, n + j))
+            }
+        }
+    }
+    pairs.sort_by_key(|(overlap, i, j)| (Reverse(*overlap), *i, *j));
+
+    let mut paired = vec![false; parents.len()];
+    for &(_, i, j) in &pairs {
+        if !paired[i] && !paired[j] {
+            paired[i] = true;
+            paired[j] = true;
+            union(&mut parents, i, j)
+        }
+    }
+    let duration = |i: usize| {
+        let (start, end) = if i < n {
+            top_times[i]
+        } else {
+            bottom_times[i - n]
+        };
+        millis_between(start, end)
+    };
+    let mut joined = paired.clone();
+    for &(overlap, i, j) in &pairs {
+        for (cue, partner) in [(i, j), (j, i)] {
+            if !joined[cue] && paired[partner] && overlap * 2 >= duration(cue) {
+                joined[cue] = true;
+                union(&mut parents, cue, partner)
+            }
+        }
+    }
+
+    let mut groups: Vec<(Vec<usize>, Vec<usize>)> = vec![];
+    let mut group_of_root = HashMap::new();
+    for i in 0..parents.len() {
+        let root = root(&mut parents, i);
+        let group = *group_of_root.entry(root).or_insert_with(|| {
+            groups.push((vec![], vec![]));
+            groups.len() - 1
+        });
+        if i < n {
+            groups[group].0.push(i)
+        } else {
+            groups[group].1.push(i - n)
+        }
+    }
+
+    let text = |cues: Vec<String>| (!cues.is_empty()).then(|| cues.join("\n"));
+    let mut cues: Vec<BilingualCue> = groups
+        .into_iter()
+        .map(|(mut t, mut b)| {
+            t.sort_by_key(|i| top_times[*i]);
+            b.sort_by_key(|i| bottom_times[*i]);
+            let times = t
+                .iter()
+                .map(|i| top_times[*i])
+                .chain(b.iter().map(|i| bottom_times[*i]));
+            BilingualCue {
+                start: times.clone().map(|(s, _)| s).min().unwrap(),
+                end: times.map(|(_, e)| e).max().unwrap(),
+                top: text(t.iter().map(|i| top.cues()[*i].plain_text()).collect()),
+                bottom: text(b.iter().map(|i| bottom.cues()[*i].plain_text()).collect()),
+            }
+        })
+        .collect();
+    cues.sort_by_key(|c| c.start);
+
+    // the extended cues might reach into the following ones
+    for i in 1..cues.len() {
+        let next_start = cues[i].start;
+        let previous = &mut cues[i - 1];
+        if previous.end > next_start && previous.start < next_start {
+            previous.end = next_start
+        }
+    }
+
+    cues
+}
+
+/// Whether two cues overlap for at least half the duration of the shorter one.
+fn aligned(a: (Time, Time), b: (Time, Time)) -> bool {
+    let overlap = overlap(a, b);
+    let shorter = millis_between(a.0, a.1).min(millis_between(b.0, b.1));
+    overlap > 0 && overlap * 2 >= shorter
+}
+
+/// The duration both cues are shown in milliseconds, negative if they don't overlap.
+fn overlap((a_start, a_end): (Time, Time), (b_start, b_end): (Time, Time)) -> i64 {
+    millis_between(a_start.max(b_start), a_end.min(b_end))
+}
@@ -0,0 +1,77 @@
+//! Format independent access to timed subtitle cues.
+//!
+//! It describes the [Cue] trait, which is implemented by [SRTLine](crate::SRTLine),
+//! [VTTLine](crate::VTTLine) and [SSAEvent](crate::SSAEvent), and the [Subtitle] trait, which is
+//! implemented by [SRT](crate::SRT), [VTT](crate::VTT) and [SSA](crate::SSA). Operations that only
+//! care about timing and text (metrics, linting, timing fixes, ...) are written against these
+//! traits so that they work for every format.
+
+use time::Time;
+
+/// A single timed piece of text.
+pub trait Cue {
+    /// The sequence separating two displayed lines in [Cue::text].
+    const LINE_BREAK: &'static str;
+
+    /// Time at which the cue gets visible.
+    fn start(&self) -> Time;
+    /// Time at which the cue gets hidden.
+    fn end(&self) -> Time;
+    /// Sets the time at which the cue gets visible.
+    fn set_start(&mut self, start: Time);
+    /// Sets the time at which the cue gets hidden.
+    fn set_end(&mut self, end: Time);
+    /// The raw text of the cue, including all format specific markup.
+    fn text(&self) -> &str;
+    /// Replaces the raw text of the cue.
+    fn set_text(&mut self, text: String);
+    /// The text of the cue as it would be displayed to the viewer: markup is removed, escaped
+    /// characters are resolved and every displayed line is separated by `\n`.
+    fn plain_text(&self) -> String;
+    /// The layer the cue is drawn on. Cues on higher layers are drawn above cues on lower layers.
+    /// Only SSA supports layers, all other formats draw every cue on layer `0`.
+    fn layer(&self) -> u32 {
+        0
+    }
+    /// Times inside the cue at which the text following the given byte offset in [Cue::text]
+    /// should be highlighted, like VTT timestamp tags (`<00:00:01.000>`) used for karaoke. Empty
+    /// for formats without inline timing.
+    fn inline_timestamps(&self) -> Vec<(usize, Time)> {
+        vec![]
+    }
+    /// The name of the style the cue uses. [None] for formats without per-cue styles.
+    fn style(&self) -> Option<&str> {
+        None
+    }
+    /// Changes the style the cue uses. Does nothing for formats without per-cue styles.
+    fn set_style(&mut self, _style: String) {}
+}
+
+/// A subtitle document containing a list of [Cue]s.
+pub trait Subtitle {
+    type Cue: Cue;
+
+    /// All cues of the document, in the order they are stored.
+    fn cues(&self) -> &[Self::Cue];
+    /// Mutable access to all cues of the document.
+    fn cues_mut(&mut self) -> &mut Vec<Self::Cue>;
+    /// Restores format specific numbering after cues were added, removed or reordered. Does
+    /// nothing for formats without numbering.
+    fn renumber(&mut self) {}
+}
+
+/// Removes everything between (and including) `open` and `close`. An unclosed `open` is kept as
+/// is.
+pub(crate) fn strip_markup(text: &str, open: char, close: char) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find(open) {
+        let Some(len) = rest[start..].find(close) else {
+            break;
+        };
+        result.push_str(&rest[..start]);
+        rest = &rest[start + len + close.len_utf8()..];
+    }
+    result.push_str(rest);
+    result
+}
@@ -0,0 +1,626 @@
+//! Implements helpers for EBU-TT-D (EBU Tech 3380), the TTML subset used for distributing
+//! subtitles.
+//!
+//! It describes the [EBUTTD] and [EBUTTDLine] structs and provides the [EBUTTD::parse] function.
+//!
+//! Each line carries its text color, background color and [Alignment] instead of a full TTML
+//! style model. When writing, lines are placed in one of three regions (top, middle or bottom of
+//! the 80% safe area) and styles are shared between lines with the same styling. Documents always
+//! use the `media` time base with clock times and contain the `ebuttm:documentMetadata` which
+//! declares conformance to EBU-TT-D.
+
+use regex::Regex;
+use roxmltree::{Document, Node};
+use serde::{Deserialize, Serialize};
+use std::fmt::Display;
+use time::Time;
+
+use crate::error;
+use crate::imsc::ssa_color;
+use crate::ttml::{
+    attribute, attributes, escape_attribute, escape_text, span_attributes, ssa_alignment,
+    with_fragment, TTMLErrorKind, TTML,
+};
+use crate::util::{Alignment, Color, BLACK, WHITE};
+
+use super::cue::{Cue, Subtitle};
+use super::srt::{SRTLine, SRT};
+use super::ssa::{SSAEvent, SSAEventLineType, SSAStyle, SSA};
+use super::strip_bom;
+use super::vtt::{VTTLine, VTTStyle, VTT};
+
+/// Value of `ebuttm:conformsToStandard` for EBU-TT-D 1.0.1.
+pub const EBU_TT_D_STANDARD: &str = "urn:ebu:tt:distribution:2018-04";
+
+/// Contains a Vec<[EBUTTDLine]> and the language of the document.
+///
+/// A written document generally looks like:
+/// ```text
+/// <?xml version="1.0" encoding="UTF-8"?>
+/// <tt xmlns="http://www.w3.org/ns/ttml" ... xml:lang="en" ttp:timeBase="media" ttp:cellResolution="32 15">
+///   <head>
+///     ...
+///   </head>
+///   <body>
+///     <div style="default">
+///       <p xml:id="sub1" begin="00:00:01.000" end="00:00:03.000" region="bottom" style="center"><span style="s1">This is my</span><br/><span style="s1">text</span></p>
+///     </div>
+///   </body>
+/// </tt>
+/// ```
+#[derive(Clone, Debug, Eq, PartialEq, Deserialize, Serialize)]
+pub struct EBUTTD {
+    pub lang: String,
+    pub lines: Vec<EBUTTDLine>,
+}
+
+impl Default for EBUTTD {
+    fn default() -> Self {
+        Self {
+            lang: "en".to_string(),
+            lines: vec![],
+        }
+    }
+}
+
+/// Describes each line
+///
+/// Displayed lines in the text are separated by `\n`. The styling applies to the whole line.
+#[derive(Clone, Debug, Eq, PartialEq, Deserialize, Serialize)]
+pub struct EBUTTDLine {
+    pub start: Time,
+    pub end: Time,
+    pub text: String,
+    pub alignment: Alignment,
+    pub color: Color,
+    /// Background of each displayed line. Defaults to [BLACK], as recommended for broadcast.
+    pub background_color: Color,
+    pub italic: bool,
+}
+
+impl Default for EBUTTDLine {
+    fn default() -> Self {
+        Self {
+            start: Time::from_hms(0, 0, 0).unwrap(),
+            end: Time::from_hms(0, 0, 0).unwrap(),
+            text: "".to_string(),
+            alignment: Alignment::BottomCenter,
+            color: WHITE,
+            background_color: BLACK,
+            italic: false,
+        }
+    }
+}
+
+impl EBUTTD {
+    /// Parses the given [String] into a [EBUTTD].
+    ///
+    /// The document must use the `media` time base (the default) and only clock times
+    /// (`00:00:01.500`) or offset times in hours, minutes, seconds or milliseconds (`1.5s`).
+    /// Frames, ticks and `dur` are not allowed. The styling of a line is taken from its paragraph
+    /// and first `<span>`, missing colors default to white text on a black background.
+    pub fn parse<S: AsRef<str>>(content: S) -> Result<EBUTTD, EBUTTDError> {
+        let content = strip_bom(&content);
+        let document = Document::parse(content).map_err(|e| {
+            EBUTTDError::new(EBUTTDErrorKind::Xml(e.to_string()), e.pos().row as usize)
+        })?;
+        let root = document.root_element();
+        if root.tag_name().name() != "tt" {
+            return Err(EBUTTDError::new(EBUTTDErrorKind::Invalid, line(root)));
+        }
+        if let Some(time_base) =
+            attribute(&attributes(root), "ttp:timeBase").filter(|t| *t != "media")
+        {
+            return Err(EBUTTDError::new(
+                EBUTTDErrorKind::Constraint(format!("time base '{time_base}' is not allowed")),
+                line(root),
+            ));
+        }
+
+        let time_regex =
+            Regex::new(r"^\s*(\d{2,}:[0-5]\d:[0-5]\d(\.\d+)?|\d+(\.\d+)?(h|m|s|ms))\s*$").unwrap();
+        for node in root.descendants().filter(|n| n.is_element()) {
+            if node.has_attribute("dur") {
+                return Err(EBUTTDError::new(
+                    EBUTTDErrorKind::Constraint("dur is not allowed".to_string()),
+                    line(node),
+                ));
+            }
+            for name in ["begin", "end"] {
+                if let Some(time) = node.attribute(name).filter(|t| !time_regex.is_match(t)) {
+                    return Err(EBUTTDError::new(
+                        EBUTTDErrorKind::Constraint(format!("time '{time}' is not allowed")),
+                        line(node),
+                    ));
+                }
+            }
+        }
+
+        let ttml = TTML::parse(content).map_err(|e| {
+            let kind = match e.kind() {
+                TTMLErrorKind::Xml(message) => EBUTTDErrorKind::Xml(message.clone()),
+                TTMLErrorKind::Invalid => EBUTTDErrorKind::Invalid,
+                TTMLErrorKind::Parse(message) => EBUTTDErrorKind::Parse(message.clone()),
+            };
+            EBUTTDError::new(kind, e.line())
+        })?;
+
+        let mut lines = vec![];
+        for paragraph in &ttml.paragraphs {
+            let mut style = ttml.paragraph_style(paragraph);
+            let (_, alignment) = ssa_alignment(&style, paragraph.region.is_some());
+            let span = with_fragment(&paragraph.text, |node| {
+                node.descendants()
+                    .find(|n| n.tag_name().name() == "span")
+                    .map(span_attributes)
+            });
+            if let Some((ids, attributes)) = span {
+                style.extend(ttml.resolve_style(&ids, &attributes))
+            }
+            let color = |name| style.get(name).and_then(|c| Color::from_ttml(c).ok());
+
+            lines.push(EBUTTDLine {
+                start: paragraph.begin,
+                end: paragraph.end,
+                text: paragraph.plain_text(),
+                alignment,
+                color: color("tts:color").unwrap_or(WHITE),
+                background_color: color("tts:backgroundColor").unwrap_or(BLACK),
+                italic: style.get("tts:fontStyle").is_some_and(|s| s == "italic"),
+            })
+        }
+
+        Ok(EBUTTD {
+            lang: ttml.lang.unwrap_or_default(),
+            lines,
+        })
+    }
+
+    /// Converts to [SRT]. Italic lines are wrapped in `<i>` tags, colors other than white in
+    /// `<font color="...">` tags. Alignments other than bottom center are converted to `{\an8}`
+    /// like tags. Backgrounds are dropped.
+    pub fn to_srt(&self) -> SRT {
+        SRT {
+            lines: self
+                .lines
+                .iter()
+                .enumerate()
+                .map(|(i, line)| {
+                    let mut text = line.text.replace('\n', "\r\n");
+                    if line.color != WHITE {
+                        text = format!(
+                            "<font color=\"{}\">{text}</font>",
+                            line.color.to_vtt_string()
+                        )
+                    }
+                    if line.italic {
+                        text = format!("<i>{text}</i>")
+                    }
+                    if line.alignment != Alignment::BottomCenter {
+                        text = format!("{{\\an{}}}{text}", line.alignment as u8)
+                    }
+                    SRTLine {
+                        sequence_number: i as u32 + 1,
+                        start: line.start,
+                        end: line.end,
+                        text,
+                    }
+                })
+                .collect(),
+        }
+    }
+
+    /// Converts to [VTT]. The alignment is converted to `line` and `align` settings, italic lines
+    /// are wrapped in `<i>` tags. Lines not using white text on a black background get a
+    /// `::cue(.sN)` style with their colors.
+    pub fn to_vtt(&self) -> VTT {
+        let mut styles: Vec<VTTStyle> = vec![];
+        let mut colors: Vec<(Color, Color)> = vec![];
+        let mut lines = vec![];
+        for line in &self.lines {
+            let mut text = line.text.replace('&', "&amp;").replace('<', "&lt;");
+            if line.italic {
+                text = format!("<i>{text}</i>")
+            }
+            if line.color != WHITE || line.background_color != BLACK {
+                let key = (line.color, line.background_color);
+                let i = colors.iter().position(|c| *c == key).unwrap_or_else(|| {
+                    colors.push(key);
+                    styles.push(VTTStyle {
+                        selector: Some(format!(".s{}", colors.len())),
+                        entries: [
+                            ("color".to_string(), line.color.to_vtt_string()),
+                            (
+                                "background-color".to_string(),
+                                line.background_color.to_vtt_string(),
+                            ),
+                        ]
+                        .into(),
+                    });
+                    colors.len() - 1
+                });
+                text = format!("<c.s{}>{text}</c>", i + 1)
+            }
+
+            let mut vtt_line = VTTLine {
+                start: line.start,
+                end: line.end,
+                text,
+                ..Default::default()
+            };
+            let (vertical, horizontal) = split_alignment(line.alignment);
+            let line_setting = match vertical {
+                1 => Some("50%"),
+                2 => Some("0"),
+                _ => None,
+            };
+            let align_setting = match horizontal {
+                0 => Some("left"),
+                2 => Some("right"),
+                _ => None,
+            };
+            for (name, value) in [("line", line_setting), ("align", align_setting)] {
+                if let Some(value) = value {
+                    vtt_line
+                        .settings
+                        .insert(name.to_string(), Some(value.to_string()));
+                }
+            }
+            lines.push(vtt_line)
+        }
+
+        VTT {
+            styles,
+            lines,
+            ..Default::default()
+        }
+    }
+
+    /// Converts to [SSA]. Alignments other than bottom center, italics and colors other than white
+    /// are converted to override tags. Backgrounds are dropped.
+    pub fn to_ssa(&self) -> SSA {
+        let default_style = SSAStyle {
+            name: "Default".to_string(),
+            fontname: "Arial".to_string(),
+            fontsize: 20.0,
+            alignment: Alignment::BottomCenter,
+            ..Default::default()
+        };
+
+        let mut events = vec![];
+        for line in &self.lines {
+            let mut overrides = String::new();
+            if line.alignment != Alignment::BottomCenter {
+                overrides.push_str(&format!("\\an{}", line.alignment as u8))
+            }
+            if line.italic {
+                overrides.push_str("\\i1")
+            }
+            if line.color != WHITE {
+                overrides.push_str(&format!("\\c{}&", line.color.to_ssa_string()))
+            }
+            let text = line.text.replace('\n', "\\N");
+
+            events.push(SSAEvent {
+                start: line.start,
+                end: line.end,
+                style: "Default".to_string(),
+                text: if overrides.is_empty() {
+                    text
+                } else {
+                    format!("{{{overrides}}}{text}")
+                },
+                ..Default::default()
+            })
+        }
+
+        SSA {
+            styles: vec![default_style],
+            events,
+            ..Default::default()
+        }
+    }
+}
+
+impl SRT {
+    /// Converts to [EBUTTD] with the language `lang`. See [VTT::to_ebu_tt_d].
+    pub fn to_ebu_tt_d(&self, lang: &str) -> EBUTTD {
+        self.to_vtt().to_ebu_tt_d(lang)
+    }
+}
+
+impl VTT {
+    /// Converts to [EBUTTD] with the language `lang`. The alignment is taken from the `line` and
+    /// `align` settings. Colors are taken from the `::cue` style or the style of a `<c>` tag
+    /// wrapping the whole cue, and default to white text on a black background. Cues wrapped in
+    /// `<i>` tags are italic, all other markup is removed.
+    pub fn to_ebu_tt_d(&self, lang: &str) -> EBUTTD {
+        let color = |selector: Option<&str>, name: &str| {
+            self.styles
+                .iter()
+                .rev()
+                .filter(|s| s.selector.as_deref() == selector)
+                .find_map(|s| s.entries.get(name))
+                .and_then(|c| Color::from_vtt(c).ok())
+        };
+        let class_regex = Regex::new(r"^<c((?:\.[^\s.>]+)+)>.*</c>$").unwrap();
+
+        let mut lines = vec![];
+        for line in &self.lines {
+            let text = line.text.trim();
+            let mut classes = class_regex
+                .captures(text)
+                .map(|c| {
+                    c[1].split('.')
+                        .filter(|c| !c.is_empty())
+                        .map(|c| format!(".{c}"))
+                        .collect::<Vec<String>>()
+                })
+                .unwrap_or_default();
+            classes.reverse();
+            let class_color = |name| {
+                classes
+                    .iter()
+                    .find_map(|class| color(Some(class), name))
+                    .or(color(None, name))
+            };
+
+            let setting = |name: &str| line.settings.get(name).cloned().flatten();
+            let vertical = match setting("line") {
+                Some(line) if line.ends_with('%') => {
+                    match line.trim_end_matches('%').parse::<f32>() {
+                        Ok(p) if p < 100.0 / 3.0 => 2,
+                        Ok(p) if p < 200.0 / 3.0 => 1,
+                        _ => 0,
+                    }
+                }
+                Some(line) if line.parse::<i32>().is_ok_and(|l| l >= 0) => 2,
+                _ => 0,
+            };
+            let horizontal = match setting("align").as_deref() {
+                Some("start") | Some("left") => 0,
+                Some("end") | Some("right") => 2,
+                _ => 1,
+            };
+
+            lines.push(EBUTTDLine {
+                start: line.start,
+                end: line.end,
+                text: line.plain_text(),
+                alignment: join_alignment(vertical, horizontal),
+                color: class_color("color").unwrap_or(WHITE),
+                background_color: class_color("background-color").unwrap_or(BLACK),
+                italic: text.starts_with("<i>") && text.ends_with("</i>"),
+            })
+        }
+
+        EBUTTD {
+            lang: lang.to_string(),
+            lines,
+        }
+    }
+}
+
+impl SSA {
+    /// Converts to [EBUTTD] with the language `lang`. The alignment is taken from the style of an
+    /// event or its `\an` override tag, colors and italics from the style. Only styles with a
+    /// `BorderStyle` of 3 (opaque box) keep their background, all others get a black one.
+    /// Comments are skipped.
+    pub fn to_ebu_tt_d(&self, lang: &str) -> EBUTTD {
+        let an_regex = Regex::new(r"\{[^}]*\\an([1-9])").unwrap();
+
+        let mut lines = vec![];
+        for event in &self.events {
+            if event.line_type == SSAEventLineType::Comment {
+                continue;
+            }
+            let style = self.styles.iter().find(|s| s.name == event.style);
+            let alignment = an_regex
+                .captures(&event.text)
+                .and_then(|c| Alignment::infer_from_str(&c[1]).ok())
+                .or(style.map(|s| s.alignment))
+                .unwrap_or(Alignment::BottomCenter);
+            let background_color = style
+                .filter(|s| s.border_style == 3)
+                .and_then(|s| s.back_color)
+                .map(ssa_color)
+                .unwrap_or(BLACK);
+
+            lines.push(EBUTTDLine {
+                start: event.start,
+                end: event.end,
+                text: event.plain_text(),
+                alignment,
+                color: style
+                    .and_then(|s| s.primary_color)
+                    .map(ssa_color)
+                    .unwrap_or(WHITE),
+                background_color,
+                italic: style.is_some_and(|s| s.italic),
+            })
+        }
+
+        EBUTTD {
+            lang: lang.to_string(),
+            lines,
+        }
+    }
+}
+
+impl Cue for EBUTTDLine {
+    const LINE_BREAK: &'static str = "\n";
+
+    fn start(&self) -> Time {
+        self.start
+    }
+    fn end(&self) -> Time {
+        self.end
+    }
+    fn set_start(&mut self, start: Time) {
+        self.start = start
+    }
+    fn set_end(&mut self, end: Time) {
+        self.end = end
+    }
+    fn text(&self) -> &str {
+        &self.text
+    }
+    fn set_text(&mut self, text: String) {
+        self.text = text
+    }
+    /// The text is always plain.
+    fn plain_text(&self) -> String {
+        self.text.clone()
+    }
+}
+
+impl Subtitle for EBUTTD {
+    type Cue = EBUTTDLine;
+
+    fn cues(&self) -> &[EBUTTDLine] {
+        &self.lines
+    }
+    fn cues_mut(&mut self) -> &mut Vec<EBUTTDLine> {
+        &mut self.lines
+    }
+}
+
+/// Writes a document conforming to EBU-TT-D. Lines with no duration are skipped, as the profile
+/// requires every paragraph to begin before it ends.
+impl Display for EBUTTD {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let lines: Vec<&EBUTTDLine> = self.lines.iter().filter(|l| l.start < l.end).collect();
+
+        // styles of the spans, shared by all lines styled the same
+        let mut span_styles: Vec<(Color, Color, bool)> = vec![];
+        for line in &lines {
+            let key = (line.color, line.background_color, line.italic);
+            if !span_styles.contains(&key) {
+                span_styles.push(key)
+            }
+        }
+        let used = |position: usize, vertical: bool| {
+            lines.iter().any(|l| {
+                let (v, h) = split_alignment(l.alignment);
+                (if vertical { v } else { h }) as usize == position
+            })
+        };
+
+        let mut out = vec![
+            r#"<?xml version="1.0" encoding="UTF-8"?>"#.to_string(),
+            format!(
+                concat!(
+                    r#"<tt xmlns="http://www.w3.org/ns/ttml" xmlns:ttp="http://www.w3.org/ns/ttml#parameter""#,
+                    r#" xmlns:tts="http://www.w3.org/ns/ttml#styling" xmlns:ebuttm="urn:ebu:tt:metadata""#,
+                    r#" xmlns:ebutts="urn:ebu:tt:style" xml:lang="{}" xml:space="default""#,
+                    r#" ttp:timeBase="media" ttp:cellResolution="32 15">"#
+                ),
+                escape_attribute(&self.lang)
+            ),
+            "  <head>".to_string(),
+            "    <metadata>".to_string(),
+            "      <ebuttm:documentMetadata>".to_string(),
+            format!("        <ebuttm:conformsToStandard>{EBU_TT_D_STANDARD}</ebuttm:conformsToStandard>"),
+            "      </ebuttm:documentMetadata>".to_string(),
+            "    </metadata>".to_string(),
+            "    <styling>".to_string(),
+            r#"      <style xml:id="default" tts:fontFamily="proportionalSansSerif" tts:fontSize="100%" tts:lineHeight="normal" ebutts:linePadding="0.5c"/>"#.to_string(),
+        ];
+        for (i, align) in ["left", "center", "right"].into_iter().enumerate() {
+            if used(i, false) {
+                out.push(format!(
+                    r#"      <style xml:id="{align}" tts:textAlign="{align}"/>"#
+                ))
+            }
+        }
+        for (i, (color, background_color, italic)) in span_styles.iter().enumerate() {
+            out.push(format!(
+                r#"      <style xml:id="s{}" tts:color="{}" tts:backgroundColor="{}"{}/>"#,
+                i + 1,
+                color.to_ttml_string(),
+                background_color.to_ttml_string(),
+                if *italic {
+                    r#" tts:fontStyle="italic""#
+                } else {
+                    ""
+                }
+            ))
+        }
+        out.push("    </styling>".to_string());
+        out.push("    <layout>".to_string());
+        for (i, (region, display_align)) in
+            [("bottom", "after"), ("middle", "center"), ("top", "before")]
+                .into_iter()
+                .enumerate()
+        {
+            // EBU-TT-D requires at least one region
+            if used(i, true) || (i == 0 && lines.is_empty()) {
+                out.push(format!(
+                    r#"      <region xml:id="{region}" tts:origin="10% 10%" tts:extent="80% 80%" tts:displayAlign="{display_align}"/>"#
+                ))
+            }
+        }
+        out.push("    </layout>".to_string());
+        out.push("  </head>".to_string());
+
+        out.push("  <body>".to_string());
+        out.push(r#"    <div style="default">"#.to_string());
+        for (i, line) in lines.iter().enumerate() {
+            let (vertical, horizontal) = split_alignment(line.alignment);
+            let span_style = span_styles
+                .iter()
+                .position(|s| *s == (line.color, line.background_color, line.italic))
+                .unwrap()
+                + 1;
+            let text: Vec<String> = line
+                .text
+                .lines()
+                .map(|l| format!(r#"<span style="s{span_style}">{}</span>"#, escape_text(l)))
+                .collect();
+            out.push(format!(
+                r#"      <p xml:id="sub{}" begin="{}" end="{}" region="{}" style="{}">{}</p>"#,
+                i + 1,
+                format_time(line.start),
+                format_time(line.end),
+                ["bottom", "middle", "top"][vertical as usize],
+                ["left", "center", "right"][horizontal as usize],
+                text.join("<br/>")
+            ))
+        }
+        out.push("    </div>".to_string());
+        out.push("  </body>".to_string());
+        out.push("</tt>".to_string());
+
+        write!(f, "{}", out.join("\n"))
+    }
+}
+
+error! {
+    EBUTTDError => EBUTTDErrorKind {
+        Xml(String),
+        Invalid,
+        Parse(String),
+        Constraint(String),
+    }
+}
+
+fn line(node: Node) -> usize {
+    node.document().text_pos_at(node.range().start).row as usize
+}
+
+fn format_time(time: Time) -> String {
+    let (hour, minute, second, milli) = time.as_hms_milli();
+    format!("{hour:0>2}:{minute:0>2}:{second:0>2}.{milli:0>3}")
+}
+
+/// Returns the vertical (`0` bottom, `1` middle, `2` top) and horizontal (`0` left, `1` center,
+/// `2` right) position of `alignment`.
+fn split_alignment(alignment: Alignment) -> (u8, u8) {
+    let alignment = alignment as u8 - 1;
+    (alignment / 3, alignment % 3)
+}
+
+fn join_alignment(vertical: u8, horizontal: u8) -> Alignment {
+    // safe unwrap: the number is always between 1 and 9
+    Alignment::infer_from_str(&(vertical * 3 + horizontal + 1).to_string()).unwrap()
+}
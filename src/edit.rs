@@ -0,0 +1,152 @@
+//! Implements high level editing of cues.
+//!
+//! All functions keep the format specific numbering consistent (see [Subtitle::renumber]). New
+//! cues are copies of the cue they originate from, so format specific attributes like the SSA
+//! layer, style and actor, or the VTT settings are kept.
+
+use std::ops::{Range, RangeBounds};
+use time::Time;
+
+use crate::cue::{Cue, Subtitle};
+use crate::metrics::count_characters;
+use crate::util::{millis_to_time, time_to_millis};
+
+/// Splits the cue at `index` at `at` into two cues with the same text. Returns `false` and does
+/// nothing if `at` isn't strictly between the start and end of the cue.
+///
+/// # Panics
+///
+/// Panics if `index` is out of bounds.
+pub fn split_cue<S: Subtitle>(subtitle: &mut S, index: usize, at: Time) -> bool
+where
+    S::Cue: Clone,
+{
+    let cues = subtitle.cues_mut();
+    if at <= cues[index].start() || at >= cues[index].end() {
+        return false;
+    }
+
+    let mut second = cues[index].clone();
+    cues[index].set_end(at);
+    second.set_start(at);
+    cues.insert(index + 1, second);
+    subtitle.renumber();
+
+    true
+}
+
+/// Splits the cue at `index` into one cue per displayed line (separated by [Cue::LINE_BREAK]).
+/// The duration of the cue is distributed proportionally to the amount of characters in each
+/// line. Returns the number of cues the cue was split into.
+///
+/// Markup is not moved between lines, e.g. an italic tag opened in the first line and closed in
+/// the second one ends up unclosed in the first cue.
+///
+/// # Panics
+///
+/// Panics if `index` is out of bounds.
+pub fn split_cue_at_line_breaks<S: Subtitle>(subtitle: &mut S, index: usize) -> usize
+where
+    S::Cue: Clone,
+{
+    let cues = subtitle.cues_mut();
+    let cue = cues[index].clone();
+    let lines: Vec<&str> = cue.text().split(S::Cue::LINE_BREAK).collect();
+    if lines.len() < 2 {
+        return 1;
+    }
+
+    let parts: Vec<(S::Cue, usize)> = lines
+        .iter()
+        .map(|line| {
+            let mut part = cue.clone();
+            part.set_text(line.to_string());
+            let characters = part.plain_text().lines().map(count_characters).sum();
+            (part, characters)
+        })
+        .collect();
+    let total_characters = parts.iter().map(|(_, c)| c).sum::<usize>();
+    let start = time_to_millis(cue.start());
+    let duration = time_to_millis(cue.end()) - start;
+
+    // offset of the i-th line, distributed evenly if there are no characters to weigh by
+    let offset = |characters_before: usize, i: usize| {
+        if total_characters == 0 {
+            duration * i as i64 / lines.len() as i64
+        } else {
+            duration * characters_before as i64 / total_characters as i64
+        }
+    };
+
+    let mut characters_before = 0;
+    let mut split = vec![];
+    for (i, (mut part, characters)) in parts.into_iter().enumerate() {
+        part.set_start(millis_to_time(start + offset(characters_before, i)));
+        characters_before += characters;
+        if i + 1 < lines.len() {
+            part.set_end(millis_to_time(start + offset(characters_before, i + 1)))
+        } else {
+            part.set_end(cue.end())
+        }
+        split.push(part)
+    }
+
+    let count = split.len();
+    cues.splice(index..=index, split);
+    subtitle.renumber();
+
+    count
+}
+
+/// Joins the cues in `range` into the first one of them. The texts are separated by
+/// [Cue::LINE_BREAK] and the joined cue spans from the earliest start to the latest end. Does
+/// nothing if `range` contains less than two cues.
+///
+/// # Panics
+///
+/// Panics if `range` is out of bounds.
+pub fn join_cues<S: Subtitle>(subtitle: &mut S, range: Range<usize>) {
+    if range.len() < 2 {
+        return;
+    }
+    let cues = subtitle.cues_mut();
+
+    let first = range.start;
+    let joined: Vec<S::Cue> = cues.drain(first + 1..range.end).collect();
+    let mut text = cues[first].text().to_string();
+    let mut start = cues[first].start();
+    let mut end = cues[first].end();
+    for cue in &joined {
+        text.push_str(S::Cue::LINE_BREAK);
+        text.push_str(cue.text());
+        start = start.min(cue.start());
+        end = end.max(cue.end());
+    }
+    cues[first].set_text(text);
+    cues[first].set_start(start);
+    cues[first].set_end(end);
+    subtitle.renumber()
+}
+
+/// Inserts `cue` after all cues starting at or before it and returns its index. The cues are
+/// expected to be in chronological order.
+pub fn insert_cue<S: Subtitle>(subtitle: &mut S, cue: S::Cue) -> usize {
+    let cues = subtitle.cues_mut();
+    let index = cues.partition_point(|c| c.start() <= cue.start());
+    cues.insert(index, cue);
+    subtitle.renumber();
+
+    index
+}
+
+/// Removes the cues in `range` and returns them.
+///
+/// # Panics
+///
+/// Panics if `range` is out of bounds.
+pub fn delete_cues<S: Subtitle, R: RangeBounds<usize>>(subtitle: &mut S, range: R) -> Vec<S::Cue> {
+    let deleted = subtitle.cues_mut().drain(range).collect();
+    subtitle.renumber();
+
+    deleted
+}
@@ -0,0 +1,287 @@
+//! Implements style guide compliance checks.
+//!
+//! It describes the [StyleGuide] struct, which contains a configurable set of rules, and the
+//! [Violation] struct, which is returned for every rule a cue breaks.
+//!
+//! A [StyleGuide] can be deserialized with serde, so that the guideline of each client can live in
+//! a configuration file. Every rule is optional, missing fields are not checked:
+//! ```
+//! use rsubs_lib::{StyleGuide, ViolationKind, SRT};
+//!
+//! let guide: StyleGuide = serde_json::from_str(r#"{
+//!     "max_chars_per_line": 10,
+//!     "allowed_markup": ["i"]
+//! }"#).unwrap();
+//!
+//! let srt = SRT::parse("1\n00:00:00,000 --> 00:00:02,000\n<b>This line is too long</b>").unwrap();
+//! let violations = guide.check(&srt);
+//! assert_eq!(violations.len(), 2);
+//! assert!(matches!(violations[1].kind, ViolationKind::ForbiddenMarkup(_)));
+//! ```
+
+use serde::{Deserialize, Serialize};
+
+use crate::cue::{Cue, Subtitle};
+use crate::metrics::{count_characters, CueMetrics};
+use crate::util::{millis_between, FrameRate};
+
+/// A set of rules a subtitle must follow.
+#[derive(Clone, Debug, Default, PartialEq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct StyleGuide {
+    /// Name of the profile, only used for reporting.
+    pub name: Option<String>,
+    /// Maximum characters per second of a cue.
+    pub max_cps: Option<f64>,
+    /// Maximum words per minute of a cue.
+    pub max_wpm: Option<f64>,
+    /// Maximum characters of each displayed line.
+    pub max_chars_per_line: Option<usize>,
+    /// Maximum amount of displayed lines per cue.
+    pub max_lines: Option<usize>,
+    /// Minimum time a cue must be visible, in milliseconds.
+    pub min_duration_ms: Option<i64>,
+    /// Maximum time a cue may be visible, in milliseconds.
+    pub max_duration_ms: Option<i64>,
+    /// Minimum gap between two consecutive cues, in frames of [StyleGuide::frame_rate].
+    pub min_gap_frames: Option<u32>,
+    /// Frame rate used to convert [StyleGuide::min_gap_frames] to time.
+    pub frame_rate: FrameRate,
+    /// Names of the tags that may be used, e.g. `i` for `<i>` or `{\i1}`. If [None], all markup is
+    /// allowed.
+    pub allowed_markup: Option<Vec<String>>,
+    /// Characters that must not appear in the displayed text.
+    pub forbidden_characters: Vec<char>,
+}
+
+/// A rule broken by a cue.
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct Violation {
+    /// Index of the cue in [Subtitle::cues].
+    pub cue: usize,
+    pub kind: ViolationKind,
+}
+
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub enum ViolationKind {
+    MaxCps {
+        cps: f64,
+        max: f64,
+    },
+    MaxWpm {
+        wpm: f64,
+        max: f64,
+    },
+    /// `line` is the zero based index of the displayed line that is too long.
+    MaxCharsPerLine {
+        line: usize,
+        length: usize,
+        max: usize,
+    },
+    MaxLines {
+        lines: usize,
+        max: usize,
+    },
+    MinDuration {
+        duration_ms: i64,
+        min_ms: i64,
+    },
+    MaxDuration {
+        duration_ms: i64,
+        max_ms: i64,
+    },
+    /// The gap to the following cue is too short. A negative gap means the cues overlap.
+    MinGap {
+        gap_ms: i64,
+        min_ms: i64,
+    },
+    ForbiddenMarkup(String),
+    ForbiddenCharacter(char),
+}
+
+impl StyleGuide {
+    /// Approximation of the Netflix timed text style guide for English.
+    pub fn netflix() -> Self {
+        Self {
+            name: Some("Netflix".to_string()),
+            max_cps: Some(20.0),
+            max_chars_per_line: Some(42),
+            max_lines: Some(2),
+            min_duration_ms: Some(833),
+            max_duration_ms: Some(7000),
+            min_gap_frames: Some(2),
+            frame_rate: FrameRate::FPS_23_976,
+            allowed_markup: Some(vec!["i".to_string()]),
+            ..Default::default()
+        }
+    }
+
+    /// Approximation of the BBC subtitle guidelines.
+    pub fn bbc() -> Self {
+        Self {
+            name: Some("BBC".to_string()),
+            max_wpm: Some(180.0),
+            max_chars_per_line: Some(37),
+            max_lines: Some(2),
+            min_duration_ms: Some(1000),
+            max_duration_ms: Some(8000),
+            min_gap_frames: Some(1),
+            frame_rate: FrameRate::FPS_25,
+            allowed_markup: Some(vec!["i".to_string(), "c".to_string(), "font".to_string()]),
+            ..Default::default()
+        }
+    }
+
+    /// Approximation of the DCMP captioning key.
+    pub fn dcmp() -> Self {
+        Self {
+            name: Some("DCMP".to_string()),
+            max_wpm: Some(160.0),
+            max_chars_per_line: Some(32),
+            max_lines: Some(2),
+            min_duration_ms: Some(1500),
+            max_duration_ms: Some(6000),
+            min_gap_frames: Some(2),
+            frame_rate: FrameRate::FPS_29_97,
+            allowed_markup: Some(vec!["i".to_string()]),
+            ..Default::default()
+        }
+    }
+
+    /// Checks every cue of `subtitle` and returns all violations, ordered by cue.
+    pub fn check<S: Subtitle>(&self, subtitle: &S) -> Vec<Violation> {
+        let cues = subtitle.cues();
+        let mut violations = vec![];
+
+        for (i, cue) in cues.iter().enumerate() {
+            let mut push = |kind| violations.push(Violation { cue: i, kind });
+            let metrics = CueMetrics::new(cue, None);
+
+            if let Some(max) = self.max_cps {
+                if metrics.cps > max {
+                    push(ViolationKind::MaxCps {
+                        cps: metrics.cps,
+                        max,
+                    })
+                }
+            }
+            if let Some(max) = self.max_wpm {
+                if metrics.wpm > max {
+                    push(ViolationKind::MaxWpm {
+                        wpm: metrics.wpm,
+                        max,
+                    })
+                }
+            }
+            if let Some(max) = self.max_chars_per_line {
+                for (line, text) in cue.plain_text().lines().enumerate() {
+                    let length = count_characters(text);
+                    if length > max {
+                        push(ViolationKind::MaxCharsPerLine { line, length, max })
+                    }
+                }
+            }
+            if let Some(max) = self.max_lines {
+                if metrics.line_count > max {
+                    push(ViolationKind::MaxLines {
+                        lines: metrics.line_count,
+                        max,
+                    })
+                }
+            }
+            let duration_ms = millis_between(cue.start(), cue.end());
+            if let Some(min_ms) = self.min_duration_ms {
+                if duration_ms < min_ms {
+                    push(ViolationKind::MinDuration {
+                        duration_ms,
+                        min_ms,
+                    })
+                }
+            }
+            if let Some(max_ms) = self.max_duration_ms {
+                if duration_ms > max_ms {
+                    push(ViolationKind::MaxDuration {
+                        duration_ms,
+                        max_ms,
+                    })
+                }
+            }
+            if let (Some(frames), Some(next)) = (self.min_gap_frames, cues.get(i + 1)) {
+                let gap_ms = millis_between(cue.end(), next.start());
+                let min_ms = self.frame_rate.frames_to_millis(frames as i64);
+                if gap_ms < min_ms {
+                    push(ViolationKind::MinGap { gap_ms, min_ms })
+                }
+            }
+            if let Some(allowed) = &self.allowed_markup {
+                for tag in markup_tags(cue.text()) {
+                    if !allowed.contains(&tag) {
+                        push(ViolationKind::ForbiddenMarkup(tag))
+                    }
+                }
+            }
+            let mut found = vec![];
+            for c in cue.plain_text().chars() {
+                if self.forbidden_characters.contains(&c) && !found.contains(&c) {
+                    found.push(c);
+                    push(ViolationKind::ForbiddenCharacter(c))
+                }
+            }
+        }
+
+        violations
+    }
+}
+
+/// SSA override tags, longest first so that prefix matching picks the correct one.
+const SSA_TAGS: &[&str] = &[
+    "xbord", "ybord", "xshad", "yshad", "alpha", "iclip", "fscx", "fscy", "fade", "move", "clip",
+    "blur", "bord", "shad", "fad", "pos", "org", "fsp", "frx", "fry", "frz", "fax", "fay", "pbo",
+    "1c", "2c", "3c", "4c", "1a", "2a", "3a", "4a", "fn", "fs", "fe", "fr", "an", "kf", "ko", "be",
+    "a", "k", "K", "q", "r", "p", "b", "i", "u", "s", "c", "t",
+];
+
+/// Returns the names of all html-like tags (`<i>`, `</font>`) and SSA override tags (`{\i1}`)
+/// in `text`, without duplicates. Closing tags are reported by their name, VTT timestamp tags as
+/// `timestamp`.
+fn markup_tags(text: &str) -> Vec<String> {
+    let mut tags = vec![];
+    let mut add = |tag: String| {
+        if !tag.is_empty() && !tags.contains(&tag) {
+            tags.push(tag)
+        }
+    };
+
+    let mut rest = text;
+    while let Some(start) = rest.find('<') {
+        let Some(len) = rest[start..].find('>') else {
+            break;
+        };
+        let tag = rest[start + 1..start + len].trim_start_matches('/');
+        if tag.starts_with(|c: char| c.is_ascii_digit()) {
+            add("timestamp".to_string())
+        } else {
+            add(tag
+                .split(|c: char| c.is_whitespace() || c == '.')
+                .next()
+                .unwrap_or_default()
+                .to_lowercase())
+        }
+        rest = &rest[start + len..];
+    }
+
+    let mut rest = text;
+    while let Some(start) = rest.find('{') {
+        let Some(len) = rest[start..].find('}') else {
+            break;
+        };
+        for tag in rest[start + 1..start + len].split('\\').skip(1) {
+            if let Some(name) = SSA_TAGS.iter().find(|t| tag.starts_with(**t)) {
+                add(name.to_string())
+            }
+        }
+        rest = &rest[start + len..];
+    }
+
+    tags
+}
@@ -0,0 +1,302 @@
+//! Implements editing with undo and redo.
+//!
+//! It describes the [Editor] struct, which wraps a [Subtitle] and records every edit made through
+//! it as [EditOperation] in a [History]. Only the changed cues are recorded, so no snapshots of
+//! the whole document are needed. The [History] can be serialized and restored later with
+//! [Editor::with_history].
+
+use serde::{Deserialize, Serialize};
+use time::Time;
+
+use crate::cue::{Cue, Subtitle};
+
+/// A single recorded change of a subtitle.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub enum EditOperation<C> {
+    /// The start and / or end of a cue changed.
+    Timing {
+        cue: usize,
+        old_start: Time,
+        old_end: Time,
+        new_start: Time,
+        new_end: Time,
+    },
+    /// The text of a cue changed.
+    Text {
+        cue: usize,
+        old: String,
+        new: String,
+    },
+    /// The style of a cue changed.
+    Style {
+        cue: usize,
+        old: String,
+        new: String,
+    },
+    /// A cue was inserted at `index`.
+    Insert { index: usize, cue: C },
+    /// The cue at `index` was deleted.
+    Delete { index: usize, cue: C },
+}
+
+/// All edits that can be undone and redone. Every entry is a group of operations which is undone
+/// and redone at once.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub struct History<C> {
+    undo: Vec<Vec<EditOperation<C>>>,
+    redo: Vec<Vec<EditOperation<C>>>,
+    /// Depth of nested [Editor::begin_group] calls.
+    #[serde(skip)]
+    group_depth: usize,
+}
+
+impl<C> Default for History<C> {
+    fn default() -> Self {
+        Self {
+            undo: vec![],
+            redo: vec![],
+            group_depth: 0,
+        }
+    }
+}
+
+impl<C> History<C> {
+    /// The groups of operations that can be undone, oldest first.
+    pub fn undo_stack(&self) -> &[Vec<EditOperation<C>>] {
+        &self.undo
+    }
+
+    /// The groups of operations that can be redone, most recently undone last.
+    pub fn redo_stack(&self) -> &[Vec<EditOperation<C>>] {
+        &self.redo
+    }
+
+    /// Removes all recorded operations.
+    pub fn clear(&mut self) {
+        self.undo.clear();
+        self.redo.clear()
+    }
+
+    fn record(&mut self, operation: EditOperation<C>) {
+        match self.undo.last_mut() {
+            // the group was started by begin_group
+            Some(group) if self.group_depth > 0 => group.push(operation),
+            _ => self.undo.push(vec![operation]),
+        }
+        self.redo.clear()
+    }
+}
+
+/// Wraps a subtitle and records all edits made through it.
+///
+/// Changes made directly to the subtitle are not recorded, so the history must not be used after
+/// editing the subtitle some other way.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Editor<S: Subtitle> {
+    subtitle: S,
+    history: History<S::Cue>,
+}
+
+impl<S: Subtitle> Editor<S>
+where
+    S::Cue: Clone,
+{
+    /// Wraps `subtitle` with an empty history.
+    pub fn new(subtitle: S) -> Self {
+        Self::with_history(subtitle, History::default())
+    }
+
+    /// Wraps `subtitle` with a previously recorded `history`, which must belong to the subtitle in
+    /// its current state.
+    pub fn with_history(subtitle: S, history: History<S::Cue>) -> Self {
+        Self { subtitle, history }
+    }
+
+    pub fn subtitle(&self) -> &S {
+        &self.subtitle
+    }
+
+    pub fn history(&self) -> &History<S::Cue> {
+        &self.history
+    }
+
+    /// Returns the subtitle and its history.
+    pub fn into_parts(self) -> (S, History<S::Cue>) {
+        (self.subtitle, self.history)
+    }
+
+    /// Sets the start and end of the cue at `index`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds.
+    pub fn set_timing(&mut self, index: usize, start: Time, end: Time) {
+        let cue = &self.subtitle.cues()[index];
+        self.apply(EditOperation::Timing {
+            cue: index,
+            old_start: cue.start(),
+            old_end: cue.end(),
+            new_start: start,
+            new_end: end,
+        })
+    }
+
+    /// Replaces the text of the cue at `index`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds.
+    pub fn set_text(&mut self, index: usize, text: String) {
+        let old = self.subtitle.cues()[index].text().to_string();
+        self.apply(EditOperation::Text {
+            cue: index,
+            old,
+            new: text,
+        })
+    }
+
+    /// Changes the style of the cue at `index`. Returns `false` and does nothing if the format has
+    /// no per-cue styles (see [Cue::style]).
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds.
+    pub fn set_style(&mut self, index: usize, style: String) -> bool {
+        let Some(old) = self.subtitle.cues()[index].style().map(|s| s.to_string()) else {
+            return false;
+        };
+        self.apply(EditOperation::Style {
+            cue: index,
+            old,
+            new: style,
+        });
+        true
+    }
+
+    /// Inserts `cue` at `index`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is greater than the amount of cues.
+    pub fn insert(&mut self, index: usize, cue: S::Cue) {
+        self.apply(EditOperation::Insert { index, cue })
+    }
+
+    /// Deletes the cue at `index` and returns it.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds.
+    pub fn delete(&mut self, index: usize) -> S::Cue {
+        let cue = self.subtitle.cues()[index].clone();
+        self.apply(EditOperation::Delete {
+            index,
+            cue: cue.clone(),
+        });
+        cue
+    }
+
+    /// Starts a group. All edits until the matching [Editor::end_group] are undone and redone at
+    /// once. Groups can be nested, in which case the outermost group is used.
+    pub fn begin_group(&mut self) {
+        if self.history.group_depth == 0 {
+            self.history.undo.push(vec![])
+        }
+        self.history.group_depth += 1
+    }
+
+    /// Ends the group started by [Editor::begin_group].
+    pub fn end_group(&mut self) {
+        self.history.group_depth = self.history.group_depth.saturating_sub(1);
+        if self.history.group_depth == 0 && self.history.undo.last().is_some_and(|g| g.is_empty()) {
+            self.history.undo.pop();
+        }
+    }
+
+    pub fn can_undo(&self) -> bool {
+        self.history.undo.iter().any(|g| !g.is_empty())
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.history.redo.is_empty()
+    }
+
+    /// Reverts the last group of edits, closing any open group. Returns `false` if there is
+    /// nothing to undo.
+    pub fn undo(&mut self) -> bool {
+        // an empty group might be open
+        while self.history.undo.last().is_some_and(|g| g.is_empty()) {
+            self.history.undo.pop();
+        }
+        self.history.group_depth = 0;
+        let Some(group) = self.history.undo.pop() else {
+            return false;
+        };
+
+        for operation in group.iter().rev() {
+            execute(&mut self.subtitle, operation, true)
+        }
+        self.subtitle.renumber();
+        self.history.redo.push(group);
+        true
+    }
+
+    /// Reapplies the last undone group of edits. Returns `false` if there is nothing to redo.
+    pub fn redo(&mut self) -> bool {
+        let Some(group) = self.history.redo.pop() else {
+            return false;
+        };
+
+        for operation in &group {
+            execute(&mut self.subtitle, operation, false)
+        }
+        self.subtitle.renumber();
+        self.history.undo.push(group);
+        true
+    }
+
+    fn apply(&mut self, operation: EditOperation<S::Cue>) {
+        execute(&mut self.subtitle, &operation, false);
+        self.subtitle.renumber();
+        self.history.record(operation)
+    }
+}
+
+/// Applies `operation` to `subtitle`, or reverts it if `revert` is set.
+fn execute<S: Subtitle>(subtitle: &mut S, operation: &EditOperation<S::Cue>, revert: bool)
+where
+    S::Cue: Clone,
+{
+    let cues = subtitle.cues_mut();
+    match operation {
+        EditOperation::Timing {
+            cue,
+            old_start,
+            old_end,
+            new_start,
+            new_end,
+        } => {
+            let (start, end) = if revert {
+                (old_start, old_end)
+            } else {
+                (new_start, new_end)
+            };
+            cues[*cue].set_start(*start);
+            cues[*cue].set_end(*end)
+        }
+        EditOperation::Text { cue, old, new } => {
+            cues[*cue].set_text(if revert { old } else { new }.clone())
+        }
+        EditOperation::Style { cue, old, new } => {
+            cues[*cue].set_style(if revert { old } else { new }.clone())
+        }
+        EditOperation::Insert { index, cue } | EditOperation::Delete { index, cue } => {
+            let insert = matches!(operation, EditOperation::Insert { .. }) != revert;
+            if insert {
+                cues.insert(*index, cue.clone())
+            } else {
+                cues.remove(*index);
+            }
+        }
+    }
+}
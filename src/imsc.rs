@@ -0,0 +1,1049 @@
+//! Implements exporting to the IMSC 1.1 Text Profile of TTML and checking documents against it.
+//!
+//! [SSA::to_imsc], [VTT::to_imsc] and [SRT::to_imsc] create [TTML] documents which only use
+//! constructs of the profile. Regions are derived from the alignment, margins and positions of the
+//! cues, so that cues with the same layout share a region. [TTML::validate_imsc] reports every
+//! construct of a document which is not allowed in the profile as [IMSCViolation].
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use crate::srt::SRT;
+use crate::ssa::{SSAEventLineType, SSAStyle, SSA};
+use crate::ttml::{attribute, attributes, escape_text, with_fragment};
+use crate::ttml::{TTMLParagraph, TTMLRegion, TTMLStyle, TTML};
+use crate::util::{Alignment, Color, WHITE};
+use crate::vtt::{VTTRegion, VTT};
+
+/// Profile designator of the IMSC 1.0.1 Text Profile, used in `ttp:profile`.
+pub const IMSC1_TEXT_PROFILE: &str = "http://www.w3.org/ns/ttml/profile/imsc1/text";
+/// Profile designator of the IMSC 1.1 Text Profile, used in `ttp:contentProfiles`.
+pub const IMSC1_1_TEXT_PROFILE: &str = "http://www.w3.org/ns/ttml/profile/imsc1.1/text";
+
+/// Height of generated regions in percent of the root container, enough for two lines of text
+/// in the default font size.
+const REGION_HEIGHT: f32 = 20.0;
+/// Height of a line in VTT, in percent of the video height.
+const VTT_LINE_HEIGHT: f32 = 5.33;
+
+/// A construct not allowed in the IMSC 1.1 Text Profile.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub struct IMSCViolation {
+    pub location: IMSCLocation,
+    pub kind: IMSCViolationKind,
+}
+
+/// Where an [IMSCViolation] was found.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub enum IMSCLocation {
+    /// The `<tt>` element.
+    Document,
+    /// The `<style>` with the given id.
+    Style(String),
+    /// The `<region>` with the given id.
+    Region(String),
+    /// The paragraph with the given index in [TTML::paragraphs], including its `<span>`s.
+    Paragraph(usize),
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub enum IMSCViolationKind {
+    /// Neither `ttp:contentProfiles` nor `ttp:profile` designate an IMSC 1 Text Profile.
+    MissingProfile,
+    /// The `<tt>` element has no `xml:lang`.
+    MissingLanguage,
+    /// The attribute, or this value of it, is not allowed in the profile.
+    ProhibitedAttribute { attribute: String, value: String },
+    /// The length uses `em`, or `px` while the root container has no `tts:extent` in pixels.
+    InvalidLength { attribute: String, value: String },
+    /// A style or region is referenced, but doesn't exist.
+    UnknownReference(String),
+    /// The region doesn't lie entirely within the root container.
+    RegionOutsideRootContainer,
+    /// More regions than the allowed four are presented at the same time.
+    TooManyRegions(usize),
+    /// The regions with the given ids overlap and are presented at the same time.
+    OverlappingRegions(String, String),
+}
+
+impl TTML {
+    /// Checks the document against the IMSC 1.1 Text Profile and returns all constructs which
+    /// are not allowed in it.
+    pub fn validate_imsc(&self) -> Vec<IMSCViolation> {
+        let mut violations = vec![];
+        let mut report = |location: &IMSCLocation, kind| {
+            violations.push(IMSCViolation {
+                location: location.clone(),
+                kind,
+            })
+        };
+
+        let document = IMSCLocation::Document;
+        let designates = |name, profiles: &[&str]| {
+            attribute(&self.attributes, name)
+                .is_some_and(|p| p.split_whitespace().any(|p| profiles.contains(&p)))
+        };
+        if !designates(
+            "ttp:contentProfiles",
+            &[IMSC1_TEXT_PROFILE, IMSC1_1_TEXT_PROFILE],
+        ) && !designates("ttp:profile", &[IMSC1_TEXT_PROFILE])
+        {
+            report(&document, IMSCViolationKind::MissingProfile)
+        }
+        if self.lang.is_none() {
+            report(&document, IMSCViolationKind::MissingLanguage)
+        }
+
+        // pixels may only be used if the root container has a size in pixels
+        let root_extent = attribute(&self.attributes, "tts:extent");
+        let pixels = root_extent.is_some_and(|e| {
+            let lengths: Vec<&str> = e.split_whitespace().collect();
+            lengths.len() == 2
+                && lengths.iter().all(|l| {
+                    l.strip_suffix("px")
+                        .is_some_and(|l| l.parse::<f32>().is_ok())
+                })
+        });
+        if let (Some(extent), false) = (root_extent, pixels) {
+            report(
+                &document,
+                IMSCViolationKind::InvalidLength {
+                    attribute: "tts:extent".to_string(),
+                    value: extent.to_string(),
+                },
+            )
+        }
+        let root_attributes: Vec<(String, String)> = self
+            .attributes
+            .iter()
+            .filter(|(name, _)| name != "tts:extent")
+            .cloned()
+            .collect();
+        for kind in check_attributes(&root_attributes, pixels) {
+            report(&document, kind)
+        }
+
+        let style_exists = |id: &String| self.styles.iter().any(|s| &s.id == id);
+        for style in &self.styles {
+            let location = IMSCLocation::Style(style.id.clone());
+            for kind in check_attributes(&style.attributes, pixels) {
+                report(&location, kind)
+            }
+            for id in style.style.iter().filter(|id| !style_exists(id)) {
+                report(&location, IMSCViolationKind::UnknownReference(id.clone()))
+            }
+        }
+
+        for region in &self.regions {
+            let location = IMSCLocation::Region(region.id.clone());
+            for kind in check_attributes(&region.attributes, pixels) {
+                report(&location, kind)
+            }
+            for id in region.style.iter().filter(|id| !style_exists(id)) {
+                report(&location, IMSCViolationKind::UnknownReference(id.clone()))
+            }
+            // a small tolerance for rounded percentages
+            let (x, y, width, height) = self.region_rectangle(region);
+            if x < -0.01 || y < -0.01 || x + width > 100.01 || y + height > 100.01 {
+                report(&location, IMSCViolationKind::RegionOutsideRootContainer)
+            }
+        }
+
+        for (i, paragraph) in self.paragraphs.iter().enumerate() {
+            let location = IMSCLocation::Paragraph(i);
+            let mut references = paragraph.style.clone();
+            let mut all_attributes = paragraph.attributes.clone();
+            with_fragment(&paragraph.text, |node| {
+                for span in node.descendants().filter(|n| n.tag_name().name() == "span") {
+                    for (name, value) in attributes(span) {
+                        if name == "style" {
+                            references.extend(value.split_whitespace().map(str::to_string))
+                        } else {
+                            all_attributes.push((name, value))
+                        }
+                    }
+                }
+            });
+            for kind in check_attributes(&all_attributes, pixels) {
+                report(&location, kind)
+            }
+            for id in references.iter().filter(|id| !style_exists(id)) {
+                report(&location, IMSCViolationKind::UnknownReference(id.clone()))
+            }
+            if let Some(region) = paragraph
+                .region
+                .as_ref()
+                .filter(|id| !self.regions.iter().any(|r| &r.id == *id))
+            {
+                report(
+                    &location,
+                    IMSCViolationKind::UnknownReference(region.clone()),
+                )
+            }
+        }
+
+        // the regions presented when each paragraph begins
+        let mut overlaps: Vec<(&String, &String)> = vec![];
+        for (i, paragraph) in self.paragraphs.iter().enumerate() {
+            let Some(region) = &paragraph.region else {
+                continue;
+            };
+            let mut presented: Vec<&String> = vec![];
+            for other in self.paragraphs.iter().filter(|p| {
+                p.begin < p.end && p.begin <= paragraph.begin && paragraph.begin < p.end
+            }) {
+                if let Some(other) = other.region.as_ref().filter(|r| !presented.contains(r)) {
+                    presented.push(other)
+                }
+            }
+            if !presented.contains(&region) {
+                continue;
+            }
+
+            let location = IMSCLocation::Paragraph(i);
+            if presented.len() > 4 {
+                report(
+                    &location,
+                    IMSCViolationKind::TooManyRegions(presented.len()),
+                )
+            }
+            let Some(rectangle) = self.regions.iter().find(|r| &r.id == region) else {
+                continue;
+            };
+            let rectangle = self.region_rectangle(rectangle);
+            for other in presented.into_iter().filter(|r| *r != region) {
+                if overlaps.contains(&(region, other)) || overlaps.contains(&(other, region)) {
+                    continue;
+                }
+                let Some(other_rectangle) = self.regions.iter().find(|r| &r.id == other) else {
+                    continue;
+                };
+                let (x, y, width, height) = rectangle;
+                let (other_x, other_y, other_width, other_height) =
+                    self.region_rectangle(other_rectangle);
+                if x + 0.01 < other_x + other_width
+                    && other_x + 0.01 < x + width
+                    && y + 0.01 < other_y + other_height
+                    && other_y + 0.01 < y + height
+                {
+                    overlaps.push((region, other));
+                    report(
+                        &location,
+                        IMSCViolationKind::OverlappingRegions(region.clone(), other.clone()),
+                    )
+                }
+            }
+        }
+
+        violations
+    }
+
+    /// Returns origin and extent of `region` in percent of the root container.
+    fn region_rectangle(&self, region: &TTMLRegion) -> (f32, f32, f32, f32) {
+        let layout = self.resolve_style(&region.style, &region.attributes);
+        let (x, y) = layout
+            .get("tts:origin")
+            .and_then(|o| self.percentages(o))
+            .unwrap_or((0.0, 0.0));
+        let (width, height) = layout
+            .get("tts:extent")
+            .and_then(|e| self.percentages(e))
+            .unwrap_or((100.0, 100.0));
+        (x, y, width, height)
+    }
+}
+
+/// Checks the values of `attributes`. `pixels` tells if the root container has a size in pixels.
+fn check_attributes(attributes: &[(String, String)], pixels: bool) -> Vec<IMSCViolationKind> {
+    let mut violations = vec![];
+    for (name, value) in attributes {
+        let lengths: Vec<&str> = value
+            .split_whitespace()
+            .filter(|v| v.starts_with(|c: char| c.is_ascii_digit() || c == '.' || c == '-'))
+            .collect();
+        let prohibited = match name.as_str() {
+            "ttp:timeBase" => value != "media",
+            "tts:dynamicFlow" => true,
+            // anamorphic font sizes
+            "tts:fontSize" => lengths.len() > 1,
+            // blurred outlines
+            "tts:textOutline" => lengths.len() > 1,
+            _ => false,
+        };
+        if prohibited {
+            violations.push(IMSCViolationKind::ProhibitedAttribute {
+                attribute: name.clone(),
+                value: value.clone(),
+            });
+            continue;
+        }
+
+        let length_attribute = matches!(
+            name.as_str(),
+            "tts:origin"
+                | "tts:extent"
+                | "tts:fontSize"
+                | "tts:lineHeight"
+                | "tts:padding"
+                | "tts:textOutline"
+        );
+        if length_attribute
+            && lengths
+                .iter()
+                .any(|l| l.ends_with("em") || (!pixels && l.ends_with("px")))
+        {
+            violations.push(IMSCViolationKind::InvalidLength {
+                attribute: name.clone(),
+                value: value.clone(),
+            })
+        }
+    }
+    violations
+}
+
+impl SSA {
+    /// Converts to an IMSC 1.1 Text Profile [TTML] document with the language `lang`.
+    ///
+    /// Styles are converted to `<style>`s. Each event is placed in a region derived from its
+    /// alignment (including `\an` overrides) and margins, or from its `\pos` override. Italic,
+    /// bold, underline, strikeout and color overrides are converted to `<span>`s, all other
+    /// override tags are dropped. Comments are skipped.
+    pub fn to_imsc(&self, lang: &str) -> TTML {
+        let width = self.info.play_res_x.unwrap_or(384) as f32;
+        let height = self.info.play_res_y.unwrap_or(288) as f32;
+
+        let mut ids: HashMap<&str, String> = HashMap::new();
+        let mut styles = vec![];
+        for style in &self.styles {
+            let id = unique_id(&style.name, |id| {
+                styles.iter().any(|s: &TTMLStyle| s.id == id)
+            });
+            ids.insert(&style.name, id.clone());
+            styles.push(TTMLStyle {
+                id,
+                style: vec![],
+                attributes: imsc_style(style, height),
+            })
+        }
+
+        let layout_regex =
+            Regex::new(r"\\an([1-9])|\\pos\(\s*([-\d.]+)\s*,\s*([-\d.]+)\s*\)").unwrap();
+        let mut layout = Layout::default();
+        let mut paragraphs = vec![];
+        for event in &self.events {
+            if event.line_type == SSAEventLineType::Comment {
+                continue;
+            }
+            let style = self.styles.iter().find(|s| s.name == event.style);
+            let style_alignment = style.map_or(Alignment::BottomCenter, |s| s.alignment);
+
+            let mut alignment = style_alignment;
+            let mut position = None;
+            for block in override_blocks(&event.text) {
+                for c in layout_regex.captures_iter(block) {
+                    if let Some(an) = c.get(1) {
+                        alignment = Alignment::infer_from_str(an.as_str()).unwrap_or(alignment)
+                    } else if let (Ok(x), Ok(y)) = (c[2].parse::<f32>(), c[3].parse::<f32>()) {
+                        position = Some((x / width * 100.0, y / height * 100.0))
+                    }
+                }
+            }
+            let vertical = (alignment as u8 - 1) / 3;
+            let horizontal = (alignment as u8 - 1) % 3;
+
+            let (x, y) = match position {
+                Some((x, y)) => (
+                    match horizontal {
+                        0 => (x, 100.0),
+                        1 => (x - x.min(100.0 - x), x + x.min(100.0 - x)),
+                        _ => (0.0, x),
+                    },
+                    match vertical {
+                        0 => (y - REGION_HEIGHT, y),
+                        1 => (y - REGION_HEIGHT / 2.0, y + REGION_HEIGHT / 2.0),
+                        _ => (y, y + REGION_HEIGHT),
+                    },
+                ),
+                None => {
+                    // margins of 0 mean that the margins of the style are used
+                    let margin = |event: f32, style: Option<f32>| {
+                        if event != 0.0 {
+                            event
+                        } else {
+                            style.unwrap_or_default()
+                        }
+                    };
+                    let left = margin(event.margin_l, style.map(|s| s.margin_l)) / width * 100.0;
+                    let right = margin(event.margin_r, style.map(|s| s.margin_r)) / width * 100.0;
+                    let vertical_margin =
+                        margin(event.margin_v, style.map(|s| s.margin_v)) / height * 100.0;
+                    (
+                        (left, 100.0 - right),
+                        match vertical {
+                            0 => (
+                                100.0 - vertical_margin - REGION_HEIGHT,
+                                100.0 - vertical_margin,
+                            ),
+                            1 => (50.0 - REGION_HEIGHT / 2.0, 50.0 + REGION_HEIGHT / 2.0),
+                            _ => (vertical_margin, vertical_margin + REGION_HEIGHT),
+                        },
+                    )
+                }
+            };
+
+            let mut paragraph_attributes = vec![];
+            if (style_alignment as u8 - 1) % 3 != horizontal {
+                paragraph_attributes.push((
+                    "tts:textAlign".to_string(),
+                    text_align(horizontal).to_string(),
+                ))
+            }
+            let base = style.map(Run::from_ssa_style).unwrap_or_default();
+
+            paragraphs.push(TTMLParagraph {
+                begin: event.start,
+                end: event.end,
+                region: Some(layout.region(None, x, y, vertical)),
+                style: style
+                    .map(|s| vec![ids[s.name.as_str()].clone()])
+                    .unwrap_or_default(),
+                attributes: paragraph_attributes,
+                text: markup(ssa_runs(&event.text, &base, &self.styles), &base),
+                ..Default::default()
+            })
+        }
+
+        imsc_document(lang, self.info.title.clone(), styles, layout, paragraphs)
+    }
+}
+
+impl VTT {
+    /// Converts to an IMSC 1.1 Text Profile [TTML] document with the language `lang`.
+    ///
+    /// The `::cue` styles are converted to a `default` style used by all cues, `::cue(.class)`
+    /// styles to `<style>`s used by `<c.class>` tags. [VTTRegion]s are converted to regions, all
+    /// other cues are placed in regions derived from their `line`, `position`, `size` and `align`
+    /// settings. `<i>`, `<b>`, `<u>`, `<c>` and `<font color="...">` tags are converted to
+    /// `<span>`s, all other tags are dropped.
+    pub fn to_imsc(&self, lang: &str) -> TTML {
+        let mut styles: Vec<TTMLStyle> = vec![];
+        let mut classes = HashMap::new();
+        for style in &self.styles {
+            let id = match &style.selector {
+                None => "default".to_string(),
+                Some(selector) => match selector.strip_prefix('.') {
+                    Some(class) if !class.contains([' ', '.', ':', '[']) => {
+                        let id = classes.get(class).cloned().unwrap_or_else(|| {
+                            unique_id(class, |id| {
+                                id == "default" || styles.iter().any(|s| s.id == id)
+                            })
+                        });
+                        classes.insert(class.to_string(), id.clone());
+                        id
+                    }
+                    _ => continue,
+                },
+            };
+            let attributes = css_style(&style.entries);
+            match styles.iter_mut().find(|s| s.id == id) {
+                Some(existing) => existing.attributes.extend(attributes),
+                None => styles.push(TTMLStyle {
+                    id,
+                    style: vec![],
+                    attributes,
+                }),
+            }
+        }
+        let default_style: Vec<String> = styles
+            .iter()
+            .find(|s| s.id == "default")
+            .map(|s| vec![s.id.clone()])
+            .unwrap_or_default();
+
+        let mut layout = Layout::default();
+        let mut region_ids = HashMap::new();
+        for region in &self.regions {
+            let Some(id) = &region.id else { continue };
+            let (x, y) = vtt_region_bounds(region);
+            region_ids.insert(id.clone(), layout.region(Some(id), x, y, 0));
+        }
+
+        let mut paragraphs = vec![];
+        for line in &self.lines {
+            let setting = |name: &str| {
+                line.settings
+                    .get(name)
+                    .cloned()
+                    .flatten()
+                    .map(|s| s.split(',').next().unwrap_or_default().trim().to_string())
+            };
+            let align = setting("align");
+            let horizontal = match align.as_deref() {
+                Some("start") | Some("left") => 0,
+                Some("end") | Some("right") => 2,
+                _ => 1,
+            };
+
+            let region = match setting("region").and_then(|r| region_ids.get(&r)) {
+                Some(id) => id.clone(),
+                None => {
+                    let percentage = |name| setting(name).and_then(|s| parse_percentage(&s));
+                    let size = percentage("size").unwrap_or(100.0);
+                    let position = percentage("position").unwrap_or(match horizontal {
+                        0 => 0.0,
+                        1 => 50.0,
+                        _ => 100.0,
+                    });
+                    let x = match horizontal {
+                        0 => (position, position + size),
+                        1 => (position - size / 2.0, position + size / 2.0),
+                        _ => (position - size, position),
+                    };
+                    let (y, vertical) = match setting("line") {
+                        Some(line) if line.ends_with('%') => {
+                            let y = parse_percentage(&line).unwrap_or(100.0);
+                            ((y, y + REGION_HEIGHT), 2)
+                        }
+                        Some(line) if line.parse::<i32>().is_ok_and(|l| l >= 0) => {
+                            let y = line.parse::<i32>().unwrap() as f32 * VTT_LINE_HEIGHT;
+                            ((y, y + REGION_HEIGHT), 2)
+                        }
+                        Some(line) if line.parse::<i32>().is_ok() => {
+                            let y =
+                                100.0 + (line.parse::<i32>().unwrap() + 1) as f32 * VTT_LINE_HEIGHT;
+                            ((y - REGION_HEIGHT, y), 0)
+                        }
+                        _ => ((95.0 - REGION_HEIGHT, 95.0), 0),
+                    };
+                    layout.region(None, x, y, vertical)
+                }
+            };
+
+            paragraphs.push(TTMLParagraph {
+                id: line.identifier.as_ref().map(|i| unique_id(i, |_| false)),
+                begin: line.start,
+                end: line.end,
+                region: Some(region),
+                style: default_style.clone(),
+                attributes: vec![(
+                    "tts:textAlign".to_string(),
+                    align.unwrap_or("center".to_string()),
+                )],
+                text: vtt_markup(&line.text, &classes),
+            })
+        }
+
+        // identifiers must be unique in the document
+        let mut seen = vec![];
+        for paragraph in &mut paragraphs {
+            if let Some(id) = &paragraph.id {
+                if seen.contains(id)
+                    || styles.iter().any(|s| &s.id == id)
+                    || layout.regions.iter().any(|r| &r.id == id)
+                {
+                    paragraph.id = None
+                } else {
+                    seen.push(id.clone())
+                }
+            }
+        }
+
+        imsc_document(lang, None, styles, layout, paragraphs)
+    }
+}
+
+impl SRT {
+    /// Converts to an IMSC 1.1 Text Profile [TTML] document with the language `lang`. See
+    /// [VTT::to_imsc].
+    pub fn to_imsc(&self, lang: &str) -> TTML {
+        self.to_vtt().to_imsc(lang)
+    }
+}
+
+fn imsc_document(
+    lang: &str,
+    title: Option<String>,
+    styles: Vec<TTMLStyle>,
+    layout: Layout,
+    paragraphs: Vec<TTMLParagraph>,
+) -> TTML {
+    TTML {
+        lang: Some(lang.to_string()),
+        attributes: vec![(
+            "ttp:contentProfiles".to_string(),
+            IMSC1_1_TEXT_PROFILE.to_string(),
+        )],
+        title,
+        styles,
+        regions: layout.regions,
+        paragraphs,
+        ..Default::default()
+    }
+}
+
+/// The regions of an export. Cues with the same layout share a region.
+#[derive(Default)]
+struct Layout {
+    regions: Vec<TTMLRegion>,
+}
+
+impl Layout {
+    /// Returns the id of the region spanning `x` and `y` (start and end in percent of the root
+    /// container), with text aligned to its bottom (`0`), middle (`1`) or top (`2`). Regions with
+    /// an `id` are always created, all other ones reused if possible.
+    fn region(&mut self, id: Option<&str>, x: (f32, f32), y: (f32, f32), vertical: u8) -> String {
+        let clamp = |(start, end): (f32, f32)| {
+            let start = start.clamp(0.0, 100.0);
+            (start, end.clamp(start, 100.0))
+        };
+        let ((left, right), (top, bottom)) = (clamp(x), clamp(y));
+        let (name, display_align) = match vertical {
+            0 => ("bottom", "after"),
+            1 => ("middle", "center"),
+            _ => ("top", "before"),
+        };
+        let attributes = vec![
+            (
+                "tts:origin".to_string(),
+                format!("{} {}", percent(left), percent(top)),
+            ),
+            (
+                "tts:extent".to_string(),
+                format!("{} {}", percent(right - left), percent(bottom - top)),
+            ),
+            ("tts:displayAlign".to_string(), display_align.to_string()),
+        ];
+
+        if id.is_none() {
+            if let Some(region) = self.regions.iter().find(|r| r.attributes == attributes) {
+                return region.id.clone();
+            }
+        }
+        let id = unique_id(id.unwrap_or(name), |id| {
+            self.regions.iter().any(|r| r.id == id)
+        });
+        self.regions.push(TTMLRegion {
+            id: id.clone(),
+            style: vec![],
+            attributes,
+        });
+        id
+    }
+}
+
+/// Returns the horizontal and vertical bounds of a VTT region. Its lines are stacked from the
+/// bottom.
+fn vtt_region_bounds(region: &VTTRegion) -> ((f32, f32), (f32, f32)) {
+    let width = region.width.unwrap_or(100.0);
+    let height = region.lines.unwrap_or(3) as f32 * VTT_LINE_HEIGHT;
+    let (anchor_x, anchor_y) = region.region_anchor.unwrap_or((0.0, 100.0));
+    let (viewport_x, viewport_y) = region.viewport_anchor.unwrap_or((0.0, 100.0));
+    let x = viewport_x - width * anchor_x / 100.0;
+    let y = viewport_y - height * anchor_y / 100.0;
+    ((x, x + width), (y, y + height))
+}
+
+/// Turns `name` into a valid `xml:id` which isn't `taken`.
+fn unique_id(name: &str, taken: impl Fn(&str) -> bool) -> String {
+    let mut id: String = name
+        .chars()
+        .map(|c| {
+            if c.is_alphanumeric() || matches!(c, '-' | '_' | '.') {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    if !id.starts_with(|c: char| c.is_alphabetic() || c == '_') {
+        id.insert(0, '_')
+    }
+    let mut unique = id.clone();
+    let mut n = 1;
+    while taken(&unique) {
+        n += 1;
+        unique = format!("{id}{n}")
+    }
+    unique
+}
+
+fn percent(value: f32) -> String {
+    format!("{}%", (value * 100.0).round() / 100.0)
+}
+
+fn parse_percentage(value: &str) -> Option<f32> {
+    value.trim().strip_suffix('%')?.parse().ok()
+}
+
+fn text_align(horizontal: u8) -> &'static str {
+    match horizontal {
+        0 => "left",
+        1 => "center",
+        _ => "right",
+    }
+}
+
+/// Converts a color of a SSA file. Like [Color::to_ssa_string], an alpha of `255` is treated as
+/// opaque, all other values are the inverted SSA transparency.
+pub(crate) fn ssa_color(color: Color) -> Color {
+    if color.a == 255 {
+        color
+    } else {
+        Color::new(color.r, color.g, color.b, 255 - color.a)
+    }
+}
+
+/// Returns the styling attributes of `style`. Font sizes are converted to percent of the default
+/// font size of one cell (a 15th of the height).
+fn imsc_style(style: &SSAStyle, height: f32) -> Vec<(String, String)> {
+    let mut attributes = vec![
+        ("tts:fontFamily".to_string(), style.fontname.clone()),
+        (
+            "tts:fontSize".to_string(),
+            percent(style.fontsize / height * 100.0 * 15.0),
+        ),
+    ];
+    if let Some(color) = style.primary_color {
+        attributes.push(("tts:color".to_string(), ssa_color(color).to_ttml_string()))
+    }
+    // border style 3 draws an opaque box instead of an outline
+    if style.border_style == 3 {
+        if let Some(color) = style.back_color {
+            attributes.push((
+                "tts:backgroundColor".to_string(),
+                ssa_color(color).to_ttml_string(),
+            ))
+        }
+    } else if style.outline > 0.0 && style.fontsize > 0.0 {
+        let color = style
+            .outline_color
+            .map(ssa_color)
+            .unwrap_or(Color::new(0, 0, 0, 255));
+        attributes.push((
+            "tts:textOutline".to_string(),
+            format!(
+                "{} {}",
+                color.to_ttml_string(),
+                // the profile allows at most 10% of the font size
+                percent((style.outline / style.fontsize * 100.0).min(10.0))
+            ),
+        ))
+    }
+    if style.italic {
+        attributes.push(("tts:fontStyle".to_string(), "italic".to_string()))
+    }
+    if style.bold {
+        attributes.push(("tts:fontWeight".to_string(), "bold".to_string()))
+    }
+    let decoration = match (style.underline, style.strikeout) {
+        (true, true) => Some("underline lineThrough"),
+        (true, false) => Some("underline"),
+        (false, true) => Some("lineThrough"),
+        (false, false) => None,
+    };
+    if let Some(decoration) = decoration {
+        attributes.push(("tts:textDecoration".to_string(), decoration.to_string()))
+    }
+    attributes.push((
+        "tts:textAlign".to_string(),
+        text_align((style.alignment as u8 - 1) % 3).to_string(),
+    ));
+    attributes
+}
+
+/// Converts CSS declarations of a [VTTStyle](crate::VTTStyle) to styling attributes.
+fn css_style(entries: &HashMap<String, String>) -> Vec<(String, String)> {
+    // sorted, so that the output is stable
+    let mut entries: Vec<(&String, &String)> = entries.iter().collect();
+    entries.sort();
+
+    let mut attributes = vec![];
+    let mut decoration = vec![];
+    for (name, value) in entries {
+        let value = value.trim();
+        let attribute = match name.as_str() {
+            "color" => Color::from_vtt(value)
+                .ok()
+                .map(|c| ("tts:color", c.to_ttml_string())),
+            "background-color" | "background" => Color::from_vtt(value)
+                .ok()
+                .map(|c| ("tts:backgroundColor", c.to_ttml_string())),
+            "font-family" => Some(("tts:fontFamily", value.to_string())),
+            "font-size" => {
+                if let Some(vh) = value.strip_suffix("vh").and_then(|v| v.parse::<f32>().ok()) {
+                    Some(("tts:fontSize", percent(vh * 15.0)))
+                } else {
+                    parse_percentage(value).map(|p| ("tts:fontSize", percent(p)))
+                }
+            }
+            "font-style" if value == "italic" || value == "oblique" => {
+                Some(("tts:fontStyle", "italic".to_string()))
+            }
+            "font-weight"
+                if value.starts_with("bold") || value.parse::<u32>().is_ok_and(|w| w >= 600) =>
+            {
+                Some(("tts:fontWeight", "bold".to_string()))
+            }
+            "text-decoration" => {
+                if value.contains("underline") {
+                    decoration.push("underline")
+                }
+                if value.contains("line-through") {
+                    decoration.push("lineThrough")
+                }
+                None
+            }
+            "opacity" => Some(("tts:opacity", value.to_string())),
+            _ => None,
+        };
+        if let Some((name, value)) = attribute {
+            attributes.push((name.to_string(), value))
+        }
+    }
+    if !decoration.is_empty() {
+        attributes.push(("tts:textDecoration".to_string(), decoration.join(" ")))
+    }
+    attributes
+}
+
+/// Returns the override blocks (`{...}`) of a SSA text.
+fn override_blocks(text: &str) -> impl Iterator<Item = &str> {
+    text.split('{')
+        .skip(1)
+        .filter_map(|block| block.split_once('}').map(|(block, _)| block))
+}
+
+/// The styling of a run of text.
+#[derive(Clone, Debug, Default, PartialEq)]
+struct Run {
+    italic: bool,
+    bold: bool,
+    underline: bool,
+    strikeout: bool,
+    color: Option<Color>,
+}
+
+impl Run {
+    fn from_ssa_style(style: &SSAStyle) -> Self {
+        Self {
+            italic: style.italic,
+            bold: style.bold,
+            underline: style.underline,
+            strikeout: style.strikeout,
+            color: style.primary_color.map(ssa_color),
+        }
+    }
+
+    /// Returns the attributes of a `<span>` which changes `base` into `self`.
+    fn attributes(&self, base: &Self) -> Vec<(String, String)> {
+        let mut attributes = vec![];
+        if self.italic != base.italic {
+            let style = if self.italic { "italic" } else { "normal" };
+            attributes.push(("tts:fontStyle".to_string(), style.to_string()))
+        }
+        if self.bold != base.bold {
+            let weight = if self.bold { "bold" } else { "normal" };
+            attributes.push(("tts:fontWeight".to_string(), weight.to_string()))
+        }
+        let mut decoration = vec![];
+        if self.underline != base.underline {
+            decoration.push(if self.underline {
+                "underline"
+            } else {
+                "noUnderline"
+            })
+        }
+        if self.strikeout != base.strikeout {
+            decoration.push(if self.strikeout {
+                "lineThrough"
+            } else {
+                "noLineThrough"
+            })
+        }
+        if !decoration.is_empty() {
+            attributes.push(("tts:textDecoration".to_string(), decoration.join(" ")))
+        }
+        if self.color != base.color {
+            let color = self.color.unwrap_or(WHITE);
+            attributes.push(("tts:color".to_string(), color.to_ttml_string()))
+        }
+        attributes
+    }
+}
+
+/// Splits a SSA text into runs of equally styled text. Line breaks are converted to `\n`.
+fn ssa_runs(text: &str, base: &Run, styles: &[SSAStyle]) -> Vec<(Run, String)> {
+    let mut runs = vec![];
+    let mut run = base.clone();
+    let mut rest = text;
+
+    loop {
+        let (plain, block) = match rest.split_once('{') {
+            Some((plain, after)) if after.contains('}') => (plain, Some(after)),
+            _ => (rest, None),
+        };
+        let plain = plain
+            .replace("\\N", "\n")
+            .replace("\\n", " ")
+            .replace("\\h", "\u{a0}");
+        if !plain.is_empty() {
+            runs.push((run.clone(), plain))
+        }
+        let Some(block) = block else { break };
+        let (block, after) = block.split_once('}').unwrap();
+        rest = after;
+
+        for tag in block.split('\\').skip(1) {
+            let tag = tag.trim();
+            let flag = |name: char| {
+                tag.strip_prefix(name)
+                    .and_then(|v| v.parse::<u32>().ok())
+                    .map(|v| v != 0)
+            };
+            if let Some(italic) = flag('i') {
+                run.italic = italic
+            } else if let Some(bold) = flag('b') {
+                run.bold = bold
+            } else if let Some(underline) = flag('u') {
+                run.underline = underline
+            } else if let Some(strikeout) = flag('s') {
+                run.strikeout = strikeout
+            } else if let Some(color) = tag.strip_prefix("1c").or(tag.strip_prefix('c')) {
+                let hex = color.trim_start_matches("&H").trim_end_matches('&');
+                run.color = if hex.is_empty() {
+                    base.color
+                } else if let Ok(bgr) = u32::from_str_radix(hex, 16) {
+                    Some(Color::new(
+                        bgr as u8,
+                        (bgr >> 8) as u8,
+                        (bgr >> 16) as u8,
+                        255,
+                    ))
+                } else {
+                    run.color
+                }
+            } else if let Some(style) = tag.strip_prefix('r') {
+                run = styles
+                    .iter()
+                    .find(|s| !style.is_empty() && s.name == style)
+                    .map(Run::from_ssa_style)
+                    .unwrap_or(base.clone())
+            }
+        }
+    }
+
+    runs
+}
+
+/// Converts runs of text to markup, using `<span>`s for runs which are styled differently than
+/// `base`.
+fn markup(runs: Vec<(Run, String)>, base: &Run) -> String {
+    // merges neighbouring runs with the same style
+    let mut merged: Vec<(Run, String)> = vec![];
+    for (run, text) in runs {
+        match merged.last_mut() {
+            Some((last, last_text)) if *last == run => last_text.push_str(&text),
+            _ => merged.push((run, text)),
+        }
+    }
+
+    let mut markup = String::new();
+    for (run, text) in merged {
+        let text = escape_text(&text).replace('\n', "<br/>");
+        let attributes = run.attributes(base);
+        if attributes.is_empty() {
+            markup.push_str(&text)
+        } else {
+            let attributes: String = attributes
+                .iter()
+                .map(|(name, value)| format!(" {name}=\"{value}\""))
+                .collect();
+            markup.push_str(&format!("<span{attributes}>{text}</span>"))
+        }
+    }
+    markup
+}
+
+/// Converts the markup of a VTT text. `classes` maps class names to style ids.
+fn vtt_markup(text: &str, classes: &HashMap<String, String>) -> String {
+    let tag_regex = Regex::new(r"<(/?)([^\s.>/]*)([^>]*)>").unwrap();
+    let color_regex = Regex::new(r#"color\s*=\s*"?([^"\s>]+)"?"#).unwrap();
+    let plain = |text: &str| {
+        let text = text
+            .replace("&lt;", "<")
+            .replace("&gt;", ">")
+            .replace("&nbsp;", "\u{a0}")
+            .replace("&lrm;", "\u{200e}")
+            .replace("&rlm;", "\u{200f}")
+            .replace("&amp;", "&");
+        escape_text(&text)
+            .replace("\r\n", "\n")
+            .replace('\n', "<br/>")
+    };
+
+    let mut markup = String::new();
+    // the open tags and whether they were converted to a `<span>`
+    let mut open: Vec<(String, bool)> = vec![];
+    let mut last = 0;
+    for c in tag_regex.captures_iter(text) {
+        let tag = c.get(0).unwrap();
+        markup.push_str(&plain(&text[last..tag.start()]));
+        last = tag.end();
+
+        let name = c[2].to_lowercase();
+        if &c[1] == "/" {
+            if let Some(i) = open.iter().rposition(|(n, _)| *n == name) {
+                for (_, span) in open.drain(i..).rev() {
+                    if span {
+                        markup.push_str("</span>")
+                    }
+                }
+            }
+            continue;
+        }
+
+        let mut attributes = vec![];
+        let style: Vec<&str> = c[3]
+            .split_whitespace()
+            .next()
+            .unwrap_or_default()
+            .split('.')
+            .filter_map(|class| classes.get(class).map(String::as_str))
+            .collect();
+        if !style.is_empty() {
+            attributes.push(("style".to_string(), style.join(" ")))
+        }
+        match name.as_str() {
+            "i" => attributes.push(("tts:fontStyle".to_string(), "italic".to_string())),
+            "b" => attributes.push(("tts:fontWeight".to_string(), "bold".to_string())),
+            "u" => attributes.push(("tts:textDecoration".to_string(), "underline".to_string())),
+            "font" => {
+                if let Some(color) = color_regex
+                    .captures(&c[3])
+                    .and_then(|c| Color::from_vtt(&c[1]).ok())
+                {
+                    attributes.push(("tts:color".to_string(), color.to_ttml_string()))
+                }
+            }
+            _ => (),
+        }
+
+        if !attributes.is_empty() {
+            markup.push_str("<span");
+            for (name, value) in &attributes {
+                markup.push_str(&format!(" {name}=\"{value}\""))
+            }
+            markup.push('>')
+        }
+        open.push((name, !attributes.is_empty()))
+    }
+    markup.push_str(&plain(&text[last..]));
+    for (_, span) in open.into_iter().rev() {
+        if span {
+            markup.push_str("</span>")
+        }
+    }
+
+    markup
+}
@@ -0,0 +1,142 @@
+//! Implements keyframe (scene cut) aware timing.
+//!
+//! It describes the [Keyframes] struct, which can be parsed from the keyframe lists used by
+//! typesetting tools, and provides the [snap_to_keyframes] function.
+//!
+//! Supported formats are:
+//! - Aegisub keyframe files, starting with `# keyframe format v1`
+//! - XviD first pass stats, starting with `# XviD 2pass stat file`, in which every frame line
+//!   starting with `i` is a keyframe
+//! - Plain lists of frame numbers, one per line. Empty lines and lines starting with `#` are
+//!   ignored
+
+use serde::{Deserialize, Serialize};
+
+use crate::cue::{Cue, Subtitle};
+use crate::error;
+use crate::timing::{TimingChange, TimingChangeReason};
+use crate::util::{FrameRate, FrameRounding};
+
+/// A sorted list of keyframe numbers.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub struct Keyframes {
+    pub frames: Vec<i64>,
+}
+
+impl Keyframes {
+    /// Parses the given [String] into [Keyframes]. The format is detected by the header.
+    pub fn parse<S: AsRef<str>>(content: S) -> Result<Keyframes, KeyframesError> {
+        let content = content.as_ref();
+        let mut lines = (1..).zip(content.lines());
+
+        let mut frames = match lines.next() {
+            Some((_, header)) if header.trim().starts_with("# keyframe format v1") => {
+                parse::parse_frame_list(lines)?
+            }
+            Some((_, header)) if header.to_lowercase().contains("xvid 2pass stat file") => {
+                parse::parse_xvid(lines)
+            }
+            _ => parse::parse_frame_list((1..).zip(content.lines()))?,
+        };
+        frames.sort();
+        frames.dedup();
+
+        Ok(Keyframes { frames })
+    }
+
+    /// Returns the keyframe closest to `frame`, if there is one within `threshold` frames.
+    pub fn nearest(&self, frame: i64, threshold: i64) -> Option<i64> {
+        let idx = self.frames.partition_point(|f| *f < frame);
+        [idx.checked_sub(1), Some(idx)]
+            .into_iter()
+            .flatten()
+            .filter_map(|i| self.frames.get(i).copied())
+            .filter(|f| (f - frame).abs() <= threshold)
+            .min_by_key(|f| (f - frame).abs())
+    }
+}
+
+/// Moves the start and end of every cue in `subtitle` onto the nearest keyframe if it lies within
+/// `threshold` frames, and returns the adjusted cues.
+///
+/// A snapped start makes the cue appear exactly at the keyframe, a snapped end makes it disappear
+/// exactly at the keyframe. Ends are not snapped if this would make the cue empty.
+pub fn snap_to_keyframes<S: Subtitle>(
+    subtitle: &mut S,
+    keyframes: &Keyframes,
+    frame_rate: FrameRate,
+    threshold: i64,
+) -> Vec<TimingChange> {
+    let mut changes = vec![];
+
+    for (i, cue) in subtitle.cues_mut().iter_mut().enumerate() {
+        let (old_start, old_end) = (cue.start(), cue.end());
+        let start_frame = frame_rate.time_to_frame(old_start, FrameRounding::Up);
+        let end_frame = frame_rate.time_to_frame(old_end, FrameRounding::Up);
+
+        let new_start_frame = keyframes.nearest(start_frame, threshold);
+        let new_end_frame = keyframes
+            .nearest(end_frame, threshold)
+            .filter(|f| *f > new_start_frame.unwrap_or(start_frame));
+        if new_start_frame.is_none() && new_end_frame.is_none() {
+            continue;
+        }
+
+        let new_start = new_start_frame.map_or(old_start, |f| frame_rate.frame_boundary(f));
+        let new_end = new_end_frame.map_or(old_end, |f| frame_rate.frame_boundary(f));
+        if (new_start, new_end) != (old_start, old_end) && new_start < new_end {
+            cue.set_start(new_start);
+            cue.set_end(new_end);
+            changes.push(TimingChange {
+                cue: i,
+                reason: TimingChangeReason::Keyframe,
+                old_start,
+                old_end,
+                new_start,
+                new_end,
+            })
+        }
+    }
+
+    changes
+}
+
+error! {
+    KeyframesError => KeyframesErrorKind {
+        Parse(String),
+    }
+}
+
+mod parse {
+    use super::*;
+
+    pub(super) fn parse_xvid<'a, I: Iterator<Item = (usize, &'a str)>>(lines: I) -> Vec<i64> {
+        lines
+            .map(|(_, line)| line.trim())
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .enumerate()
+            .filter(|(_, line)| line.starts_with('i'))
+            .map(|(frame, _)| frame as i64)
+            .collect()
+    }
+
+    pub(super) fn parse_frame_list<'a, I: Iterator<Item = (usize, &'a str)>>(
+        lines: I,
+    ) -> Result<Vec<i64>, KeyframesError> {
+        let mut frames = vec![];
+        for (i, line) in lines {
+            let line = line.trim();
+            // the 'fps' line of aegisub keyframe files isn't needed, as the frame rate is given by
+            // the caller
+            if line.is_empty() || line.starts_with('#') || line.starts_with("fps") {
+                continue;
+            }
+            frames.push(
+                line.parse::<i64>().map_err(|e| {
+                    KeyframesError::new(KeyframesErrorKind::Parse(e.to_string()), i)
+                })?,
+            )
+        }
+        Ok(frames)
+    }
+}
@@ -1,7 +1,7 @@
 //! # rsubs-lib
 //!
 //! This [crate](https://crates.io/crates/rsubs-lib) provides a simple way for parsing, modifying or converting
-//! subtitle files such as `.srt`,`.ssa`,`.ass` and `.vtt`.
+//! subtitle files such as `.srt`,`.ssa`,`.ass`,`.vtt`, `.smi`, `.lrc`, `.sbv`, `.scc`, MicroDVD or SubViewer `.sub`, TTML / DFXP / IMSC, EBU-TT-D and YouTube JSON3 / SRV3.
 //!
 //! Example usage:
 //! ```
@@ -27,13 +27,61 @@
 //! ```
 //!
 //!
+mod alignment;
+mod bilingual;
+mod cue;
+mod ebu_tt_d;
+mod edit;
+mod guideline;
+mod history;
+mod imsc;
+mod keyframes;
+mod lrc;
+mod merge;
+mod metrics;
+mod microdvd;
+mod overlap;
+mod playback;
+mod sami;
+mod sbv;
+mod scc;
+mod shot_change;
+mod slice;
 mod srt;
 mod ssa;
+mod subviewer;
+mod time_index;
+mod timed_text;
+mod timing;
+mod ttml;
 pub mod util;
 mod vtt;
 
+pub use alignment::*;
+pub use bilingual::*;
+pub use cue::*;
+pub use ebu_tt_d::*;
+pub use edit::*;
+pub use guideline::*;
+pub use history::*;
+pub use imsc::*;
+pub use keyframes::*;
+pub use lrc::*;
+pub use metrics::*;
+pub use microdvd::*;
+pub use overlap::*;
+pub use playback::*;
+pub use sami::*;
+pub use sbv::*;
+pub use scc::*;
+pub use shot_change::*;
 pub use srt::*;
 pub use ssa::*;
+pub use subviewer::*;
+pub use time_index::*;
+pub use timed_text::*;
+pub use timing::*;
+pub use ttml::*;
 pub use vtt::*;
 
 macro_rules! error {
@@ -71,6 +119,21 @@ macro_rules! error {
             $($field $(($($t),*))?),*
         }
     };
+    // errors of parsers for single values, which have no line
+    ($error_name:ident { $($field:ident $(($($t:ty),*))?),*, }) => {
+        #[derive(Debug, Eq, PartialEq)]
+        pub enum $error_name {
+            $($field $(($($t),*))?),*
+        }
+
+        impl std::fmt::Display for $error_name {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                write!(f, "error: {:?}", self)
+            }
+        }
+
+        impl std::error::Error for $error_name {}
+    };
 }
 pub(crate) use error;
 
@@ -0,0 +1,451 @@
+//! Implements helpers for LRC `.lrc` lyrics.
+//!
+//! It describes the [LRC], [LRCMetadata] and [LRCLine] structs and
+//! provides the [LRC::parse] function.
+//!
+//! Enhanced LRC word timing (`<00:12.50>` tags in front of each word) is kept in the text and
+//! reported by [Cue::inline_timestamps]. It's converted to `\k` karaoke tags by [LRC::to_ssa] and
+//! to timestamp tags by [LRC::to_vtt].
+
+use regex::Regex;
+use serde::Deserialize;
+use serde::Serialize;
+use std::fmt::Display;
+use time::{Duration, Time};
+
+use crate::error;
+use crate::util::{millis_between, millis_to_time, time_to_millis};
+
+use super::cue::{strip_markup, Cue, Subtitle};
+use super::srt::{SRTLine, SRT};
+use super::ssa::{SSAEvent, SSAStyle, SSA};
+use super::strip_bom;
+use super::vtt::{VTTLine, VTT};
+
+/// How long the last line is shown if no empty line ends it.
+const LAST_LINE_DURATION: Duration = Duration::seconds(5);
+
+/// Contains the metadata and a Vec<[LRCLine]>
+///
+/// The `.lrc` format generally looks like:
+///```text
+/// [ar:Artist]
+/// [ti:Title]
+/// [offset:+500]
+/// [00:12.00]This is my text
+/// [00:15.30][01:02.00]This is my <00:16.10>repeated <00:16.80>text
+/// [00:18.00]
+/// ```
+/// A line is shown until the next one starts. Lines without text only end the previous line.
+#[derive(Clone, Debug, Default, Eq, PartialEq, Deserialize, Serialize)]
+pub struct LRC {
+    pub metadata: LRCMetadata,
+    pub lines: Vec<LRCLine>,
+}
+
+/// The ID tags of a LRC file. The `[offset:]` tag is applied to all times when parsing and isn't
+/// kept.
+#[derive(Clone, Debug, Default, Eq, PartialEq, Deserialize, Serialize)]
+pub struct LRCMetadata {
+    /// `[ar:]`
+    pub artist: Option<String>,
+    /// `[ti:]`
+    pub title: Option<String>,
+    /// `[al:]`
+    pub album: Option<String>,
+    /// `[au:]`, the author of the song text.
+    pub author: Option<String>,
+    /// `[length:]`, kept as it's written in the file.
+    pub length: Option<String>,
+    /// `[by:]`, the author of the LRC file.
+    pub by: Option<String>,
+    /// `[re:]` or `[tool:]`, the program which created the file.
+    pub tool: Option<String>,
+    /// `[ve:]`, the version of the program.
+    pub version: Option<String>,
+    /// All other ID tags, in the order they appear in the file.
+    pub additional_fields: Vec<(String, String)>,
+}
+
+/// Describes each line
+///
+/// The text may contain enhanced LRC word tags (`<00:12.50>`). Lines which share a timestamp in
+/// the file are joined by `\n`.
+#[derive(Clone, Debug, Eq, PartialEq, Deserialize, Serialize)]
+pub struct LRCLine {
+    pub start: Time,
+    pub end: Time,
+    pub text: String,
+}
+
+impl Default for LRCLine {
+    fn default() -> Self {
+        Self {
+            start: Time::from_hms(0, 0, 0).unwrap(),
+            end: Time::from_hms(0, 0, 0).unwrap(),
+            text: "".to_string(),
+        }
+    }
+}
+
+impl LRC {
+    /// Parses the given [String] into a [LRC].
+    ///
+    /// A positive `[offset:]` (in milliseconds) makes all lines and words appear earlier, a
+    /// negative one later. Lines with several timestamps are repeated at each of them.
+    pub fn parse<S: AsRef<str>>(content: S) -> Result<LRC, LRCError> {
+        let tag_regex = Regex::new(r"^\[([^\]]*)\]").unwrap();
+
+        let mut metadata = LRCMetadata::default();
+        let mut offset = 0;
+        // start in milliseconds and text of every timestamp
+        let mut entries: Vec<(i64, String)> = vec![];
+        for (i, line) in strip_bom(&content).lines().enumerate() {
+            let mut rest = line.trim();
+            let mut starts = vec![];
+            while let Some(tag) = tag_regex.captures(rest) {
+                let value = tag[1].trim().to_string();
+                rest = &rest[tag.get(0).unwrap().end()..];
+
+                if let Some(start) = parse::time(&value) {
+                    starts.push(start);
+                    continue;
+                }
+                // ID tags fill the whole line
+                let Some((name, value)) = value.split_once(':') else {
+                    continue;
+                };
+                let value = value.trim().to_string();
+                match name.trim().to_lowercase().as_str() {
+                    "ar" => metadata.artist = Some(value),
+                    "ti" => metadata.title = Some(value),
+                    "al" => metadata.album = Some(value),
+                    "au" => metadata.author = Some(value),
+                    "length" => metadata.length = Some(value),
+                    "by" => metadata.by = Some(value),
+                    "re" | "tool" => metadata.tool = Some(value),
+                    "ve" => metadata.version = Some(value),
+                    "offset" => {
+                        offset = value.trim_start_matches('+').parse::<i64>().map_err(|_| {
+                            LRCError::new(
+                                LRCErrorKind::Parse(format!("invalid offset '{value}'")),
+                                i + 1,
+                            )
+                        })?
+                    }
+                    name => metadata.additional_fields.push((name.to_string(), value)),
+                }
+            }
+            for start in starts {
+                entries.push((start, rest.trim().to_string()))
+            }
+        }
+        entries.sort_by_key(|(start, _)| *start);
+
+        let mut lines: Vec<LRCLine> = vec![];
+        for (i, (start, text)) in entries.iter().enumerate() {
+            if text.is_empty() {
+                continue;
+            }
+            let start = millis_to_time(start.saturating_sub(offset));
+            let text = parse::shift_words(text, offset);
+            // lines with the same timestamp are shown together
+            if let Some(last) = lines.last_mut().filter(|l| l.start == start) {
+                last.text.push('\n');
+                last.text.push_str(&text);
+                continue;
+            }
+            let end = entries[i + 1..]
+                .iter()
+                .map(|(next, _)| millis_to_time(next.saturating_sub(offset)))
+                .find(|next| *next > start)
+                .unwrap_or(start + LAST_LINE_DURATION);
+            lines.push(LRCLine { start, end, text })
+        }
+
+        Ok(LRC { metadata, lines })
+    }
+
+    /// Converts to [SRT]. Word tags are removed.
+    pub fn to_srt(&self) -> SRT {
+        SRT {
+            lines: self
+                .lines
+                .iter()
+                .enumerate()
+                .map(|(i, line)| SRTLine {
+                    sequence_number: i as u32 + 1,
+                    start: line.start,
+                    end: line.end,
+                    text: line.plain_text().replace('\n', "\r\n"),
+                })
+                .collect(),
+        }
+    }
+
+    /// Converts to [VTT]. Word tags are converted to timestamp tags (`<00:00:12.500>`), except
+    /// the ones outside the time the line is shown.
+    pub fn to_vtt(&self) -> VTT {
+        VTT {
+            lines: self
+                .lines
+                .iter()
+                .map(|line| {
+                    let mut text = String::new();
+                    for (time, word) in line.words() {
+                        if time.is_some_and(|t| t > line.start && t < line.end) {
+                            let (hour, minute, second, milli) = time.unwrap().as_hms_milli();
+                            text.push_str(&format!(
+                                "<{hour:0>2}:{minute:0>2}:{second:0>2}.{milli:0>3}>"
+                            ))
+                        }
+                        text.push_str(&word.replace('&', "&amp;").replace('<', "&lt;"))
+                    }
+                    VTTLine {
+                        start: line.start,
+                        end: line.end,
+                        text,
+                        ..Default::default()
+                    }
+                })
+                .collect(),
+            ..Default::default()
+        }
+    }
+
+    /// Converts to [SSA]. Lines with word tags get a `\k` karaoke tag in front of every word,
+    /// lasting until the next word tag or the end of the line.
+    pub fn to_ssa(&self) -> SSA {
+        let default_style = SSAStyle {
+            name: "Default".to_string(),
+            fontname: "Arial".to_string(),
+            fontsize: 20.0,
+            ..Default::default()
+        };
+
+        let mut events = vec![];
+        for line in &self.lines {
+            let words = line.words();
+            let karaoke = words.iter().any(|(time, _)| time.is_some());
+
+            let mut text = String::new();
+            let mut start = line.start;
+            for (i, (_, word)) in words.iter().enumerate() {
+                if karaoke {
+                    let end = words[i + 1..]
+                        .iter()
+                        .find_map(|(time, _)| *time)
+                        .unwrap_or(line.end)
+                        .clamp(start, line.end.max(start));
+                    // a trailing word tag only marks the end of the last word
+                    if word.is_empty() && i + 1 == words.len() {
+                        break;
+                    }
+                    let centis = (millis_between(start, end) + 5) / 10;
+                    text.push_str(&format!("{{\\k{centis}}}"));
+                    start = end
+                }
+                text.push_str(&word.replace('\n', "\\N"))
+            }
+
+            events.push(SSAEvent {
+                start: line.start,
+                end: line.end,
+                style: "Default".to_string(),
+                text,
+                ..Default::default()
+            })
+        }
+
+        SSA {
+            styles: vec![default_style],
+            events,
+            ..Default::default()
+        }
+    }
+}
+
+impl LRCLine {
+    /// Splits the text at the word tags. Returns the time of each tag (and [None] for the text
+    /// in front of the first one) with the text following it.
+    fn words(&self) -> Vec<(Option<Time>, String)> {
+        let mut words = vec![];
+        let mut last = 0;
+        let mut time = None;
+        for (offset, next) in self.inline_timestamps() {
+            if offset > last || time.is_some() {
+                words.push((time, self.text[last..offset].to_string()))
+            }
+            let len = self.text[offset..].find('>').unwrap() + 1;
+            last = offset + len;
+            time = Some(next)
+        }
+        words.push((time, self.text[last..].to_string()));
+        words
+    }
+}
+
+impl Cue for LRCLine {
+    const LINE_BREAK: &'static str = "\n";
+
+    fn start(&self) -> Time {
+        self.start
+    }
+    fn end(&self) -> Time {
+        self.end
+    }
+    fn set_start(&mut self, start: Time) {
+        self.start = start
+    }
+    fn set_end(&mut self, end: Time) {
+        self.end = end
+    }
+    fn text(&self) -> &str {
+        &self.text
+    }
+    fn set_text(&mut self, text: String) {
+        self.text = text
+    }
+    /// Removes all word tags (`<00:12.50>`).
+    fn plain_text(&self) -> String {
+        strip_markup(&self.text, '<', '>')
+    }
+    /// Returns the position and time of all word tags (`<00:12.50>`).
+    fn inline_timestamps(&self) -> Vec<(usize, Time)> {
+        let tag_regex = Regex::new(r"<([^<>]*)>").unwrap();
+        tag_regex
+            .captures_iter(&self.text)
+            .filter_map(|c| {
+                let time = parse::time(&c[1])?;
+                Some((c.get(0).unwrap().start(), millis_to_time(time)))
+            })
+            .collect()
+    }
+}
+
+impl Subtitle for LRC {
+    type Cue = LRCLine;
+
+    fn cues(&self) -> &[LRCLine] {
+        &self.lines
+    }
+    fn cues_mut(&mut self) -> &mut Vec<LRCLine> {
+        &mut self.lines
+    }
+}
+
+impl Display for LRC {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let metadata = &self.metadata;
+        let mut lines = vec![];
+        for (name, value) in [
+            ("ar", &metadata.artist),
+            ("ti", &metadata.title),
+            ("al", &metadata.album),
+            ("au", &metadata.author),
+            ("length", &metadata.length),
+            ("by", &metadata.by),
+            ("re", &metadata.tool),
+            ("ve", &metadata.version),
+        ] {
+            if let Some(value) = value {
+                lines.push(format!("[{name}:{value}]"))
+            }
+        }
+        for (name, value) in &metadata.additional_fields {
+            lines.push(format!("[{name}:{value}]"))
+        }
+
+        for (i, line) in self.lines.iter().enumerate() {
+            let start = parse::format_time(time_to_millis(line.start));
+            for text in line.text.split('\n') {
+                lines.push(format!("[{start}]{text}"))
+            }
+            // the end is given by the start of the next line if they touch
+            if !matches!(self.lines.get(i + 1), Some(n) if n.start == line.end) {
+                lines.push(format!(
+                    "[{}]",
+                    parse::format_time(time_to_millis(line.end))
+                ))
+            }
+        }
+
+        write!(f, "{}", lines.join("\n"))
+    }
+}
+
+error! {
+    LRCError => LRCErrorKind {
+        Parse(String),
+    }
+}
+
+mod parse {
+    use super::*;
+
+    /// Parses a `mm:ss.xx` timestamp to milliseconds. Minutes may exceed 59, the fraction may
+    /// have 1 to 3 digits.
+    pub(super) fn time(value: &str) -> Option<i64> {
+        let (minutes, seconds) = value.trim().split_once(':')?;
+        let (seconds, fraction) = match seconds.split_once(['.', ':']) {
+            Some((seconds, fraction)) => (seconds, fraction),
+            None => (seconds, "0"),
+        };
+        if [minutes, seconds, fraction]
+            .iter()
+            .any(|v| v.is_empty() || !v.chars().all(|c| c.is_ascii_digit()))
+            || seconds.len() > 2
+            || fraction.len() > 3
+        {
+            return None;
+        }
+        let fraction = format!("{fraction:0<3}").parse::<i64>().ok()?;
+        minutes
+            .parse::<i64>()
+            .ok()?
+            .checked_mul(60_000)?
+            .checked_add(seconds.parse::<i64>().ok()? * 1000 + fraction)
+    }
+
+    /// Formats milliseconds as `mm:ss.xx`.
+    pub(super) fn format_time(millis: i64) -> String {
+        let centis = millis.saturating_add(5) / 10;
+        format!(
+            "{:0>2}:{:0>2}.{:0>2}",
+            centis / 6000,
+            centis / 100 % 60,
+            centis % 100
+        )
+    }
+
+    /// Applies `offset` to the word tags of `text`. Tags with milliseconds (`<00:01.234>`) keep
+    /// them.
+    pub(super) fn shift_words(text: &str, offset: i64) -> String {
+        if offset == 0 {
+            return text.to_string();
+        }
+        let tag_regex = Regex::new(r"<([^<>]*)>").unwrap();
+        tag_regex
+            .replace_all(text, |c: &regex::Captures| match time(&c[1]) {
+                Some(time) => {
+                    let time = time.saturating_sub(offset).max(0);
+                    let millis = c[1]
+                        .trim()
+                        .rsplit_once(['.', ':'])
+                        .is_some_and(|(_, fraction)| fraction.len() == 3);
+                    if millis {
+                        format!(
+                            "<{:0>2}:{:0>2}.{:0>3}>",
+                            time / 60_000,
+                            time / 1000 % 60,
+                            time % 1000
+                        )
+                    } else {
+                        format!("<{}>", format_time(time))
+                    }
+                }
+                None => c[0].to_string(),
+            })
+            .to_string()
+    }
+}
@@ -0,0 +1,253 @@
+//! Implements concatenating subtitles.
+//!
+//! [SRT::append], [VTT::append] and [SSA::append] add the cues of another subtitle, moved by an
+//! offset, to an existing one. [SRT::merge], [VTT::merge] and [SSA::merge] concatenate multiple
+//! parts at once, e.g. the subtitles of CD1 and CD2 of a movie.
+
+use regex::{Captures, Regex};
+use std::collections::{HashMap, HashSet};
+
+use crate::cue::Subtitle;
+use crate::srt::SRT;
+use crate::ssa::SSA;
+use crate::timing::shift;
+use crate::vtt::VTT;
+
+macro_rules! impl_merge {
+    ($($subtitle:ident),*) => {
+        $(
+            impl $subtitle {
+                /// Concatenates all `parts`, each moved by its offset in milliseconds. Everything
+                /// apart from the cues is reconciled like [Self::append] does, with the first part
+                /// taking precedence.
+                pub fn merge<I: IntoIterator<Item = ($subtitle, i64)>>(parts: I) -> $subtitle {
+                    let mut parts = parts.into_iter();
+                    let Some((mut merged, offset_ms)) = parts.next() else {
+                        return $subtitle::default();
+                    };
+                    shift(&mut merged, offset_ms);
+                    for (part, offset_ms) in parts {
+                        merged.append(part, offset_ms)
+                    }
+                    merged
+                }
+            }
+        )*
+    };
+}
+
+impl_merge!(SRT, VTT);
+
+impl SRT {
+    /// Appends all lines of `other`, moved by `offset_ms` milliseconds, and renumbers all lines.
+    pub fn append(&mut self, mut other: SRT, offset_ms: i64) {
+        shift(&mut other, offset_ms);
+        self.lines.append(&mut other.lines);
+        self.renumber()
+    }
+}
+
+impl VTT {
+    /// Appends all lines of `other`, moved by `offset_ms` milliseconds.
+    ///
+    /// Styles and regions of `other` which are already present are skipped. Regions whose id is
+    /// already used by a different region get renamed (`fred` becomes `fred_2`), and the lines of
+    /// `other` are updated accordingly.
+    ///
+    /// Numeric cue identifiers of `other` are renumbered to follow the largest numeric identifier
+    /// of `self`, like [SRT::append] does. Other identifiers which are already used get renamed
+    /// like regions.
+    pub fn append(&mut self, mut other: VTT, offset_ms: i64) {
+        shift(&mut other, offset_ms);
+
+        for style in other.styles {
+            if !self.styles.contains(&style) {
+                self.styles.push(style)
+            }
+        }
+
+        let mut taken: HashSet<String> = self
+            .regions
+            .iter()
+            .chain(&other.regions)
+            .filter_map(|r| r.id.clone())
+            .collect();
+        let mut renames = HashMap::new();
+        for mut region in other.regions {
+            if self.regions.contains(&region) {
+                continue;
+            }
+            if let Some(id) = &region.id {
+                if self.regions.iter().any(|r| r.id.as_ref() == Some(id)) {
+                    let new_id = unique_name(id, &mut taken);
+                    renames.insert(id.clone(), new_id.clone());
+                    region.id = Some(new_id)
+                }
+            }
+            self.regions.push(region)
+        }
+
+        let last_number = self
+            .lines
+            .iter()
+            .filter_map(|l| l.identifier.as_ref()?.parse::<u64>().ok())
+            .max()
+            .unwrap_or(0);
+        let mut taken: HashSet<String> = self
+            .lines
+            .iter()
+            .chain(&other.lines)
+            .filter_map(|l| l.identifier.clone())
+            .collect();
+        let used: HashSet<String> = self
+            .lines
+            .iter()
+            .filter_map(|l| l.identifier.clone())
+            .collect();
+
+        for mut line in other.lines {
+            if let Some(identifier) = &mut line.identifier {
+                if let Ok(number) = identifier.parse::<u64>() {
+                    *identifier = last_number.saturating_add(number).to_string()
+                } else if used.contains(identifier) {
+                    *identifier = unique_name(identifier, &mut taken)
+                }
+            }
+            if let Some(Some(region)) = line.settings.get_mut("region") {
+                if let Some(new_id) = renames.get(region) {
+                    *region = new_id.clone()
+                }
+            }
+            self.lines.push(line)
+        }
+    }
+}
+
+impl SSA {
+    /// Concatenates all `parts`, each moved by its offset in milliseconds. Everything apart from
+    /// the events is reconciled like [Self::append] does, with the first part taking precedence.
+    pub fn merge<I: IntoIterator<Item = (SSA, i64)>>(parts: I) -> SSA {
+        let mut parts = parts.into_iter();
+        let Some((mut merged, offset_ms)) = parts.next() else {
+            return SSA::default();
+        };
+        shift(&mut merged, offset_ms);
+        let reset_regex = reset_regex();
+        for (part, offset_ms) in parts {
+            merged.append_with(part, offset_ms, &reset_regex)
+        }
+        merged
+    }
+
+    /// Appends all events of `other`, moved by `offset_ms` milliseconds.
+    ///
+    /// The [SSAInfo](crate::SSAInfo) of `self` is kept. If the play resolution of `other` differs,
+    /// its styles and event margins are scaled to the play resolution of `self`. Positioning
+    /// override tags (like `\pos`) are not scaled.
+    ///
+    /// Styles of `other` which are already present are skipped. Styles whose name is already used
+    /// by a different style get renamed (`Default` becomes `Default_2`), and the events of `other`
+    /// (including `\r` override tags) are updated accordingly.
+    pub fn append(&mut self, other: SSA, offset_ms: i64) {
+        self.append_with(other, offset_ms, &reset_regex())
+    }
+
+    /// [Self::append] with an already compiled [reset_regex].
+    fn append_with(&mut self, mut other: SSA, offset_ms: i64, reset_regex: &Regex) {
+        shift(&mut other, offset_ms);
+
+        if let (Some(from), Some(to)) = (play_res(&other), play_res(self)) {
+            if from != to {
+                rescale(&mut other, from, to)
+            }
+        }
+
+        let mut taken: HashSet<String> = self
+            .styles
+            .iter()
+            .chain(&other.styles)
+            .map(|s| s.name.clone())
+            .collect();
+        let mut renames = HashMap::new();
+        for mut style in other.styles {
+            if self.styles.contains(&style) {
+                continue;
+            }
+            if self.styles.iter().any(|s| s.name == style.name) {
+                let new_name = unique_name(&style.name, &mut taken);
+                renames.insert(style.name.clone(), new_name.clone());
+                style.name = new_name
+            }
+            self.styles.push(style)
+        }
+
+        for mut event in other.events {
+            if let Some(new_name) = renames.get(&event.style) {
+                event.style = new_name.clone()
+            }
+            if !renames.is_empty() {
+                event.text = reset_regex
+                    .replace_all(&event.text, |c: &Captures| match renames.get(&c[1]) {
+                        Some(new_name) => format!("\\r{new_name}"),
+                        None => c[0].to_string(),
+                    })
+                    .to_string()
+            }
+            self.events.push(event)
+        }
+
+        for font in other.fonts {
+            if !self.fonts.contains(&font) {
+                self.fonts.push(font)
+            }
+        }
+        for graphic in other.graphics {
+            if !self.graphics.contains(&graphic) {
+                self.graphics.push(graphic)
+            }
+        }
+    }
+}
+
+/// Matches `\r` override tags, capturing the style name.
+fn reset_regex() -> Regex {
+    Regex::new(r"\\r([^\\}]*)").unwrap()
+}
+
+/// Returns the first of `name_2`, `name_3`, ... which isn't in `taken`, and marks it as taken.
+fn unique_name(name: &str, taken: &mut HashSet<String>) -> String {
+    let new_name = (2..)
+        .map(|i| format!("{name}_{i}"))
+        .find(|n| !taken.contains(n))
+        .unwrap();
+    taken.insert(new_name.clone());
+    new_name
+}
+
+fn play_res(ssa: &SSA) -> Option<(u32, u32)> {
+    match (ssa.info.play_res_x, ssa.info.play_res_y) {
+        (Some(x), Some(y)) if x > 0 && y > 0 => Some((x, y)),
+        _ => None,
+    }
+}
+
+/// Scales all styles and event margins of `ssa` from the play resolution `from` to `to`.
+fn rescale(ssa: &mut SSA, from: (u32, u32), to: (u32, u32)) {
+    let x = to.0 as f32 / from.0 as f32;
+    let y = to.1 as f32 / from.1 as f32;
+
+    for style in &mut ssa.styles {
+        style.fontsize *= y;
+        style.outline *= y;
+        style.shadow *= y;
+        style.spacing *= x;
+        style.margin_l = (style.margin_l * x).round();
+        style.margin_r = (style.margin_r * x).round();
+        style.margin_v = (style.margin_v * y).round();
+    }
+    for event in &mut ssa.events {
+        event.margin_l = (event.margin_l * x).round();
+        event.margin_r = (event.margin_r * x).round();
+        event.margin_v = (event.margin_v * y).round();
+    }
+}
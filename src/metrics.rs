@@ -0,0 +1,196 @@
+//! Implements readability metrics for subtitles.
+//!
+//! It describes the [CueMetrics], [SubtitleMetrics] and [Distribution] structs, which can be
+//! computed for every type implementing [Subtitle].
+//!
+//! All metrics are based on [Cue::plain_text], so markup doesn't influence them. Characters are
+//! counted as Unicode scalar values, with combining marks and zero-width characters ignored, so
+//! that CJK and other non-latin texts produce the same numbers a human reader would count.
+
+use serde::{Deserialize, Serialize};
+use time::Duration;
+
+use crate::cue::{Cue, Subtitle};
+
+/// Readability metrics of a single [Cue].
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct CueMetrics {
+    /// Time the cue is visible.
+    pub duration: Duration,
+    /// Number of displayed characters, including spaces but excluding line breaks.
+    pub characters: usize,
+    /// Number of words. CJK ideographs and kana are counted as one word each, as these scripts
+    /// don't separate words by spaces.
+    pub words: usize,
+    /// Number of displayed lines.
+    pub line_count: usize,
+    /// Number of characters of the longest line.
+    pub max_line_length: usize,
+    /// Characters per second. Is [f64::INFINITY] if the cue contains text but has no duration.
+    pub cps: f64,
+    /// Words per minute. Is [f64::INFINITY] if the cue contains text but has no duration.
+    pub wpm: f64,
+    /// Time between the end of this cue and the start of the following one. Negative if both
+    /// overlap and [None] for the last cue.
+    pub gap_to_next: Option<Duration>,
+}
+
+impl CueMetrics {
+    /// Computes the metrics of `cue`. `next` is the cue following it and is used to calculate
+    /// [CueMetrics::gap_to_next].
+    pub fn new<C: Cue>(cue: &C, next: Option<&C>) -> Self {
+        let text = cue.plain_text();
+        let lines: Vec<&str> = text.lines().collect();
+        let line_lengths: Vec<usize> = lines.iter().map(|l| count_characters(l)).collect();
+        let characters = line_lengths.iter().sum();
+        let words = lines.iter().map(|l| count_words(l)).sum();
+        let duration = cue.end() - cue.start();
+
+        Self {
+            duration,
+            characters,
+            words,
+            line_count: lines.len(),
+            max_line_length: line_lengths.into_iter().max().unwrap_or(0),
+            cps: per_duration(characters, duration, 1.0),
+            wpm: per_duration(words, duration, 60.0),
+            gap_to_next: next.map(|n| n.start() - cue.end()),
+        }
+    }
+}
+
+/// Summary of a set of values.
+#[derive(Clone, Debug, Default, PartialEq, Deserialize, Serialize)]
+pub struct Distribution {
+    pub min: f64,
+    pub max: f64,
+    pub mean: f64,
+    pub median: f64,
+    /// The value which 90% of all values are lower or equal to.
+    pub p90: f64,
+}
+
+impl Distribution {
+    /// Creates a distribution from the given values. All fields are `0` if `values` is empty.
+    pub fn new<I: IntoIterator<Item = f64>>(values: I) -> Self {
+        let mut values: Vec<f64> = values.into_iter().collect();
+        if values.is_empty() {
+            return Self::default();
+        }
+        values.sort_by(f64::total_cmp);
+
+        let percentile = |p: f64| values[((values.len() - 1) as f64 * p).round() as usize];
+        Self {
+            min: values[0],
+            max: values[values.len() - 1],
+            mean: values.iter().sum::<f64>() / values.len() as f64,
+            median: percentile(0.5),
+            p90: percentile(0.9),
+        }
+    }
+}
+
+/// Readability metrics of a whole subtitle.
+#[derive(Clone, Debug, Default, PartialEq, Deserialize, Serialize)]
+pub struct SubtitleMetrics {
+    /// Metrics of each cue, in the same order as [Subtitle::cues].
+    pub cues: Vec<CueMetrics>,
+    /// Distribution of [CueMetrics::cps].
+    pub cps: Distribution,
+    /// Distribution of [CueMetrics::wpm].
+    pub wpm: Distribution,
+    /// Distribution of [CueMetrics::duration], in seconds.
+    pub duration: Distribution,
+    /// Distribution of [CueMetrics::max_line_length].
+    pub max_line_length: Distribution,
+    /// Distribution of [CueMetrics::line_count].
+    pub line_count: Distribution,
+    /// Distribution of [CueMetrics::gap_to_next], in seconds.
+    pub gap_to_next: Distribution,
+}
+
+impl SubtitleMetrics {
+    /// Computes the metrics of every cue in `subtitle` and their distributions.
+    pub fn new<S: Subtitle>(subtitle: &S) -> Self {
+        let cues = subtitle.cues();
+        let cues: Vec<CueMetrics> = cues
+            .iter()
+            .enumerate()
+            .map(|(i, cue)| CueMetrics::new(cue, cues.get(i + 1)))
+            .collect();
+
+        Self {
+            cps: Distribution::new(cues.iter().map(|c| c.cps)),
+            wpm: Distribution::new(cues.iter().map(|c| c.wpm)),
+            duration: Distribution::new(cues.iter().map(|c| c.duration.as_seconds_f64())),
+            max_line_length: Distribution::new(cues.iter().map(|c| c.max_line_length as f64)),
+            line_count: Distribution::new(cues.iter().map(|c| c.line_count as f64)),
+            gap_to_next: Distribution::new(
+                cues.iter()
+                    .filter_map(|c| c.gap_to_next.map(|g| g.as_seconds_f64())),
+            ),
+            cues,
+        }
+    }
+}
+
+/// Counts the characters of `text` as they are perceived by a reader.
+pub fn count_characters(text: &str) -> usize {
+    text.chars().filter(|c| !is_zero_width(*c)).count()
+}
+
+/// Counts the words of `text`. Every CJK ideograph or kana counts as a single word.
+pub fn count_words(text: &str) -> usize {
+    let mut words = 0;
+    let mut in_word = false;
+    for c in text.chars() {
+        if is_cjk(c) {
+            words += 1;
+            in_word = false;
+        } else if c.is_whitespace() {
+            in_word = false;
+        } else if !in_word && !is_zero_width(c) {
+            words += 1;
+            in_word = true;
+        }
+    }
+    words
+}
+
+fn per_duration(count: usize, duration: Duration, unit_seconds: f64) -> f64 {
+    let seconds = duration.as_seconds_f64();
+    if count == 0 {
+        0.0
+    } else if seconds <= 0.0 {
+        f64::INFINITY
+    } else {
+        count as f64 / seconds * unit_seconds
+    }
+}
+
+/// Combining marks, variation selectors and zero-width formatting characters.
+fn is_zero_width(c: char) -> bool {
+    matches!(c,
+        '\u{0300}'..='\u{036F}'
+        | '\u{1AB0}'..='\u{1AFF}'
+        | '\u{1DC0}'..='\u{1DFF}'
+        | '\u{200B}'..='\u{200F}'
+        | '\u{202A}'..='\u{202E}'
+        | '\u{2060}'..='\u{2064}'
+        | '\u{20D0}'..='\u{20FF}'
+        | '\u{3099}'..='\u{309A}'
+        | '\u{FE00}'..='\u{FE0F}'
+        | '\u{FE20}'..='\u{FE2F}'
+        | '\u{FEFF}')
+}
+
+/// Chinese and japanese characters, which aren't separated by spaces.
+fn is_cjk(c: char) -> bool {
+    matches!(c,
+        '\u{3040}'..='\u{3096}'
+        | '\u{309B}'..='\u{30FF}'
+        | '\u{3400}'..='\u{4DBF}'
+        | '\u{4E00}'..='\u{9FFF}'
+        | '\u{F900}'..='\u{FAFF}'
+        | '\u{20000}'..='\u{2FA1F}')
+}
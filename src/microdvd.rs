@@ -0,0 +1,466 @@
+//! Implements helpers for MicroDVD `.sub`.
+//!
+//! It describes the [MicroDVD] and [MicroDVDLine] structs and
+//! provides the [MicroDVD::parse] function.
+//!
+//! Supported control codes are `{y:...}` (`i`, `b`, `u` and `s` for italic, bold, underline and
+//! strikeout) and `{c:$BBGGRR}` (text color). Codes with a lowercase key apply to the line they
+//! precede, codes with an uppercase key (`{Y:i}`) to all following lines of the cue. All other
+//! control codes are kept in the text, but dropped when converting to other formats.
+
+use regex::Regex;
+use serde::Deserialize;
+use serde::Serialize;
+use std::fmt::Display;
+use time::Time;
+
+use crate::error;
+use crate::util::{Alignment, Color, FrameRate, FrameRounding};
+
+use super::cue::{strip_markup, Cue, Subtitle};
+use super::srt::{SRTLine, SRT};
+use super::ssa::{SSAEvent, SSAStyle, SSA};
+use super::strip_bom;
+use super::vtt::{VTTLine, VTT};
+
+/// Contains a Vec<[MicroDVDLine]> and the frame rate their frame numbers refer to.
+///
+/// The `.sub` format stores frame numbers instead of timestamps and generally looks like:
+///```text
+/// {1}{1}23.976
+/// {0}{48}This is my text
+/// {48}{96}{y:i}This is my|second text
+/// ```
+/// The first line is an optional header containing the frame rate.
+#[derive(Clone, Debug, Default, Eq, PartialEq, Deserialize, Serialize)]
+pub struct MicroDVD {
+    pub frame_rate: FrameRate,
+    pub lines: Vec<MicroDVDLine>,
+}
+
+/// Describes each line
+///
+/// The frame numbers of the file are converted to [Time]s using [MicroDVD::frame_rate] and
+/// converted back when writing. Displayed lines in the text are separated by `|`.
+#[derive(Clone, Debug, Eq, PartialEq, Deserialize, Serialize)]
+pub struct MicroDVDLine {
+    pub start: Time,
+    pub end: Time,
+    pub text: String,
+}
+
+impl MicroDVD {
+    /// Parses the given [String] into a [MicroDVD].
+    ///
+    /// The frame rate is taken from `frame_rate` if given, otherwise from the `{1}{1}23.976`
+    /// header. It's an error if neither is available, or if the header frame rate isn't positive
+    /// and `frame_rate` isn't given.
+    pub fn parse<S: AsRef<str>>(
+        content: S,
+        frame_rate: Option<FrameRate>,
+    ) -> Result<MicroDVD, MicroDVDError> {
+        let mut header_frame_rate = None;
+        let mut frames = vec![];
+
+        for (i, line) in (1..).zip(strip_bom(&content).lines()) {
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+            let (start, rest) = parse::parse_frame(line, i)?;
+            let (end, text) = parse::parse_frame(rest, i)?;
+
+            // the header looks like a regular line with the frame rate as text
+            if frames.is_empty() && header_frame_rate.is_none() && start == end && start <= 1 {
+                if let Ok(fps) = text.trim().parse::<f64>() {
+                    match parse::frame_rate(fps) {
+                        Some(fps) => header_frame_rate = Some(fps),
+                        // the header isn't needed if the frame rate is given
+                        None if frame_rate.is_some() => (),
+                        None => {
+                            return Err(MicroDVDError::new(
+                                MicroDVDErrorKind::Parse(format!(
+                                    "invalid frame rate '{}'",
+                                    text.trim()
+                                )),
+                                i,
+                            ))
+                        }
+                    }
+                    continue;
+                }
+            }
+            frames.push((start, end, text.to_string()))
+        }
+
+        let frame_rate = frame_rate.or(header_frame_rate).ok_or(MicroDVDError::new(
+            MicroDVDErrorKind::Parse("missing frame rate".to_string()),
+            1,
+        ))?;
+
+        Ok(MicroDVD {
+            frame_rate,
+            lines: frames
+                .into_iter()
+                .map(|(start, end, text)| MicroDVDLine {
+                    start: frame_rate.frame_to_time(start),
+                    end: frame_rate.frame_to_time(end),
+                    text,
+                })
+                .collect(),
+        })
+    }
+
+    /// Converts to [SRT]. Style codes are converted to `<i>`, `<b>`, `<u>` and `<s>` tags, colors
+    /// to `<font color="...">` tags.
+    pub fn to_srt(&self) -> SRT {
+        SRT {
+            lines: self
+                .lines
+                .iter()
+                .enumerate()
+                .map(|(i, line)| SRTLine {
+                    sequence_number: i as u32 + 1,
+                    start: line.start,
+                    end: line.end,
+                    text: tagged_lines(&line.text, true).join("\r\n"),
+                })
+                .collect(),
+        }
+    }
+
+    /// Converts to [VTT]. Style codes are converted to `<i>`, `<b>`, `<u>` and `<s>` tags, colors
+    /// are dropped.
+    pub fn to_vtt(&self) -> VTT {
+        VTT {
+            lines: self
+                .lines
+                .iter()
+                .map(|line| VTTLine {
+                    start: line.start,
+                    end: line.end,
+                    text: tagged_lines(&line.text, false).join("\n"),
+                    ..Default::default()
+                })
+                .collect(),
+            ..Default::default()
+        }
+    }
+
+    /// Converts to [SSA]. Style and color codes are converted to override tags.
+    pub fn to_ssa(&self) -> SSA {
+        let default_style = SSAStyle {
+            name: "Default".to_string(),
+            fontname: "Arial".to_string(),
+            fontsize: 20.0,
+            alignment: Alignment::BottomCenter,
+            ..Default::default()
+        };
+
+        let mut events = vec![];
+        for line in &self.lines {
+            let mut previous = LineCodes::default();
+            let mut text = vec![];
+            for (i, codes) in line_codes(&line.text).into_iter().enumerate() {
+                let mut overrides = String::new();
+                // override tags are valid until the end of the event, so they have to be reset if
+                // the next line is styled differently
+                if i > 0 && (codes.styles != previous.styles || codes.color != previous.color) {
+                    overrides.push_str("\\r")
+                }
+                if i == 0 || !overrides.is_empty() {
+                    for style in &codes.styles {
+                        overrides.push_str(&format!("\\{style}1"))
+                    }
+                    if let Some(color) = codes.color {
+                        overrides.push_str(&format!("\\c{}&", color.to_ssa_string()))
+                    }
+                }
+                text.push(if overrides.is_empty() {
+                    codes.text.to_string()
+                } else {
+                    format!("{{{overrides}}}{}", codes.text)
+                });
+                previous = codes;
+            }
+
+            events.push(SSAEvent {
+                start: line.start,
+                end: line.end,
+                style: "Default".to_string(),
+                text: text.join("\\N"),
+                ..Default::default()
+            })
+        }
+
+        SSA {
+            styles: vec![default_style],
+            events,
+            ..Default::default()
+        }
+    }
+}
+
+impl SRT {
+    /// Converts to [MicroDVD] using `frame_rate`. `<i>`, `<b>`, `<u>` and `<s>` tags are converted
+    /// to style codes, `<font color="...">` tags to color codes. As codes always apply to whole
+    /// lines, a line gets a style if any part of it has it. All other markup is removed.
+    pub fn to_microdvd(&self, frame_rate: FrameRate) -> MicroDVD {
+        let tag_regex =
+            Regex::new(r#"<(/?)(b|i|u|s|font)(?:\s+color="?([^">]*)"?)?[^>]*>"#).unwrap();
+
+        let mut lines = vec![];
+        for line in &self.lines {
+            // styles and colors opened in a previous line are still active
+            let mut styles: Vec<char> = vec![];
+            let mut colors: Vec<Option<Color>> = vec![];
+            let mut text = vec![];
+
+            for displayed in line.text.lines() {
+                let mut line_styles = styles.clone();
+                let mut line_color = colors.last().copied().flatten();
+                for c in tag_regex.captures_iter(displayed) {
+                    let closing = &c[1] == "/";
+                    match (&c[2], closing) {
+                        ("font", false) => {
+                            let color = c.get(3).and_then(|c| Color::from_vtt(c.as_str()).ok());
+                            line_color = line_color.or(color);
+                            colors.push(color)
+                        }
+                        ("font", true) => {
+                            colors.pop();
+                        }
+                        (style, false) => {
+                            let style = style.chars().next().unwrap();
+                            if !line_styles.contains(&style) {
+                                line_styles.push(style)
+                            }
+                            styles.push(style)
+                        }
+                        (style, true) => {
+                            let style = style.chars().next().unwrap();
+                            styles.retain(|s| *s != style)
+                        }
+                    }
+                }
+
+                let mut codes = String::new();
+                if !line_styles.is_empty() {
+                    codes.push_str(&format!("{{y:{}}}", String::from_iter(line_styles)))
+                }
+                if let Some(color) = line_color {
+                    codes.push_str(&format!("{{c:{}}}", color.to_microdvd_string()))
+                }
+                let plain = strip_markup(&strip_markup(displayed, '<', '>'), '{', '}');
+                text.push(format!("{codes}{plain}"))
+            }
+
+            lines.push(MicroDVDLine {
+                start: line.start,
+                end: line.end,
+                text: text.join("|"),
+            })
+        }
+
+        MicroDVD { frame_rate, lines }
+    }
+}
+
+impl VTT {
+    /// Converts to [MicroDVD] using `frame_rate`. See [SRT::to_microdvd].
+    pub fn to_microdvd(&self, frame_rate: FrameRate) -> MicroDVD {
+        self.to_srt().to_microdvd(frame_rate)
+    }
+}
+
+impl SSA {
+    /// Converts to [MicroDVD] using `frame_rate`. See [SRT::to_microdvd].
+    pub fn to_microdvd(&self, frame_rate: FrameRate) -> MicroDVD {
+        self.to_srt().to_microdvd(frame_rate)
+    }
+}
+
+impl Cue for MicroDVDLine {
+    const LINE_BREAK: &'static str = "|";
+
+    fn start(&self) -> Time {
+        self.start
+    }
+    fn end(&self) -> Time {
+        self.end
+    }
+    fn set_start(&mut self, start: Time) {
+        self.start = start
+    }
+    fn set_end(&mut self, end: Time) {
+        self.end = end
+    }
+    fn text(&self) -> &str {
+        &self.text
+    }
+    fn set_text(&mut self, text: String) {
+        self.text = text
+    }
+    /// Removes all control codes (`{y:i}`).
+    fn plain_text(&self) -> String {
+        self.text
+            .split('|')
+            .map(|line| strip_markup(line, '{', '}'))
+            .collect::<Vec<String>>()
+            .join("\n")
+    }
+}
+
+impl Subtitle for MicroDVD {
+    type Cue = MicroDVDLine;
+
+    fn cues(&self) -> &[MicroDVDLine] {
+        &self.lines
+    }
+    fn cues_mut(&mut self) -> &mut Vec<MicroDVDLine> {
+        &mut self.lines
+    }
+}
+
+impl Display for MicroDVD {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let fps = format!("{:.3}", self.frame_rate.fps());
+        let mut lines = vec![format!(
+            "{{1}}{{1}}{}",
+            fps.trim_end_matches('0').trim_end_matches('.')
+        )];
+
+        for line in &self.lines {
+            lines.push(format!(
+                "{{{}}}{{{}}}{}",
+                self.frame_rate
+                    .time_to_frame(line.start, FrameRounding::Nearest),
+                self.frame_rate
+                    .time_to_frame(line.end, FrameRounding::Nearest),
+                line.text
+            ))
+        }
+
+        write!(f, "{}", lines.join("\r\n"))
+    }
+}
+
+/// The control codes which apply to a displayed line.
+#[derive(Default)]
+struct LineCodes<'a> {
+    /// `i`, `b`, `u` and / or `s`.
+    styles: Vec<char>,
+    color: Option<Color>,
+    /// The text without leading control codes.
+    text: &'a str,
+}
+
+/// Splits `text` into displayed lines and resolves which control codes apply to each of them.
+fn line_codes(text: &str) -> Vec<LineCodes<'_>> {
+    let mut cue_codes = LineCodes::default();
+    let mut lines = vec![];
+
+    for line in text.split('|') {
+        let mut codes = LineCodes {
+            styles: cue_codes.styles.clone(),
+            color: cue_codes.color,
+            text: line,
+        };
+        // control codes are only allowed in front of the text
+        while let Some((key, value, rest)) = codes
+            .text
+            .strip_prefix('{')
+            .and_then(|code| code.split_once('}'))
+            .and_then(|(code, rest)| code.split_once(':').map(|(k, v)| (k, v, rest)))
+        {
+            let targets = if key.chars().all(|c| c.is_uppercase()) {
+                vec![&mut cue_codes, &mut codes]
+            } else {
+                vec![&mut codes]
+            };
+            for target in targets {
+                match key.to_lowercase().as_str() {
+                    "y" => {
+                        for style in value.chars().filter(|c| "ibus".contains(*c)) {
+                            if !target.styles.contains(&style) {
+                                target.styles.push(style)
+                            }
+                        }
+                    }
+                    "c" => target.color = Color::from_microdvd(value).ok().or(target.color),
+                    _ => (),
+                }
+            }
+            codes.text = rest;
+        }
+        lines.push(codes)
+    }
+
+    lines
+}
+
+/// Converts the control codes of `text` into html-like tags, one [String] per displayed line.
+fn tagged_lines(text: &str, colors: bool) -> Vec<String> {
+    line_codes(text)
+        .into_iter()
+        .map(|codes| {
+            let mut line = codes.text.to_string();
+            for style in &codes.styles {
+                line = format!("<{style}>{line}</{style}>")
+            }
+            if let (true, Some(color)) = (colors, codes.color) {
+                line = format!("<font color=\"{}\">{line}</font>", color.to_vtt_string())
+            }
+            line
+        })
+        .collect()
+}
+
+error! {
+    MicroDVDError => MicroDVDErrorKind {
+        Parse(String),
+    }
+}
+
+mod parse {
+    use super::*;
+
+    /// Parses the `{frame}` at the beginning of `line` and returns the rest of it.
+    pub(super) fn parse_frame(line: &str, line_num: usize) -> Result<(i64, &str), MicroDVDError> {
+        let (frame, rest) = line
+            .strip_prefix('{')
+            .and_then(|l| l.split_once('}'))
+            .ok_or(MicroDVDError::new(
+                MicroDVDErrorKind::Parse("missing frame number".to_string()),
+                line_num,
+            ))?;
+        let frame = frame
+            .trim()
+            .parse::<i64>()
+            .map_err(|e| MicroDVDError::new(MicroDVDErrorKind::Parse(e.to_string()), line_num))?;
+        Ok((frame, rest))
+    }
+
+    /// Converts the floating point frame rate of the header, using the exact NTSC frame rates for
+    /// `23.976`, `29.97` and `59.94`. Returns [None] if `fps` isn't positive or too large.
+    pub(super) fn frame_rate(fps: f64) -> Option<FrameRate> {
+        let millis = (fps * 1000.0).round();
+        if !(1.0..=u32::MAX as f64).contains(&millis) {
+            return None;
+        }
+        Some(
+            [
+                FrameRate::FPS_23_976,
+                FrameRate::FPS_29_97,
+                FrameRate::FPS_59_94,
+            ]
+            .into_iter()
+            .find(|fr| (fr.fps() - fps).abs() < 0.01)
+            .unwrap_or(if fps.fract() == 0.0 {
+                FrameRate::new(fps as u32, 1)
+            } else {
+                FrameRate::new(millis as u32, 1000)
+            }),
+        )
+    }
+}
@@ -0,0 +1,187 @@
+//! Implements detection and resolution of overlapping cues.
+//!
+//! Many players (especially for `.srt`) render overlapping cues poorly. [find_overlaps] lists
+//! them and [resolve_overlaps] removes them with a selected [OverlapStrategy]. For `.ass`/`.ssa`
+//! overlapping events can also be kept and stacked on screen with [SSA::stack_overlaps].
+
+use serde::{Deserialize, Serialize};
+
+use crate::cue::{Cue, Subtitle};
+use crate::ssa::SSA;
+use crate::util::time_to_millis;
+
+/// Two cues which are visible at the same time.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub struct Overlap {
+    /// Index of the cue starting first in [Subtitle::cues].
+    pub first: usize,
+    /// Index of the cue starting second in [Subtitle::cues].
+    pub second: usize,
+}
+
+/// Describes how overlapping cues are resolved.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub enum OverlapStrategy {
+    /// The earlier cue ends when the later one starts. Cues starting at the same time are merged
+    /// as with [OverlapStrategy::Merge], as truncating would make them vanish.
+    Truncate,
+    /// Overlapping cues are combined into a single cue, lasting from the first start to the last
+    /// end. The text of every original cue is prefixed with a dialogue dash (`- `).
+    Merge,
+}
+
+/// How [SSA::stack_overlaps] places overlapping events.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub enum StackMode {
+    /// Every overlapping event is put on its own layer, counting up from the layer of the first
+    /// one.
+    Layers,
+    /// Every second overlapping event is moved to the top of the screen with an `{\an8}` override.
+    Alignment,
+}
+
+/// Returns all pairs of overlapping cues, ordered by the start of [Overlap::first]. Cues ending
+/// exactly when another one starts don't overlap.
+pub fn find_overlaps<S: Subtitle>(subtitle: &S) -> Vec<Overlap> {
+    let cues = subtitle.cues();
+    let order = chronological_order(subtitle);
+    let mut overlaps = vec![];
+
+    for (k, &first) in order.iter().enumerate() {
+        let end = time_to_millis(cues[first].end());
+        for &second in &order[k + 1..] {
+            if time_to_millis(cues[second].start()) >= end {
+                break;
+            }
+            overlaps.push(Overlap { first, second })
+        }
+    }
+
+    overlaps
+}
+
+/// Resolves all overlapping cues of `subtitle` with the given strategy and returns the overlaps
+/// that were found (with indices referring to the cues before resolving). Merged cues are
+/// removed, so the subtitle gets renumbered afterward.
+pub fn resolve_overlaps<S: Subtitle>(subtitle: &mut S, strategy: OverlapStrategy) -> Vec<Overlap> {
+    let overlaps = find_overlaps(subtitle);
+    if overlaps.is_empty() {
+        return overlaps;
+    }
+
+    match strategy {
+        OverlapStrategy::Truncate => {
+            // cues starting at the same time cannot be truncated
+            merge_clusters(subtitle, |_, start, cluster_start| start == cluster_start);
+
+            let order = chronological_order(subtitle);
+            let cues = subtitle.cues_mut();
+            for pair in order.windows(2) {
+                let next_start = cues[pair[1]].start();
+                if cues[pair[0]].end() > next_start {
+                    cues[pair[0]].set_end(next_start)
+                }
+            }
+        }
+        OverlapStrategy::Merge => {
+            merge_clusters(subtitle, |cluster_end, start, _| start < cluster_end);
+        }
+    }
+    subtitle.renumber();
+
+    overlaps
+}
+
+impl SSA {
+    /// Keeps overlapping events but places them so that they don't cover each other. See
+    /// [StackMode] for the available placements.
+    pub fn stack_overlaps(&mut self, mode: StackMode) {
+        for cluster in clusters(self, |cluster_end, start, _| start < cluster_end) {
+            let base_layer = self.events[cluster[0]].layer;
+            for (position, &i) in cluster.iter().enumerate().skip(1) {
+                let event = &mut self.events[i];
+                match mode {
+                    StackMode::Layers => event.layer = base_layer + position as u32,
+                    StackMode::Alignment => {
+                        if position % 2 == 1 && !event.text.contains("\\an") {
+                            event.text = format!("{{\\an8}}{}", event.text)
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Indices of all cues, ordered by start. Cues starting at the same time keep their order.
+fn chronological_order<S: Subtitle>(subtitle: &S) -> Vec<usize> {
+    let cues = subtitle.cues();
+    let mut order: Vec<usize> = (0..cues.len()).collect();
+    order.sort_by_key(|&i| cues[i].start());
+    order
+}
+
+/// Groups the cues in chronological order. A cue belongs to the current group if `belongs`,
+/// called with the latest end of the group, the start of the cue and the start of the group (all
+/// in milliseconds), returns `true`. Only groups with more than one cue are returned.
+fn clusters<S: Subtitle, F: Fn(i64, i64, i64) -> bool>(
+    subtitle: &S,
+    belongs: F,
+) -> Vec<Vec<usize>> {
+    let cues = subtitle.cues();
+    let mut clusters: Vec<Vec<usize>> = vec![];
+    let mut cluster_start = 0;
+    let mut cluster_end = 0;
+
+    for i in chronological_order(subtitle) {
+        let start = time_to_millis(cues[i].start());
+        let end = time_to_millis(cues[i].end());
+        match clusters.last_mut() {
+            Some(cluster) if belongs(cluster_end, start, cluster_start) => {
+                cluster.push(i);
+                cluster_end = cluster_end.max(end)
+            }
+            _ => {
+                clusters.push(vec![i]);
+                cluster_start = start;
+                cluster_end = end
+            }
+        }
+    }
+
+    clusters.retain(|c| c.len() > 1);
+    clusters
+}
+
+/// Merges every cluster (see [clusters]) into its first cue and removes the others.
+fn merge_clusters<S: Subtitle, F: Fn(i64, i64, i64) -> bool>(subtitle: &mut S, belongs: F) {
+    let clusters = clusters(subtitle, belongs);
+    let cues = subtitle.cues_mut();
+    let mut removed = vec![false; cues.len()];
+
+    for cluster in clusters {
+        let end = cluster.iter().map(|&i| cues[i].end()).max().unwrap();
+        let text = cluster
+            .iter()
+            .map(|&i| {
+                let text = cues[i].text();
+                if text.trim_start().starts_with('-') {
+                    text.to_string()
+                } else {
+                    format!("- {text}")
+                }
+            })
+            .collect::<Vec<String>>()
+            .join(S::Cue::LINE_BREAK);
+
+        let first = &mut cues[cluster[0]];
+        first.set_end(end);
+        first.set_text(text);
+        for &i in &cluster[1..] {
+            removed[i] = true
+        }
+    }
+
+    let mut removed = removed.into_iter();
+    cues.retain(|_| !removed.next().unwrap());
+}
@@ -0,0 +1,110 @@
+//! Implements a time ordered stream of playback events.
+//!
+//! It describes the [Playback] struct, which turns the cues of a [Subtitle] into
+//! [PlaybackEvent]s. Players can advance a clock through the stream and only have to react to the
+//! returned events instead of checking every cue on every frame.
+
+use serde::{Deserialize, Serialize};
+use time::Time;
+
+use crate::cue::{Cue, Subtitle};
+
+/// Something that happens to a cue at a specific time.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub struct PlaybackEvent {
+    pub time: Time,
+    /// Index of the cue in [Subtitle::cues].
+    pub cue: usize,
+    pub kind: PlaybackEventKind,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub enum PlaybackEventKind {
+    /// The cue got visible.
+    Shown,
+    /// The cue got hidden.
+    Hidden,
+    /// An inline timestamp of the cue (see [Cue::inline_timestamps]) was reached. All text
+    /// before `offset` in [Cue::text] is now in the past, everything after it in the future.
+    Step { offset: usize },
+}
+
+impl PlaybackEventKind {
+    /// Order of events at the same time: cues are hidden before new ones are shown, and steps
+    /// happen once their cue is visible.
+    fn order(&self) -> u8 {
+        match self {
+            PlaybackEventKind::Hidden => 0,
+            PlaybackEventKind::Shown => 1,
+            PlaybackEventKind::Step { .. } => 2,
+        }
+    }
+}
+
+/// A time ordered stream of [PlaybackEvent]s.
+///
+/// Cues with no duration are never shown. Inline timestamps outside the time a cue is visible are
+/// ignored.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Playback {
+    events: Vec<PlaybackEvent>,
+    position: usize,
+}
+
+impl Playback {
+    /// Creates the event stream of all cues of `subtitle`.
+    pub fn new<S: Subtitle>(subtitle: &S) -> Self {
+        let mut events = vec![];
+        for (i, cue) in subtitle.cues().iter().enumerate() {
+            let (start, end) = (cue.start(), cue.end());
+            if start >= end {
+                continue;
+            }
+            let event = |time, kind| PlaybackEvent { time, cue: i, kind };
+
+            events.push(event(start, PlaybackEventKind::Shown));
+            for (offset, time) in cue.inline_timestamps() {
+                if time >= start && time < end {
+                    events.push(event(time, PlaybackEventKind::Step { offset }))
+                }
+            }
+            events.push(event(end, PlaybackEventKind::Hidden));
+        }
+        events.sort_by_key(|e| (e.time, e.kind.order(), e.cue));
+
+        Self {
+            events,
+            position: 0,
+        }
+    }
+
+    /// All events, regardless of the current position.
+    pub fn events(&self) -> &[PlaybackEvent] {
+        &self.events
+    }
+
+    /// Returns all events up to and including `time` that weren't returned yet.
+    pub fn advance(&mut self, time: Time) -> &[PlaybackEvent] {
+        let start = self.position;
+        self.position += self.events[start..].partition_point(|e| e.time <= time);
+        &self.events[start..self.position]
+    }
+
+    /// Moves the position, so that the next returned event is the first one at or after `time`.
+    /// Use [TimeIndex](crate::TimeIndex) to find out which cues are visible at the new position.
+    pub fn seek(&mut self, time: Time) {
+        self.position = self.events.partition_point(|e| e.time < time)
+    }
+}
+
+impl Iterator for Playback {
+    type Item = PlaybackEvent;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let event = self.events.get(self.position).cloned();
+        if event.is_some() {
+            self.position += 1
+        }
+        event
+    }
+}
@@ -0,0 +1,616 @@
+//! Implements helpers for SAMI `.smi`.
+//!
+//! It describes the [SAMI], [SAMIClass] and [SAMILine] structs and
+//! provides the [SAMI::parse] function.
+//!
+//! A file may contain several languages, each one being a class defined in the `<STYLE>` block
+//! and referenced by the `<P>` elements. Use [SAMI::select] or [SAMI::split] to get the lines of a
+//! single language before converting.
+
+use regex::Regex;
+use serde::Deserialize;
+use serde::Serialize;
+use std::fmt::Display;
+use time::{Duration, Time};
+
+use crate::error;
+use crate::util::{millis_to_time, time_to_millis, Color};
+
+use super::cue::{strip_markup, Cue, Subtitle};
+use super::srt::{SRTLine, SRT};
+use super::ssa::{SSAEvent, SSAStyle, SSA};
+use super::strip_bom;
+use super::vtt::{VTTLine, VTT};
+
+/// How long the last line of a class is shown if no `<SYNC>` clears it.
+const LAST_LINE_DURATION: Duration = Duration::seconds(5);
+
+/// Contains the title, the language classes and a Vec<[SAMILine]>
+///
+/// The `.smi` format is HTML-like and generally looks like:
+///```text
+/// <SAMI>
+/// <HEAD>
+/// <STYLE TYPE="text/css">
+/// <!--
+/// .KRCC { Name: Korean; lang: ko-KR; }
+/// .ENCC { Name: English; lang: en-US; }
+/// -->
+/// </STYLE>
+/// </HEAD>
+/// <BODY>
+/// <SYNC Start=1000><P Class=KRCC>안녕하세요
+/// <SYNC Start=1000><P Class=ENCC>Hello<br>world
+/// <SYNC Start=3000><P Class=KRCC>&nbsp;
+/// <SYNC Start=3000><P Class=ENCC>&nbsp;
+/// </BODY>
+/// </SAMI>
+/// ```
+/// A `<SYNC>` shows the text of its `<P>` until the next `<SYNC>` of the same class. Empty texts
+/// (`&nbsp;`) only clear the screen.
+#[derive(Clone, Debug, Default, Eq, PartialEq, Deserialize, Serialize)]
+pub struct SAMI {
+    pub title: Option<String>,
+    pub classes: Vec<SAMIClass>,
+    pub lines: Vec<SAMILine>,
+}
+
+/// A language class of the `<STYLE>` block, like `.ENCC { Name: English; lang: en-US; }`.
+#[derive(Clone, Debug, Default, Eq, PartialEq, Deserialize, Serialize)]
+pub struct SAMIClass {
+    /// The class name used by the `<P>` elements, without the leading dot.
+    pub name: String,
+    /// The `Name` property, a human readable name of the language.
+    pub display_name: Option<String>,
+    /// The `lang` property, a language tag like `en-US`.
+    pub lang: Option<String>,
+}
+
+/// Describes each line
+///
+/// The text keeps its HTML markup (`<i>`, `<font color="...">`, ...) with whitespace collapsed
+/// like a browser would. Displayed lines are separated by `<br>`.
+#[derive(Clone, Debug, Eq, PartialEq, Deserialize, Serialize)]
+pub struct SAMILine {
+    pub start: Time,
+    pub end: Time,
+    /// The class of the `<P>` element, [None] if it has none.
+    pub class: Option<String>,
+    pub text: String,
+}
+
+impl Default for SAMILine {
+    fn default() -> Self {
+        Self {
+            start: Time::from_hms(0, 0, 0).unwrap(),
+            end: Time::from_hms(0, 0, 0).unwrap(),
+            class: None,
+            text: "".to_string(),
+        }
+    }
+}
+
+impl SAMI {
+    /// Parses the given [String] into a [SAMI].
+    ///
+    /// Classes used by `<P>` elements but not defined in the `<STYLE>` block are added to
+    /// [SAMI::classes] as well. Lines are ordered by their start, lines of different classes
+    /// starting at the same time keep the order of the file.
+    pub fn parse<S: AsRef<str>>(content: S) -> Result<SAMI, SAMIError> {
+        let content = strip_bom(&content);
+        let line_of = |offset: usize| content[..offset].matches('\n').count() + 1;
+
+        let sami_regex = Regex::new(r"(?i)<sami\b").unwrap();
+        if !sami_regex.is_match(content) {
+            return Err(SAMIError::new(SAMIErrorKind::Invalid, 1));
+        }
+
+        let mut sami = SAMI::default();
+        let title_regex = Regex::new(r"(?is)<title[^>]*>(.*?)</title>").unwrap();
+        sami.title = title_regex
+            .captures(content)
+            .map(|c| parse::collapse_whitespace(&c[1]))
+            .filter(|t| !t.is_empty());
+        let style_regex = Regex::new(r"(?is)<style[^>]*>(.*?)</style>").unwrap();
+        if let Some(style) = style_regex.captures(content) {
+            sami.classes = parse::classes(&style[1])
+        }
+
+        let body_start = Regex::new(r"(?i)<body[^>]*>")
+            .unwrap()
+            .find(content)
+            .map_or(0, |m| m.end());
+        let body_end = Regex::new(r"(?i)</body\s*>")
+            .unwrap()
+            .find_at(content, body_start)
+            .map_or(content.len(), |m| m.start());
+
+        // the texts of each class at every <SYNC>, an empty text clears the screen
+        let sync_regex = Regex::new(r"(?i)<sync\b([^>]*)>").unwrap();
+        let start_regex = Regex::new(r#"(?i)\bstart\s*=\s*["']?([^"'\s>]*)"#).unwrap();
+        let mut entries: Vec<(Time, Option<String>, String)> = vec![];
+        let syncs: Vec<_> = sync_regex.captures_iter(&content[..body_end]).collect();
+        for (i, sync) in syncs.iter().enumerate() {
+            let tag = sync.get(0).unwrap();
+            if tag.start() < body_start {
+                continue;
+            }
+            let start = start_regex
+                .captures(&sync[1])
+                .map(|c| c[1].to_string())
+                .unwrap_or_default();
+            let start = start.parse::<i64>().map_err(|_| {
+                SAMIError::new(
+                    SAMIErrorKind::Parse(format!("invalid start '{start}'")),
+                    line_of(tag.start()),
+                )
+            })?;
+            let start = millis_to_time(start);
+
+            let end = syncs
+                .get(i + 1)
+                .map_or(body_end, |s| s.get(0).unwrap().start());
+            for (class, text) in parse::paragraphs(&content[tag.end()..end]) {
+                entries.push((start, class, text))
+            }
+        }
+
+        for (i, (start, class, text)) in entries.iter().enumerate() {
+            if parse::is_clear(text) {
+                continue;
+            }
+            if let Some(class) = class {
+                if !sami
+                    .classes
+                    .iter()
+                    .any(|c| c.name.eq_ignore_ascii_case(class))
+                {
+                    sami.classes.push(SAMIClass {
+                        name: class.clone(),
+                        ..Default::default()
+                    })
+                }
+            }
+            let end = entries[i + 1..]
+                .iter()
+                .find(|(next, next_class, _)| next > start && parse::same_class(class, next_class))
+                .map_or(*start + LAST_LINE_DURATION, |(next, _, _)| *next);
+            sami.lines.push(SAMILine {
+                start: *start,
+                end,
+                class: class.clone(),
+                text: text.clone(),
+            })
+        }
+        sami.lines.sort_by_key(|l| l.start);
+
+        Ok(sami)
+    }
+
+    /// Returns a [SAMI] containing only the class `class` (case insensitive) and its lines.
+    /// Lines without a class are part of every language.
+    pub fn select(&self, class: &str) -> SAMI {
+        SAMI {
+            title: self.title.clone(),
+            classes: self
+                .classes
+                .iter()
+                .filter(|c| c.name.eq_ignore_ascii_case(class))
+                .cloned()
+                .collect(),
+            lines: self
+                .lines
+                .iter()
+                .filter(|l| !matches!(&l.class, Some(c) if !c.eq_ignore_ascii_case(class)))
+                .cloned()
+                .collect(),
+        }
+    }
+
+    /// Splits into one [SAMI] per class, see [SAMI::select]. A file without classes is returned
+    /// as is.
+    pub fn split(&self) -> Vec<SAMI> {
+        if self.classes.is_empty() {
+            return vec![self.clone()];
+        }
+        self.classes.iter().map(|c| self.select(&c.name)).collect()
+    }
+
+    /// Converts to [SRT]. `<i>`, `<b>`, `<u>` and `<font color="...">` tags are kept, all other
+    /// markup is removed. Lines of all classes are converted, use [SAMI::select] to get a single
+    /// language.
+    pub fn to_srt(&self) -> SRT {
+        SRT {
+            lines: self
+                .lines
+                .iter()
+                .enumerate()
+                .map(|(i, line)| {
+                    let mut text = String::new();
+                    for token in parse::tokens(&line.text) {
+                        match token {
+                            parse::Token::Text(t) => text.push_str(&t),
+                            parse::Token::Break => text.push_str("\r\n"),
+                            parse::Token::Open(tag, None) => text.push_str(&format!("<{tag}>")),
+                            parse::Token::Open(_, Some(color)) => text
+                                .push_str(&format!("<font color=\"{}\">", color.to_vtt_string())),
+                            parse::Token::Close(tag) => text.push_str(&format!("</{tag}>")),
+                        }
+                    }
+                    SRTLine {
+                        sequence_number: i as u32 + 1,
+                        start: line.start,
+                        end: line.end,
+                        text,
+                    }
+                })
+                .collect(),
+        }
+    }
+
+    /// Converts to [VTT]. `<i>`, `<b>` and `<u>` tags are kept, all other markup is removed. Lines
+    /// of all classes are converted, use [SAMI::select] to get a single language.
+    pub fn to_vtt(&self) -> VTT {
+        VTT {
+            lines: self
+                .lines
+                .iter()
+                .map(|line| {
+                    let mut text = String::new();
+                    for token in parse::tokens(&line.text) {
+                        match token {
+                            parse::Token::Text(t) => {
+                                text.push_str(&t.replace('&', "&amp;").replace('<', "&lt;"))
+                            }
+                            parse::Token::Break => text.push('\n'),
+                            parse::Token::Open(tag, None) => text.push_str(&format!("<{tag}>")),
+                            parse::Token::Close(tag) if tag != "font" => {
+                                text.push_str(&format!("</{tag}>"))
+                            }
+                            _ => (),
+                        }
+                    }
+                    VTTLine {
+                        start: line.start,
+                        end: line.end,
+                        text,
+                        ..Default::default()
+                    }
+                })
+                .collect(),
+            ..Default::default()
+        }
+    }
+
+    /// Converts to [SSA]. `<i>`, `<b>`, `<u>` and `<font color="...">` tags are converted to
+    /// override tags, all other markup is removed. Lines of all classes are converted, use
+    /// [SAMI::select] to get a single language.
+    pub fn to_ssa(&self) -> SSA {
+        let default_style = SSAStyle {
+            name: "Default".to_string(),
+            fontname: "Arial".to_string(),
+            fontsize: 20.0,
+            ..Default::default()
+        };
+
+        let mut events = vec![];
+        for line in &self.lines {
+            let mut text = String::new();
+            // open colors, the previous one is restored when a <font> is closed
+            let mut colors: Vec<Color> = vec![];
+            for token in parse::tokens(&line.text) {
+                match token {
+                    parse::Token::Text(t) => text.push_str(&t),
+                    parse::Token::Break => text.push_str("\\N"),
+                    parse::Token::Open(tag, None) => text.push_str(&format!("{{\\{tag}1}}")),
+                    parse::Token::Open(_, Some(color)) => {
+                        text.push_str(&format!("{{\\c{}&}}", color.to_ssa_string()));
+                        colors.push(color)
+                    }
+                    parse::Token::Close("font") => {
+                        colors.pop();
+                        match colors.last() {
+                            Some(color) => {
+                                text.push_str(&format!("{{\\c{}&}}", color.to_ssa_string()))
+                            }
+                            None => text.push_str("{\\c}"),
+                        }
+                    }
+                    parse::Token::Close(tag) => text.push_str(&format!("{{\\{tag}0}}")),
+                }
+            }
+
+            events.push(SSAEvent {
+                start: line.start,
+                end: line.end,
+                style: "Default".to_string(),
+                text,
+                ..Default::default()
+            })
+        }
+
+        SSA {
+            styles: vec![default_style],
+            events,
+            ..Default::default()
+        }
+    }
+}
+
+impl Cue for SAMILine {
+    const LINE_BREAK: &'static str = "<br>";
+
+    fn start(&self) -> Time {
+        self.start
+    }
+    fn end(&self) -> Time {
+        self.end
+    }
+    fn set_start(&mut self, start: Time) {
+        self.start = start
+    }
+    fn set_end(&mut self, end: Time) {
+        self.end = end
+    }
+    fn text(&self) -> &str {
+        &self.text
+    }
+    fn set_text(&mut self, text: String) {
+        self.text = text
+    }
+    /// Converts `<br>` to newlines, removes all other tags and resolves character references.
+    fn plain_text(&self) -> String {
+        let text = Regex::new(r"(?i)<br\s*/?>")
+            .unwrap()
+            .replace_all(&self.text, "\n");
+        parse::unescape(&strip_markup(&text, '<', '>'))
+    }
+    fn style(&self) -> Option<&str> {
+        self.class.as_deref()
+    }
+    fn set_style(&mut self, style: String) {
+        self.class = Some(style)
+    }
+}
+
+impl Subtitle for SAMI {
+    type Cue = SAMILine;
+
+    fn cues(&self) -> &[SAMILine] {
+        &self.lines
+    }
+    fn cues_mut(&mut self) -> &mut Vec<SAMILine> {
+        &mut self.lines
+    }
+}
+
+impl Display for SAMI {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut lines = vec!["<SAMI>".to_string(), "<HEAD>".to_string()];
+        if let Some(title) = &self.title {
+            lines.push(format!("<TITLE>{title}</TITLE>"))
+        }
+        lines.push("<STYLE TYPE=\"text/css\">".to_string());
+        lines.push("<!--".to_string());
+        lines.push(
+            "P { font-family: Arial; font-weight: normal; color: white; text-align: center; }"
+                .to_string(),
+        );
+        for class in &self.classes {
+            let mut properties = vec![];
+            if let Some(name) = &class.display_name {
+                properties.push(format!("Name: {name};"))
+            }
+            if let Some(lang) = &class.lang {
+                properties.push(format!("lang: {lang};"))
+            }
+            lines.push(format!(".{} {{ {} }}", class.name, properties.join(" ")))
+        }
+        lines.push("-->".to_string());
+        lines.push("</STYLE>".to_string());
+        lines.push("</HEAD>".to_string());
+        lines.push("<BODY>".to_string());
+
+        // a line is cleared unless the next line of its class starts when it ends
+        let mut entries: Vec<(Time, Option<&String>, &str)> = vec![];
+        for (i, line) in self.lines.iter().enumerate() {
+            entries.push((line.start, line.class.as_ref(), &line.text));
+            let continued = self.lines[i + 1..]
+                .iter()
+                .find(|next| parse::same_class(&line.class, &next.class))
+                .is_some_and(|next| next.start == line.end);
+            if !continued {
+                entries.push((line.end, line.class.as_ref(), "&nbsp;"))
+            }
+        }
+        entries.sort_by_key(|(time, _, _)| *time);
+
+        for (time, class, text) in entries {
+            let class = class.map_or(String::new(), |c| format!(" Class={c}"));
+            lines.push(format!(
+                "<SYNC Start={}><P{class}>{text}",
+                time_to_millis(time)
+            ))
+        }
+        lines.push("</BODY>".to_string());
+        lines.push("</SAMI>".to_string());
+
+        write!(f, "{}", lines.join("\r\n"))
+    }
+}
+
+error! {
+    SAMIError => SAMIErrorKind {
+        Invalid,
+        Parse(String),
+    }
+}
+
+mod parse {
+    use super::*;
+
+    /// Parses the class definitions (`.ENCC { Name: English; lang: en-US; }`) of a `<STYLE>`
+    /// block.
+    pub(super) fn classes(style: &str) -> Vec<SAMIClass> {
+        let class_regex = Regex::new(r"\.([\w-]+)\s*\{([^}]*)\}").unwrap();
+        class_regex
+            .captures_iter(style)
+            .map(|c| {
+                let mut class = SAMIClass {
+                    name: c[1].to_string(),
+                    ..Default::default()
+                };
+                for property in c[2].split(';') {
+                    let Some((name, value)) = property.split_once(':') else {
+                        continue;
+                    };
+                    let value = Some(value.trim().to_string()).filter(|v| !v.is_empty());
+                    match name.trim().to_lowercase().as_str() {
+                        "name" => class.display_name = value,
+                        "lang" => class.lang = value,
+                        _ => (),
+                    }
+                }
+                class
+            })
+            .collect()
+    }
+
+    /// Returns the class and text of every `<P>` in the content of a `<SYNC>`. Content without
+    /// a `<P>` is treated as a single paragraph without class.
+    pub(super) fn paragraphs(content: &str) -> Vec<(Option<String>, String)> {
+        let p_regex = Regex::new(r"(?i)<p\b([^>]*)>").unwrap();
+        let class_regex = Regex::new(r#"(?i)\bclass\s*=\s*["']?([^"'\s>]+)"#).unwrap();
+        let close_regex = Regex::new(r"(?i)</p\s*>").unwrap();
+
+        let tags: Vec<_> = p_regex.captures_iter(content).collect();
+        if tags.is_empty() {
+            let text = text(&close_regex.replace_all(content, ""));
+            return if text.is_empty() {
+                vec![]
+            } else {
+                vec![(None, text)]
+            };
+        }
+        tags.iter()
+            .enumerate()
+            .map(|(i, tag)| {
+                let start = tag.get(0).unwrap().end();
+                let end = tags
+                    .get(i + 1)
+                    .map_or(content.len(), |t| t.get(0).unwrap().start());
+                let class = class_regex.captures(&tag[1]).map(|c| c[1].to_string());
+                (
+                    class,
+                    text(&close_regex.replace_all(&content[start..end], "")),
+                )
+            })
+            .collect()
+    }
+
+    /// Collapses whitespace like a browser would and normalizes `<br>` tags.
+    fn text(content: &str) -> String {
+        let comment_regex = Regex::new(r"(?s)<!--.*?-->").unwrap();
+        let br_regex = Regex::new(r"(?i)\s*<br\s*/?>\s*").unwrap();
+        let content = comment_regex.replace_all(content, "");
+        br_regex
+            .replace_all(&collapse_whitespace(&content), "<br>")
+            .to_string()
+    }
+
+    pub(super) fn collapse_whitespace(text: &str) -> String {
+        text.split_whitespace().collect::<Vec<&str>>().join(" ")
+    }
+
+    /// Whether `text` only clears the screen (`&nbsp;`).
+    pub(super) fn is_clear(text: &str) -> bool {
+        unescape(&strip_markup(text, '<', '>'))
+            .replace('\u{a0}', " ")
+            .trim()
+            .is_empty()
+    }
+
+    /// Lines without a class belong to every class.
+    pub(super) fn same_class(class: &Option<String>, other: &Option<String>) -> bool {
+        match (class, other) {
+            (Some(class), Some(other)) => class.eq_ignore_ascii_case(other),
+            _ => true,
+        }
+    }
+
+    pub(super) fn unescape(text: &str) -> String {
+        text.replace("&nbsp;", "\u{a0}")
+            .replace("&lt;", "<")
+            .replace("&gt;", ">")
+            .replace("&quot;", "\"")
+            .replace("&apos;", "'")
+            .replace("&amp;", "&")
+    }
+
+    pub(super) enum Token {
+        Text(String),
+        Break,
+        /// `<i>`, `<b>`, `<u>` or `<font>`, with the color of a `<font>`.
+        Open(&'static str, Option<Color>),
+        Close(&'static str),
+    }
+
+    /// Splits a text into plain text, line breaks and formatting tags. Unknown tags and
+    /// `<font>`s without a valid color are dropped.
+    pub(super) fn tokens(text: &str) -> Vec<Token> {
+        let tag_regex = Regex::new(r"<(/?)([a-zA-Z]+)([^>]*)>").unwrap();
+        let color_regex = Regex::new(r#"(?i)\bcolor\s*=\s*["']?([^"'\s>]+)"#).unwrap();
+
+        let mut tokens = vec![];
+        // whether each open <font> produced a token, so that its closing tag does as well
+        let mut fonts = vec![];
+        let mut last = 0;
+        for c in tag_regex.captures_iter(text) {
+            let tag = c.get(0).unwrap();
+            if tag.start() > last {
+                tokens.push(Token::Text(unescape(&text[last..tag.start()])))
+            }
+            last = tag.end();
+
+            let close = &c[1] == "/";
+            let name = match c[2].to_lowercase().as_str() {
+                "br" => {
+                    tokens.push(Token::Break);
+                    continue;
+                }
+                "i" => "i",
+                "b" => "b",
+                "u" => "u",
+                "font" => "font",
+                _ => continue,
+            };
+            if name == "font" {
+                if close {
+                    if fonts.pop() == Some(true) {
+                        tokens.push(Token::Close(name))
+                    }
+                } else {
+                    let color = color_regex.captures(&c[3]).and_then(|c| {
+                        let color = &c[1];
+                        // colors are often written without the `#`
+                        Color::from_vtt(color)
+                            .or_else(|_| Color::from_vtt(&format!("#{color}")))
+                            .ok()
+                    });
+                    fonts.push(color.is_some());
+                    if color.is_some() {
+                        tokens.push(Token::Open(name, color))
+                    }
+                }
+            } else if close {
+                tokens.push(Token::Close(name))
+            } else {
+                tokens.push(Token::Open(name, None))
+            }
+        }
+        if last < text.len() {
+            tokens.push(Token::Text(unescape(&text[last..])))
+        }
+        tokens
+    }
+}
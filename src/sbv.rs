@@ -0,0 +1,242 @@
+//! Implements helpers for YouTube `.sbv`.
+//!
+//! It describes the [SBV] and [SBVLine] structs and
+//! provides the [SBV::parse] function.
+
+use regex::Regex;
+use serde::Deserialize;
+use serde::Serialize;
+use std::fmt::Display;
+use time::Time;
+
+use crate::error;
+use crate::util::{millis_to_time, time_to_millis};
+
+use super::cue::{Cue, Subtitle};
+use super::srt::{SRTLine, SRT};
+use super::ssa::SSA;
+use super::strip_bom;
+use super::vtt::VTT;
+
+/// Contains a Vec<[SBVLine]>
+///
+/// The `.sbv` format is exported by YouTube and generally looks like:
+///```text
+/// 0:00:00.000,0:00:02.000
+/// This is my text
+///
+/// 0:00:02.000,0:00:04.000
+/// This is my
+/// second text
+/// ```
+#[derive(Clone, Debug, Default, Eq, PartialEq, Deserialize, Serialize)]
+pub struct SBV {
+    pub lines: Vec<SBVLine>,
+}
+
+/// Describes each line
+///
+/// Displayed lines in the text are separated by `\n`.
+#[derive(Clone, Debug, Eq, PartialEq, Deserialize, Serialize)]
+pub struct SBVLine {
+    pub start: Time,
+    pub end: Time,
+    pub text: String,
+}
+
+impl Default for SBVLine {
+    fn default() -> Self {
+        Self {
+            start: Time::from_hms(0, 0, 0).unwrap(),
+            end: Time::from_hms(0, 0, 0).unwrap(),
+            text: "".to_string(),
+        }
+    }
+}
+
+impl SBV {
+    /// Parses the given [String] into a [SBV].
+    pub fn parse<S: AsRef<str>>(content: S) -> Result<SBV, SBVError> {
+        let time_regex = Regex::new(r"^(\d+):(\d{2}):(\d{2})\.(\d{3})$").unwrap();
+        let time = |value: &str| {
+            let c = time_regex.captures(value.trim())?;
+            let number = |i: usize| c[i].parse::<i64>().ok();
+            let (minutes, seconds) = (number(2)?, number(3)?);
+            if minutes > 59 || seconds > 59 {
+                return None;
+            }
+            number(1)?
+                .checked_mul(3_600_000)?
+                .checked_add(minutes * 60_000 + seconds * 1000 + number(4)?)
+                .map(millis_to_time)
+        };
+
+        let mut lines: Vec<SBVLine> = vec![];
+        let mut text: Option<Vec<&str>> = None;
+        for (i, line) in strip_bom(&content).lines().enumerate() {
+            let line = line.trim_end();
+            match &mut text {
+                // blank lines separate the blocks
+                Some(text) if !line.trim().is_empty() => text.push(line),
+                Some(_) => {
+                    lines.last_mut().unwrap().text = text.take().unwrap().join("\n");
+                }
+                None if line.trim().is_empty() => (),
+                None => {
+                    let (start, end) = line
+                        .split_once(',')
+                        .and_then(|(start, end)| Some((time(start)?, time(end)?)))
+                        .ok_or_else(|| {
+                            SBVError::new(
+                                SBVErrorKind::Parse(format!("invalid timing line '{line}'")),
+                                i + 1,
+                            )
+                        })?;
+                    lines.push(SBVLine {
+                        start,
+                        end,
+                        ..Default::default()
+                    });
+                    text = Some(vec![])
+                }
+            }
+        }
+        if let Some(text) = text {
+            lines.last_mut().unwrap().text = text.join("\n")
+        }
+
+        Ok(SBV { lines })
+    }
+
+    /// Converts to [SRT].
+    pub fn to_srt(&self) -> SRT {
+        SRT {
+            lines: self
+                .lines
+                .iter()
+                .enumerate()
+                .map(|(i, line)| SRTLine {
+                    sequence_number: i as u32 + 1,
+                    start: line.start,
+                    end: line.end,
+                    text: line.text.replace('\n', "\r\n"),
+                })
+                .collect(),
+        }
+    }
+
+    /// Converts to [VTT].
+    pub fn to_vtt(&self) -> VTT {
+        self.to_srt().to_vtt()
+    }
+
+    /// Converts to [SSA].
+    pub fn to_ssa(&self) -> SSA {
+        self.to_vtt().to_ssa()
+    }
+}
+
+impl SRT {
+    /// Converts to [SBV]. SBV has no markup, so all tags are removed.
+    pub fn to_sbv(&self) -> SBV {
+        SBV {
+            lines: self
+                .lines
+                .iter()
+                .map(|line| SBVLine {
+                    start: line.start,
+                    end: line.end,
+                    text: line.plain_text(),
+                })
+                .collect(),
+        }
+    }
+}
+
+impl VTT {
+    /// Converts to [SBV]. See [SRT::to_sbv].
+    pub fn to_sbv(&self) -> SBV {
+        self.to_srt().to_sbv()
+    }
+}
+
+impl SSA {
+    /// Converts to [SBV]. See [SRT::to_sbv].
+    pub fn to_sbv(&self) -> SBV {
+        self.to_srt().to_sbv()
+    }
+}
+
+impl Cue for SBVLine {
+    const LINE_BREAK: &'static str = "\n";
+
+    fn start(&self) -> Time {
+        self.start
+    }
+    fn end(&self) -> Time {
+        self.end
+    }
+    fn set_start(&mut self, start: Time) {
+        self.start = start
+    }
+    fn set_end(&mut self, end: Time) {
+        self.end = end
+    }
+    fn text(&self) -> &str {
+        &self.text
+    }
+    fn set_text(&mut self, text: String) {
+        self.text = text
+    }
+    /// The text is always plain.
+    fn plain_text(&self) -> String {
+        self.text.clone()
+    }
+}
+
+impl Subtitle for SBV {
+    type Cue = SBVLine;
+
+    fn cues(&self) -> &[SBVLine] {
+        &self.lines
+    }
+    fn cues_mut(&mut self) -> &mut Vec<SBVLine> {
+        &mut self.lines
+    }
+}
+
+impl Display for SBV {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let time = |time: Time| {
+            let millis = time_to_millis(time);
+            format!(
+                "{}:{:0>2}:{:0>2}.{:0>3}",
+                millis / 3_600_000,
+                millis / 60_000 % 60,
+                millis / 1000 % 60,
+                millis % 1000
+            )
+        };
+
+        let mut lines = vec![];
+        for line in &self.lines {
+            lines.push(format!("{},{}", time(line.start), time(line.end)));
+            // blank lines would end the block
+            lines.extend(
+                line.text
+                    .lines()
+                    .filter(|l| !l.trim().is_empty())
+                    .map(str::to_string),
+            );
+            lines.push(String::new())
+        }
+
+        write!(f, "{}", lines.join("\n"))
+    }
+}
+
+error! {
+    SBVError => SBVErrorKind {
+        Parse(String),
+    }
+}
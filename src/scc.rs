@@ -0,0 +1,688 @@
+//! Implements a decoder for Scenarist `.scc` files.
+//!
+//! It describes the [SCC], [SCCLine], [SCCRow] and [SCCSpan] structs and provides the
+//! [SCC::parse] function.
+//!
+//! SCC files contain CEA-608 byte pairs together with the SMPTE timecode (at 29.97 fps) they are
+//! sent at, one pair per frame. The decoder emulates the caption memory of a receiver in pop-on,
+//! roll-up and paint-on mode: every time the displayed caption changes, the previous line ends and
+//! a new one starts. Only the first caption channel (CC1) is decoded, other channels and XDS
+//! packets are skipped. Files can't be written, convert them to [VTT] or [SRT] instead.
+
+use serde::{Deserialize, Serialize};
+use time::{Duration, Time};
+
+use crate::error;
+use crate::util::{Color, FrameRate, Timecode};
+
+use super::srt::{SRTLine, SRT};
+use super::strip_bom;
+use super::vtt::{VTTLine, VTT};
+
+/// Duration of the last line if the file doesn't clear it.
+const LAST_LINE_DURATION: Duration = Duration::seconds(5);
+
+/// Contains a Vec<[SCCLine]>
+///
+/// The `.scc` format generally looks like:
+///```text
+/// Scenarist_SCC V1.0
+///
+/// 00:00:01:00    9420 9420 94ae 94ae 9470 9470 c8e5 ecec ef80 942f 942f
+///
+/// 00:00:03:00    942c 942c
+/// ```
+#[derive(Clone, Debug, Default, Eq, PartialEq, Deserialize, Serialize)]
+pub struct SCC {
+    pub lines: Vec<SCCLine>,
+}
+
+/// Describes each displayed caption
+#[derive(Clone, Debug, Eq, PartialEq, Deserialize, Serialize)]
+pub struct SCCLine {
+    pub start: Time,
+    pub end: Time,
+    /// The non-empty rows, from top to bottom.
+    pub rows: Vec<SCCRow>,
+}
+
+/// A row of the caption grid, without leading and trailing spaces.
+#[derive(Clone, Debug, Eq, PartialEq, Deserialize, Serialize)]
+pub struct SCCRow {
+    /// From 1 (top) to 15 (bottom).
+    pub row: u8,
+    /// The column of the first character, from 0 (left) to 31 (right).
+    pub column: u8,
+    pub spans: Vec<SCCSpan>,
+}
+
+/// Text with the same attributes.
+#[derive(Clone, Debug, Eq, PartialEq, Deserialize, Serialize)]
+pub struct SCCSpan {
+    pub text: String,
+    pub color: SCCColor,
+    pub italic: bool,
+    pub underline: bool,
+}
+
+/// The foreground colors of CEA-608.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Deserialize, Serialize)]
+pub enum SCCColor {
+    #[default]
+    White,
+    Green,
+    Blue,
+    Cyan,
+    Red,
+    Yellow,
+    Magenta,
+}
+
+impl SCCColor {
+    pub fn to_color(self) -> Color {
+        match self {
+            SCCColor::White => Color::new(255, 255, 255, 255),
+            SCCColor::Green => Color::new(0, 255, 0, 255),
+            SCCColor::Blue => Color::new(0, 0, 255, 255),
+            SCCColor::Cyan => Color::new(0, 255, 255, 255),
+            SCCColor::Red => Color::new(255, 0, 0, 255),
+            SCCColor::Yellow => Color::new(255, 255, 0, 255),
+            SCCColor::Magenta => Color::new(255, 0, 255, 255),
+        }
+    }
+
+    /// The default WebVTT color class.
+    fn vtt_class(self) -> &'static str {
+        match self {
+            SCCColor::White => "white",
+            SCCColor::Green => "lime",
+            SCCColor::Blue => "blue",
+            SCCColor::Cyan => "cyan",
+            SCCColor::Red => "red",
+            SCCColor::Yellow => "yellow",
+            SCCColor::Magenta => "magenta",
+        }
+    }
+}
+
+impl SCCLine {
+    /// Returns the text without attributes, rows are separated by `\n`.
+    pub fn text(&self) -> String {
+        self.rows
+            .iter()
+            .map(|row| row.spans.iter().map(|s| s.text.as_str()).collect())
+            .collect::<Vec<String>>()
+            .join("\n")
+    }
+}
+
+impl SCC {
+    /// Parses the given [String] into a [SCC].
+    pub fn parse<S: AsRef<str>>(content: S) -> Result<SCC, SCCError> {
+        let mut lines = strip_bom(&content)
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty());
+        match lines.next() {
+            Some((_, line)) if line.trim() == "Scenarist_SCC V1.0" => (),
+            other => {
+                return Err(SCCError::new(
+                    SCCErrorKind::Invalid,
+                    other.map_or(1, |(i, _)| i + 1),
+                ))
+            }
+        }
+
+        let mut decoder = parse::Decoder::default();
+        for (i, line) in lines {
+            let error = |message: String| SCCError::new(SCCErrorKind::Parse(message), i + 1);
+
+            let mut words = line.split_whitespace();
+            let timecode = words.next().unwrap_or_default();
+            let frame = Timecode::parse(timecode, FrameRate::FPS_29_97)
+                .map_err(|_| error(format!("invalid timecode '{timecode}'")))?
+                .to_frame(FrameRate::FPS_29_97);
+
+            for (j, word) in words.enumerate() {
+                let pair = (word.len() == 4)
+                    .then(|| u16::from_str_radix(word, 16).ok())
+                    .flatten()
+                    .ok_or_else(|| error(format!("invalid byte pair '{word}'")))?;
+                decoder.decode(
+                    (pair >> 8) as u8,
+                    pair as u8,
+                    FrameRate::FPS_29_97.frame_to_time(frame + j as i64),
+                )
+            }
+            // changes sent in one go are shown at once
+            decoder.flush()
+        }
+
+        Ok(SCC {
+            lines: decoder.finish(),
+        })
+    }
+
+    /// Converts to [SRT]. Colors, italics and underlines are converted to `<font>`, `<i>` and
+    /// `<u>` tags. Captions in the upper half of the screen get a `{\an8}` tag.
+    pub fn to_srt(&self) -> SRT {
+        SRT {
+            lines: self
+                .lines
+                .iter()
+                .enumerate()
+                .map(|(i, line)| {
+                    let mut text = line
+                        .rows
+                        .iter()
+                        .map(|row| {
+                            row.spans
+                                .iter()
+                                .map(|span| {
+                                    let mut text = span.text.clone();
+                                    if span.underline {
+                                        text = format!("<u>{text}</u>")
+                                    }
+                                    if span.italic {
+                                        text = format!("<i>{text}</i>")
+                                    }
+                                    if span.color != SCCColor::White {
+                                        text = format!(
+                                            "<font color=\"{}\">{text}</font>",
+                                            span.color.to_color().to_vtt_string()
+                                        )
+                                    }
+                                    text
+                                })
+                                .collect()
+                        })
+                        .collect::<Vec<String>>()
+                        .join("\r\n");
+                    if line.rows.last().is_some_and(|row| row.row <= 7) {
+                        text = format!("{{\\an8}}{text}")
+                    }
+                    SRTLine {
+                        sequence_number: i as u32 + 1,
+                        start: line.start,
+                        end: line.end,
+                        text,
+                    }
+                })
+                .collect(),
+        }
+    }
+
+    /// Converts to [VTT]. The top row and the leftmost column are converted to `line`, `position`
+    /// and `align` settings, mapping the caption grid to the 80% safe area. Colors are converted
+    /// to the default color classes (`<c.lime>`), italics and underlines to `<i>` and `<u>` tags.
+    pub fn to_vtt(&self) -> VTT {
+        let percent = |value: f64| format!("{}%", (value * 100.0).round() / 100.0);
+
+        VTT {
+            lines: self
+                .lines
+                .iter()
+                .map(|line| {
+                    let text = line
+                        .rows
+                        .iter()
+                        .map(|row| {
+                            row.spans
+                                .iter()
+                                .map(|span| {
+                                    let mut text =
+                                        span.text.replace('&', "&amp;").replace('<', "&lt;");
+                                    if span.underline {
+                                        text = format!("<u>{text}</u>")
+                                    }
+                                    if span.italic {
+                                        text = format!("<i>{text}</i>")
+                                    }
+                                    if span.color != SCCColor::White {
+                                        text = format!("<c.{}>{text}</c>", span.color.vtt_class())
+                                    }
+                                    text
+                                })
+                                .collect()
+                        })
+                        .collect::<Vec<String>>()
+                        .join("\n");
+
+                    let row = line.rows.first().map_or(15, |row| row.row);
+                    let column = line.rows.iter().map(|row| row.column).min().unwrap_or(0);
+                    VTTLine {
+                        start: line.start,
+                        end: line.end,
+                        settings: [
+                            (
+                                "line".to_string(),
+                                Some(percent(10.0 + (row - 1) as f64 * 80.0 / 15.0)),
+                            ),
+                            (
+                                "position".to_string(),
+                                Some(percent(10.0 + column as f64 * 80.0 / 32.0)),
+                            ),
+                            ("align".to_string(), Some("left".to_string())),
+                        ]
+                        .into(),
+                        text,
+                        ..Default::default()
+                    }
+                })
+                .collect(),
+            ..Default::default()
+        }
+    }
+}
+
+error! {
+    SCCError => SCCErrorKind {
+        Invalid,
+        Parse(String),
+    }
+}
+
+mod parse {
+    use super::*;
+
+    const ROWS: usize = 15;
+    const COLUMNS: usize = 32;
+
+    /// Rows of preamble address codes, indexed by the lower bits of the first byte and whether
+    /// the second byte is in the upper half.
+    const PAC_ROWS: [[usize; 2]; 8] = [
+        [11, 11],
+        [1, 2],
+        [3, 4],
+        [12, 13],
+        [14, 15],
+        [5, 6],
+        [7, 8],
+        [9, 10],
+    ];
+    /// Special characters, `0x11 0x30` to `0x11 0x3f`. `0x39` is a transparent space.
+    const SPECIAL: [char; 16] = [
+        '®', '°', '½', '¿', '™', '¢', '£', '♪', 'à', ' ', 'è', 'â', 'ê', 'î', 'ô', 'û',
+    ];
+    /// Extended Spanish, miscellaneous and French characters, `0x12 0x20` to `0x12 0x3f`.
+    const EXTENDED_1: [char; 32] = [
+        'Á', 'É', 'Ó', 'Ú', 'Ü', 'ü', '‘', '¡', '*', '\'', '—', '©', '℠', '•', '“', '”', 'À', 'Â',
+        'Ç', 'È', 'Ê', 'Ë', 'ë', 'Î', 'Ï', 'ï', 'Ô', 'Ù', 'ù', 'Û', '«', '»',
+    ];
+    /// Extended Portuguese, German and Danish characters, `0x13 0x20` to `0x13 0x3f`.
+    const EXTENDED_2: [char; 32] = [
+        'Ã', 'ã', 'Í', 'Ì', 'ì', 'Ò', 'ò', 'Õ', 'õ', '{', '}', '\\', '^', '_', '|', '~', 'Ä', 'ä',
+        'Ö', 'ö', 'ß', '¥', '¤', '│', 'Å', 'å', 'Ø', 'ø', '┌', '┐', '└', '┘',
+    ];
+
+    #[derive(Clone, Copy, Default, PartialEq)]
+    struct Attributes {
+        color: SCCColor,
+        italic: bool,
+        underline: bool,
+    }
+
+    type Memory = [[Option<(char, Attributes)>; COLUMNS]; ROWS];
+
+    #[derive(Clone, Copy, Default, PartialEq)]
+    enum Mode {
+        #[default]
+        PopOn,
+        /// Contains the amount of rows.
+        RollUp(usize),
+        PaintOn,
+        Text,
+    }
+
+    #[derive(Default)]
+    pub(super) struct Decoder {
+        mode: Mode,
+        displayed: Memory,
+        non_displayed: Memory,
+        /// Cursor position, starting at 0.
+        row: usize,
+        column: usize,
+        attributes: Attributes,
+        /// Bottom row of the roll-up window, starting at 0.
+        base_row: usize,
+        /// Whether the following characters belong to another channel or an XDS packet.
+        skip: bool,
+        /// The last control code, which is usually sent twice.
+        previous: Option<(u8, u8)>,
+        time: Option<Time>,
+        /// When the displayed memory was changed first since the last flush.
+        changed: Option<Time>,
+        current: Option<SCCLine>,
+        lines: Vec<SCCLine>,
+    }
+
+    impl Decoder {
+        pub(super) fn decode(&mut self, first: u8, second: u8, time: Time) {
+            // strip the parity bits
+            let (first, second) = (first & 0x7f, second & 0x7f);
+            self.time = Some(time);
+
+            match first {
+                0x00 if second == 0x00 => (),
+                0x01..=0x0e => {
+                    self.skip = true;
+                    self.previous = None
+                }
+                0x0f => self.previous = None,
+                0x10..=0x1f => {
+                    if self.previous.take() == Some((first, second)) {
+                        return;
+                    }
+                    self.previous = Some((first, second));
+
+                    // control codes of the second channel set bit 3, 0x15 / 0x1d are the
+                    // miscellaneous codes of the second field
+                    let misc = (0x20..=0x2f).contains(&second);
+                    self.skip = first & 0x08 != 0 || (first & 0x07 == 0x05 && misc);
+                    if self.skip {
+                        return;
+                    }
+                    match (first, second) {
+                        (0x14, 0x20..=0x2f) => self.control(second),
+                        (0x17, 0x21..=0x23) => {
+                            self.column = (self.column + (second - 0x20) as usize).min(COLUMNS - 1)
+                        }
+                        (0x11, 0x20..=0x2f) => self.mid_row(second),
+                        (0x11, 0x30..=0x3f) => self.write(SPECIAL[(second - 0x30) as usize]),
+                        (0x12, 0x20..=0x3f) => {
+                            self.backspace();
+                            self.write(EXTENDED_1[(second - 0x20) as usize])
+                        }
+                        (0x13, 0x20..=0x3f) => {
+                            self.backspace();
+                            self.write(EXTENDED_2[(second - 0x20) as usize])
+                        }
+                        (0x10..=0x17, 0x40..=0x7f) => self.preamble(first, second),
+                        _ => (),
+                    }
+                }
+                _ => {
+                    self.previous = None;
+                    if self.skip {
+                        return;
+                    }
+                    for byte in [first, second] {
+                        if byte >= 0x20 {
+                            self.write(character(byte))
+                        }
+                    }
+                }
+            }
+        }
+
+        /// Handles the miscellaneous control codes.
+        fn control(&mut self, code: u8) {
+            match code {
+                // resume caption loading
+                0x20 => self.mode = Mode::PopOn,
+                // backspace
+                0x21 => {
+                    self.backspace();
+                    self.touch()
+                }
+                // delete to end of row
+                0x24 => {
+                    let (row, column) = (self.row, self.column);
+                    if let Some(memory) = self.memory() {
+                        memory[row][column..].fill(None)
+                    }
+                    self.touch()
+                }
+                // roll-up captions with 2, 3 or 4 rows
+                0x25..=0x27 => {
+                    let rows = (code - 0x23) as usize;
+                    if !matches!(self.mode, Mode::RollUp(_)) {
+                        self.displayed = Default::default();
+                        self.non_displayed = Default::default();
+                        self.base_row = ROWS - 1;
+                        self.row = ROWS - 1;
+                        self.column = 0;
+                    }
+                    self.mode = Mode::RollUp(rows);
+                    self.base_row = self.base_row.max(rows - 1);
+                    for (i, row) in self.displayed.iter_mut().enumerate() {
+                        if i > self.base_row || i + rows <= self.base_row {
+                            *row = Default::default()
+                        }
+                    }
+                    self.touch()
+                }
+                // resume direct captioning
+                0x29 => self.mode = Mode::PaintOn,
+                // text restart, resume text display
+                0x2a | 0x2b => self.mode = Mode::Text,
+                // erase displayed memory
+                0x2c => {
+                    self.displayed = Default::default();
+                    self.show()
+                }
+                // carriage return
+                0x2d => {
+                    if let Mode::RollUp(rows) = self.mode {
+                        let top = self.base_row + 1 - rows;
+                        self.displayed.copy_within(top + 1..=self.base_row, top);
+                        self.displayed[self.base_row] = Default::default();
+                        self.column = 0;
+                        self.touch()
+                    }
+                }
+                // erase non-displayed memory
+                0x2e => self.non_displayed = Default::default(),
+                // end of caption
+                0x2f => {
+                    std::mem::swap(&mut self.displayed, &mut self.non_displayed);
+                    self.mode = Mode::PopOn;
+                    self.show()
+                }
+                // alarm and flash codes
+                _ => (),
+            }
+        }
+
+        /// Moves the cursor and sets the attributes.
+        fn preamble(&mut self, first: u8, second: u8) {
+            let mut row = PAC_ROWS[(first & 0x07) as usize][(second & 0x20 != 0) as usize] - 1;
+            let code = second & 0x1f;
+            self.attributes = if code < 0x10 {
+                style(code >> 1)
+            } else {
+                Attributes::default()
+            };
+            self.attributes.underline = code & 0x01 != 0;
+            self.column = if code < 0x10 {
+                0
+            } else {
+                ((code & 0x0e) >> 1) as usize * 4
+            };
+
+            if let Mode::RollUp(rows) = self.mode {
+                // the window moves with the base row
+                row = row.max(rows - 1);
+                if row != self.base_row {
+                    let mut displayed = Memory::default();
+                    for i in 0..rows {
+                        displayed[row - i] = self.displayed[self.base_row - i]
+                    }
+                    self.displayed = displayed;
+                    self.base_row = row;
+                    self.touch()
+                }
+            }
+            self.row = row
+        }
+
+        /// Inserts a space and changes the color or italics.
+        fn mid_row(&mut self, code: u8) {
+            self.write(' ');
+            let style = style((code & 0x0f) >> 1);
+            if style.italic {
+                self.attributes.italic = true
+            } else {
+                self.attributes.color = style.color;
+                self.attributes.italic = false
+            }
+            self.attributes.underline = code & 0x01 != 0
+        }
+
+        fn write(&mut self, character: char) {
+            let (row, column, attributes) = (self.row, self.column, self.attributes);
+            if let Some(memory) = self.memory() {
+                memory[row][column] = Some((character, attributes));
+                self.column = (column + 1).min(COLUMNS - 1);
+                self.touch()
+            }
+        }
+
+        fn backspace(&mut self) {
+            self.column = self.column.saturating_sub(1);
+            let (row, column) = (self.row, self.column);
+            if let Some(memory) = self.memory() {
+                memory[row][column] = None
+            }
+        }
+
+        /// The memory characters are written to.
+        fn memory(&mut self) -> Option<&mut Memory> {
+            match self.mode {
+                Mode::PopOn => Some(&mut self.non_displayed),
+                Mode::RollUp(_) | Mode::PaintOn => Some(&mut self.displayed),
+                Mode::Text => None,
+            }
+        }
+
+        /// Marks the displayed memory as changed.
+        fn touch(&mut self) {
+            if self.mode != Mode::PopOn {
+                self.changed = self.changed.or(self.time)
+            }
+        }
+
+        /// Shows the displayed memory immediately.
+        fn show(&mut self) {
+            self.flush();
+            self.changed = self.time;
+            self.flush()
+        }
+
+        /// Ends the current line and starts a new one if the displayed memory was changed.
+        pub(super) fn flush(&mut self) {
+            let Some(time) = self.changed.take() else {
+                return;
+            };
+            let rows = rows(&self.displayed);
+            if self.current.as_ref().is_some_and(|line| line.rows == rows) {
+                return;
+            }
+
+            if let Some(mut line) = self.current.take() {
+                if time > line.start {
+                    line.end = time;
+                    self.lines.push(line)
+                }
+            }
+            if !rows.is_empty() {
+                self.current = Some(SCCLine {
+                    start: time,
+                    end: time,
+                    rows,
+                })
+            }
+        }
+
+        pub(super) fn finish(mut self) -> Vec<SCCLine> {
+            self.flush();
+            if let Some(mut line) = self.current.take() {
+                line.end = line.start + LAST_LINE_DURATION;
+                self.lines.push(line)
+            }
+            self.lines
+        }
+    }
+
+    /// The attributes of preamble address and mid-row codes. `7` is white italics.
+    fn style(code: u8) -> Attributes {
+        let color = match code {
+            1 => SCCColor::Green,
+            2 => SCCColor::Blue,
+            3 => SCCColor::Cyan,
+            4 => SCCColor::Red,
+            5 => SCCColor::Yellow,
+            6 => SCCColor::Magenta,
+            _ => SCCColor::White,
+        };
+        Attributes {
+            color,
+            italic: code == 7,
+            underline: false,
+        }
+    }
+
+    /// The standard character set is ASCII with a few replacements.
+    fn character(byte: u8) -> char {
+        match byte {
+            0x2a => 'á',
+            0x5c => 'é',
+            0x5e => 'í',
+            0x5f => 'ó',
+            0x60 => 'ú',
+            0x7b => 'ç',
+            0x7c => '÷',
+            0x7d => 'Ñ',
+            0x7e => 'ñ',
+            0x7f => '█',
+            _ => byte as char,
+        }
+    }
+
+    /// Collects the non-empty rows of `memory`. Empty cells between characters become spaces.
+    fn rows(memory: &Memory) -> Vec<SCCRow> {
+        let visible = |cell: &Option<(char, Attributes)>| cell.is_some_and(|(c, _)| c != ' ');
+
+        memory
+            .iter()
+            .enumerate()
+            .filter_map(|(i, cells)| {
+                let first = cells.iter().position(visible)?;
+                let last = cells.iter().rposition(visible)?;
+
+                let mut spans: Vec<SCCSpan> = vec![];
+                let mut attributes = Attributes::default();
+                for cell in &cells[first..=last] {
+                    let character = match cell {
+                        Some((character, cell_attributes)) => {
+                            attributes = *cell_attributes;
+                            *character
+                        }
+                        None => ' ',
+                    };
+                    match spans.last_mut() {
+                        Some(span)
+                            if span.color == attributes.color
+                                && span.italic == attributes.italic
+                                && span.underline == attributes.underline =>
+                        {
+                            span.text.push(character)
+                        }
+                        _ => spans.push(SCCSpan {
+                            text: character.to_string(),
+                            color: attributes.color,
+                            italic: attributes.italic,
+                            underline: attributes.underline,
+                        }),
+                    }
+                }
+
+                Some(SCCRow {
+                    row: i as u8 + 1,
+                    column: first as u8,
+                    spans,
+                })
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,192 @@
+//! Implements shot change aware timing rules.
+//!
+//! Broadcast guidelines forbid cues from appearing or disappearing a few frames before or after a
+//! shot change, as this is perceived as flicker. [ShotChangeRules] checks cues against a list of
+//! shot changes and moves their in and out points onto the shot change where possible.
+//!
+//! Cues are expected to be in chronological order, the cues next to another one are always its
+//! neighbours in [Subtitle::cues].
+
+use serde::{Deserialize, Serialize};
+use time::Time;
+
+use crate::cue::{Cue, Subtitle};
+use crate::timing::{TimingChange, TimingChangeReason};
+use crate::util::{FrameRate, FrameRounding};
+
+/// Describes the forbidden zone around each shot change.
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct ShotChangeRules {
+    pub frame_rate: FrameRate,
+    /// Amount of frames before a shot change in which no cue may start or end.
+    pub zone_before: i64,
+    /// Amount of frames after a shot change in which no cue may start or end.
+    pub zone_after: i64,
+    /// Amount of frames a cue ending close to a shot change should end before it. `0` lets cues
+    /// disappear exactly at the shot change.
+    pub out_gap: i64,
+}
+
+impl Default for ShotChangeRules {
+    /// Cues may not start or end 1-11 frames around a shot change and should end 2 frames before
+    /// it, at 23.976 fps.
+    fn default() -> Self {
+        Self {
+            frame_rate: FrameRate::FPS_23_976,
+            zone_before: 11,
+            zone_after: 11,
+            out_gap: 2,
+        }
+    }
+}
+
+/// A cue starting or ending inside the forbidden zone of a shot change.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub struct ShotChangeViolation {
+    /// Index of the cue in [Subtitle::cues].
+    pub cue: usize,
+    pub edge: CueEdge,
+    /// The shot change the edge is too close to.
+    pub shot_change: Time,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub enum CueEdge {
+    Start,
+    End,
+}
+
+/// Result of [ShotChangeRules::fix].
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub struct ShotChangeReport {
+    /// All adjustments made.
+    pub changes: Vec<TimingChange>,
+    /// Violations that couldn't be fixed without making a cue empty or overlapping its
+    /// neighbours.
+    pub violations: Vec<ShotChangeViolation>,
+}
+
+impl ShotChangeRules {
+    /// Returns all cue starts and ends lying inside a forbidden zone, ordered by cue.
+    pub fn check<S: Subtitle>(
+        &self,
+        subtitle: &S,
+        shot_changes: &[Time],
+    ) -> Vec<ShotChangeViolation> {
+        let shot_changes = self.shot_change_frames(shot_changes);
+        let mut violations = vec![];
+
+        for (i, cue) in subtitle.cues().iter().enumerate() {
+            for edge in [CueEdge::Start, CueEdge::End] {
+                if let Some(shot_change) = self.violated_shot_change(cue, edge, &shot_changes) {
+                    violations.push(ShotChangeViolation {
+                        cue: i,
+                        edge,
+                        shot_change: self.frame_rate.frame_boundary(shot_change),
+                    })
+                }
+            }
+        }
+
+        violations
+    }
+
+    /// Moves every cue start inside a forbidden zone onto the shot change and every cue end inside
+    /// a forbidden zone [ShotChangeRules::out_gap] frames before the shot change.
+    pub fn fix<S: Subtitle>(&self, subtitle: &mut S, shot_changes: &[Time]) -> ShotChangeReport {
+        let shot_changes = self.shot_change_frames(shot_changes);
+        let frame_rate = self.frame_rate;
+        let frame = |time: Time| frame_rate.time_to_frame(time, FrameRounding::Up);
+        let cues = subtitle.cues_mut();
+        let mut report = ShotChangeReport::default();
+
+        for i in 0..cues.len() {
+            let (old_start, old_end) = (cues[i].start(), cues[i].end());
+
+            for edge in [CueEdge::Start, CueEdge::End] {
+                let Some(shot_change) = self.violated_shot_change(&cues[i], edge, &shot_changes)
+                else {
+                    continue;
+                };
+                let (start, end) = (frame(cues[i].start()), frame(cues[i].end()));
+
+                let fixed = match edge {
+                    CueEdge::Start => {
+                        let previous_end = i.checked_sub(1).map(|p| frame(cues[p].end()));
+                        let fixable = shot_change < end
+                            && !matches!(previous_end, Some(p) if p > shot_change);
+                        if fixable {
+                            cues[i].set_start(frame_rate.frame_boundary(shot_change))
+                        }
+                        fixable
+                    }
+                    CueEdge::End => {
+                        let target = shot_change - self.out_gap;
+                        let next_start = cues.get(i + 1).map(|n| frame(n.start()));
+                        let fixable =
+                            target > start && !matches!(next_start, Some(n) if target > n);
+                        if fixable {
+                            cues[i].set_end(frame_rate.frame_boundary(target))
+                        }
+                        fixable
+                    }
+                };
+                if !fixed {
+                    report.violations.push(ShotChangeViolation {
+                        cue: i,
+                        edge,
+                        shot_change: frame_rate.frame_boundary(shot_change),
+                    })
+                }
+            }
+
+            let (new_start, new_end) = (cues[i].start(), cues[i].end());
+            if (new_start, new_end) != (old_start, old_end) {
+                report.changes.push(TimingChange {
+                    cue: i,
+                    reason: TimingChangeReason::ShotChange,
+                    old_start,
+                    old_end,
+                    new_start,
+                    new_end,
+                })
+            }
+        }
+
+        report
+    }
+
+    /// Converts the shot changes to sorted frame numbers.
+    fn shot_change_frames(&self, shot_changes: &[Time]) -> Vec<i64> {
+        let mut frames: Vec<i64> = shot_changes
+            .iter()
+            .map(|t| self.frame_rate.time_to_frame(*t, FrameRounding::Nearest))
+            .collect();
+        frames.sort();
+        frames.dedup();
+        frames
+    }
+
+    /// Returns the frame of the shot change whose forbidden zone `edge` of `cue` lies in.
+    fn violated_shot_change<C: Cue>(
+        &self,
+        cue: &C,
+        edge: CueEdge,
+        shot_changes: &[i64],
+    ) -> Option<i64> {
+        let (time, allowed_offset) = match edge {
+            CueEdge::Start => (cue.start(), 0),
+            CueEdge::End => (cue.end(), -self.out_gap),
+        };
+        let frame = self.frame_rate.time_to_frame(time, FrameRounding::Up);
+
+        // all shot changes whose zone contains the frame
+        let first = shot_changes.partition_point(|c| *c < frame - self.zone_after);
+        shot_changes[first..]
+            .iter()
+            .take_while(|c| **c <= frame + self.zone_before)
+            .find(|c| frame - **c != allowed_offset)
+            .copied()
+    }
+}
@@ -0,0 +1,72 @@
+//! Implements cutting subtitles by time.
+//!
+//! [SRT::slice], [VTT::slice] and [SSA::slice] extract all cues between two timestamps,
+//! [SRT::split], [VTT::split] and [SSA::split] cut a subtitle into multiple parts. Every part
+//! keeps everything apart from the cues (styles, regions, script info, ...) of the original
+//! subtitle.
+//!
+//! Timing information inside the cue text (VTT timestamp tags, SSA `\k` or `\move` tags) is not
+//! adjusted.
+
+use time::Time;
+
+use crate::cue::{Cue, Subtitle};
+use crate::srt::SRT;
+use crate::ssa::SSA;
+use crate::util::{millis_to_time, time_to_millis};
+use crate::vtt::VTT;
+
+macro_rules! impl_slice {
+    ($($subtitle:ident),*) => {
+        $(
+            impl $subtitle {
+                /// Returns all cues visible between `start` and `end`, shifted so that `start`
+                /// becomes zero. Cues crossing the boundaries are trimmed.
+                pub fn slice(&self, start: Time, end: Time) -> $subtitle {
+                    slice(self, start, end)
+                }
+
+                /// Splits the subtitle at every given cut point. Returns one part more than cut
+                /// points are given, each part starting at zero. Cues crossing a cut point are
+                /// split into both parts.
+                pub fn split(&self, cut_points: &[Time]) -> Vec<$subtitle> {
+                    split(self, cut_points)
+                }
+            }
+        )*
+    };
+}
+
+impl_slice!(SRT, VTT, SSA);
+
+fn slice<S: Subtitle + Clone>(subtitle: &S, start: Time, end: Time) -> S {
+    let offset = time_to_millis(start);
+    let mut slice = subtitle.clone();
+
+    let cues = slice.cues_mut();
+    cues.retain(|cue| cue.start() < end && cue.end() > start);
+    for cue in cues {
+        cue.set_start(millis_to_time(
+            time_to_millis(cue.start().max(start)) - offset,
+        ));
+        cue.set_end(millis_to_time(time_to_millis(cue.end().min(end)) - offset));
+    }
+    slice.renumber();
+
+    slice
+}
+
+fn split<S: Subtitle + Clone>(subtitle: &S, cut_points: &[Time]) -> Vec<S> {
+    let mut cut_points = cut_points.to_vec();
+    cut_points.sort();
+
+    let mut parts = vec![];
+    let mut start = Time::MIDNIGHT;
+    for cut_point in cut_points {
+        parts.push(slice(subtitle, start, cut_point));
+        start = cut_point
+    }
+    parts.push(slice(subtitle, start, Time::MAX));
+
+    parts
+}
@@ -14,6 +14,7 @@ use time::macros::format_description;
 use crate::error;
 use time::Time;
 
+use super::cue::{strip_markup, Cue, Subtitle};
 use super::ssa::SSA;
 use super::strip_bom;
 use super::vtt::{VTTLine, VTT};
@@ -144,6 +145,52 @@ impl SRT {
     }
 }
 
+impl Cue for SRTLine {
+    const LINE_BREAK: &'static str = "\r\n";
+
+    fn start(&self) -> Time {
+        self.start
+    }
+    fn end(&self) -> Time {
+        self.end
+    }
+    fn set_start(&mut self, start: Time) {
+        self.start = start
+    }
+    fn set_end(&mut self, end: Time) {
+        self.end = end
+    }
+    fn text(&self) -> &str {
+        &self.text
+    }
+    fn set_text(&mut self, text: String) {
+        self.text = text
+    }
+    /// Removes html-like tags (`<i>`, `<font ...>`) as well as SSA override blocks (`{\an8}`),
+    /// which some `.srt` files contain.
+    fn plain_text(&self) -> String {
+        let text = strip_markup(&strip_markup(&self.text, '<', '>'), '{', '}');
+        text.lines().collect::<Vec<&str>>().join("\n")
+    }
+}
+
+impl Subtitle for SRT {
+    type Cue = SRTLine;
+
+    fn cues(&self) -> &[SRTLine] {
+        &self.lines
+    }
+    fn cues_mut(&mut self) -> &mut Vec<SRTLine> {
+        &mut self.lines
+    }
+    /// Sets the [SRTLine::sequence_number] of every line to its position, starting at `1`.
+    fn renumber(&mut self) {
+        for (i, line) in self.lines.iter_mut().enumerate() {
+            line.sequence_number = i as u32 + 1
+        }
+    }
+}
+
 impl Display for SRT {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let mut blocks = vec![];
@@ -16,6 +16,7 @@ use crate::util::Color;
 use crate::vtt::VTT;
 use time::Time;
 
+use super::cue::{strip_markup, Cue, Subtitle};
 use super::srt::{SRTLine, SRT};
 use super::strip_bom;
 
@@ -247,7 +248,7 @@ impl SSA {
         let mut blocks = Vec::new();
         for (i, line) in (1..).zip(strip_bom(&content).lines()) {
             match line.trim() {
-                l if l.is_empty() || l.starts_with(&[';', '#']) => continue,
+                l if l.is_empty() || l.starts_with([';', '#']) => continue,
                 l if l.starts_with('[') => blocks.push(vec![(i, line)]),
                 _ => {
                     if let Some(b) = blocks.last_mut() {
@@ -257,11 +258,7 @@ impl SSA {
             }
         }
 
-        if !blocks
-            .first()
-            .map(|b| &b[0])
-            .is_some_and(|l| l.1 == "[Script Info]")
-        {
+        if !matches!(blocks.first().map(|b| &b[0]), Some(l) if l.1 == "[Script Info]") {
             return Err(SSAError::new(SSAErrorKind::Invalid, 1));
         }
 
@@ -348,6 +345,57 @@ impl SSA {
     }
 }
 
+impl Cue for SSAEvent {
+    const LINE_BREAK: &'static str = "\\N";
+
+    fn start(&self) -> Time {
+        self.start
+    }
+    fn end(&self) -> Time {
+        self.end
+    }
+    fn set_start(&mut self, start: Time) {
+        self.start = start
+    }
+    fn set_end(&mut self, end: Time) {
+        self.end = end
+    }
+    fn text(&self) -> &str {
+        &self.text
+    }
+    fn set_text(&mut self, text: String) {
+        self.text = text
+    }
+    /// Removes all override blocks (`{\i1}`), converts hard line breaks (`\N`) to newlines, soft
+    /// line breaks (`\n`) to spaces and hard spaces (`\h`) to non-breaking spaces (U+00A0).
+    fn plain_text(&self) -> String {
+        strip_markup(&self.text, '{', '}')
+            .replace("\\N", "\n")
+            .replace("\\n", " ")
+            .replace("\\h", "\u{a0}")
+    }
+    fn layer(&self) -> u32 {
+        self.layer
+    }
+    fn style(&self) -> Option<&str> {
+        Some(&self.style)
+    }
+    fn set_style(&mut self, style: String) {
+        self.style = style
+    }
+}
+
+impl Subtitle for SSA {
+    type Cue = SSAEvent;
+
+    fn cues(&self) -> &[SSAEvent] {
+        &self.events
+    }
+    fn cues_mut(&mut self) -> &mut Vec<SSAEvent> {
+        &mut self.events
+    }
+}
+
 impl Display for SSA {
     #[rustfmt::skip]
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
@@ -532,7 +580,7 @@ mod parse {
         header_line: usize,
         mut block_lines: I,
     ) -> Result<(usize, Vec<&'a str>)> {
-        let (i, line) = block_lines.next().ok_or_else(|| Error {
+        let (i, line) = block_lines.next().ok_or(Error {
             line: header_line,
             kind: SSAErrorKind::EmptyBlock,
         })?;
@@ -646,11 +694,9 @@ mod parse {
                     .map_err(|e| map_parse_int_err(e, i))?,
                 outline: get_line_value(&headers, "Outline", &line_list, header_line, i)?
                     .parse()
-                    .map(|op: f32| f32::from(op))
                     .map_err(|e| map_parse_float_err(e, i))?,
                 shadow: get_line_value(&headers, "Shadow", &line_list, header_line, i)?
                     .parse()
-                    .map(|op: f32| f32::from(op))
                     .map_err(|e| map_parse_float_err(e, i))?,
                 alignment: Alignment::infer_from_str(get_line_value(
                     &headers,
@@ -662,19 +708,15 @@ mod parse {
                 .unwrap(),
                 margin_l: get_line_value(&headers, "MarginL", &line_list, header_line, i)?
                     .parse()
-                    .map(|op: f32| f32::from(op))
                     .map_err(|e| map_parse_float_err(e, i))?,
                 margin_r: get_line_value(&headers, "MarginR", &line_list, header_line, i)?
                     .parse()
-                    .map(|op: f32| f32::from(op))
                     .map_err(|e| map_parse_float_err(e, i))?,
                 margin_v: get_line_value(&headers, "MarginV", &line_list, header_line, i)?
                     .parse()
-                    .map(|op: f32| f32::from(op))
                     .map_err(|e| map_parse_float_err(e, i))?,
                 encoding: get_line_value(&headers, "Encoding", &line_list, header_line, i)?
                     .parse()
-                    .map(|op: f32| f32::from(op))
                     .map_err(|e| map_parse_float_err(e, i))?,
             })
         }
@@ -0,0 +1,439 @@
+//! Implements helpers for SubViewer `.sub`.
+//!
+//! It describes the [SubViewer], [SubViewerInfo] and [SubViewerLine] structs and
+//! provides the [SubViewer::parse] function.
+//!
+//! Both SubViewer 1.0 (`[00:00:01]` timestamps between the texts) and SubViewer 2.0
+//! (`00:00:01.00,00:00:03.00` time ranges) are supported.
+
+use serde::Deserialize;
+use serde::Serialize;
+use std::fmt::Display;
+use time::format_description::BorrowedFormatItem;
+use time::macros::format_description;
+use time::Time;
+
+use crate::error;
+
+use super::cue::{strip_markup, Cue, Subtitle};
+use super::srt::{SRTLine, SRT};
+use super::ssa::SSA;
+use super::strip_bom;
+use super::vtt::VTT;
+
+const TIME_FORMAT_V1: &[BorrowedFormatItem] = format_description!("[hour]:[minute]:[second]");
+const TIME_FORMAT_V2: &[BorrowedFormatItem] =
+    format_description!("[hour]:[minute]:[second].[subsecond digits:2]");
+
+/// Contains the header information and a Vec<[SubViewerLine]>
+///
+/// A SubViewer 2.0 file generally looks like:
+///```text
+/// [INFORMATION]
+/// [TITLE]My title
+/// [AUTHOR]Me
+/// [END INFORMATION]
+/// [SUBTITLE]
+/// 00:00:00.00,00:00:02.00
+/// This is my text[br]on two lines
+///
+/// 00:00:02.00,00:00:04.00
+/// This is my second text
+/// ```
+#[derive(Clone, Debug, Default, Eq, PartialEq, Deserialize, Serialize)]
+pub struct SubViewer {
+    pub version: SubViewerVersion,
+    pub info: SubViewerInfo,
+    pub lines: Vec<SubViewerLine>,
+}
+
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Deserialize, Serialize)]
+pub enum SubViewerVersion {
+    /// Timestamps with second precision in front of and after every text.
+    V1,
+    /// Time ranges with centisecond precision.
+    #[default]
+    V2,
+}
+
+/// The header fields of a SubViewer file. All values are kept as they are written in the file.
+#[derive(Clone, Debug, Default, Eq, PartialEq, Deserialize, Serialize)]
+pub struct SubViewerInfo {
+    pub title: Option<String>,
+    pub author: Option<String>,
+    pub source: Option<String>,
+    /// The program the file was created with (`[PRG]`).
+    pub program: Option<String>,
+    pub file_path: Option<String>,
+    pub delay: Option<String>,
+    pub cd_track: Option<String>,
+    pub comment: Option<String>,
+    /// The style line following `[SUBTITLE]` in SubViewer 2.0, e.g.
+    /// `[COLF]&HFFFFFF,[STYLE]bd,[SIZE]18,[FONT]Arial`.
+    pub style: Option<String>,
+
+    /// Header fields that aren't covered by the fields above, in the order they appear.
+    pub additional_fields: Vec<(String, String)>,
+}
+
+/// Describes each line
+///
+/// Displayed lines in the text are separated by `[br]`.
+#[derive(Clone, Debug, Eq, PartialEq, Deserialize, Serialize)]
+pub struct SubViewerLine {
+    pub start: Time,
+    pub end: Time,
+    pub text: String,
+}
+
+impl SubViewer {
+    /// Parses the given [String] into a [SubViewer]. The version is detected by the content.
+    pub fn parse<S: AsRef<str>>(content: S) -> Result<SubViewer, SubViewerError> {
+        let content = strip_bom(&content);
+        let lines: Vec<(usize, &str)> = (1..).zip(content.lines().map(|l| l.trim())).collect();
+
+        let is_v1 = lines.iter().any(|(_, line)| {
+            line.starts_with("******** START SCRIPT") || parse::parse_v1_timestamp(line).is_some()
+        });
+        if is_v1 {
+            parse::parse_v1(&lines)
+        } else {
+            parse::parse_v2(&lines)
+        }
+    }
+
+    /// Converts to [SRT].
+    pub fn to_srt(&self) -> SRT {
+        SRT {
+            lines: self
+                .lines
+                .iter()
+                .enumerate()
+                .map(|(i, line)| SRTLine {
+                    sequence_number: i as u32 + 1,
+                    start: line.start,
+                    end: line.end,
+                    text: line.text.replace("[br]", "\r\n"),
+                })
+                .collect(),
+        }
+    }
+
+    /// Converts to [VTT].
+    pub fn to_vtt(&self) -> VTT {
+        self.to_srt().to_vtt()
+    }
+
+    /// Converts to [SSA].
+    pub fn to_ssa(&self) -> SSA {
+        self.to_vtt().to_ssa()
+    }
+}
+
+impl SRT {
+    /// Converts to a SubViewer 2.0 [SubViewer]. SubViewer has no markup, so all tags are removed.
+    pub fn to_subviewer(&self) -> SubViewer {
+        SubViewer {
+            lines: self
+                .lines
+                .iter()
+                .map(|line| SubViewerLine {
+                    start: line.start,
+                    end: line.end,
+                    text: line
+                        .plain_text()
+                        .lines()
+                        .collect::<Vec<&str>>()
+                        .join("[br]"),
+                })
+                .collect(),
+            ..Default::default()
+        }
+    }
+}
+
+impl VTT {
+    /// Converts to a SubViewer 2.0 [SubViewer]. See [SRT::to_subviewer].
+    pub fn to_subviewer(&self) -> SubViewer {
+        self.to_srt().to_subviewer()
+    }
+}
+
+impl SSA {
+    /// Converts to a SubViewer 2.0 [SubViewer]. See [SRT::to_subviewer].
+    pub fn to_subviewer(&self) -> SubViewer {
+        self.to_srt().to_subviewer()
+    }
+}
+
+impl Cue for SubViewerLine {
+    const LINE_BREAK: &'static str = "[br]";
+
+    fn start(&self) -> Time {
+        self.start
+    }
+    fn end(&self) -> Time {
+        self.end
+    }
+    fn set_start(&mut self, start: Time) {
+        self.start = start
+    }
+    fn set_end(&mut self, end: Time) {
+        self.end = end
+    }
+    fn text(&self) -> &str {
+        &self.text
+    }
+    fn set_text(&mut self, text: String) {
+        self.text = text
+    }
+    /// Converts `[br]` to newlines and removes SSA-like override blocks (`{\i1}`), which some
+    /// players support.
+    fn plain_text(&self) -> String {
+        strip_markup(&self.text, '{', '}').replace("[br]", "\n")
+    }
+}
+
+impl Subtitle for SubViewer {
+    type Cue = SubViewerLine;
+
+    fn cues(&self) -> &[SubViewerLine] {
+        &self.lines
+    }
+    fn cues_mut(&mut self) -> &mut Vec<SubViewerLine> {
+        &mut self.lines
+    }
+}
+
+impl Display for SubViewer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let info = &self.info;
+        // only fields present in the parsed file are written
+        let fields = [
+            ("TITLE", &info.title),
+            ("AUTHOR", &info.author),
+            ("SOURCE", &info.source),
+            ("PRG", &info.program),
+            ("FILEPATH", &info.file_path),
+            ("DELAY", &info.delay),
+            ("CD TRACK", &info.cd_track),
+            ("COMMENT", &info.comment),
+        ]
+        .into_iter()
+        .filter_map(|(name, value)| value.clone().map(|value| (name, value)))
+        .chain(
+            info.additional_fields
+                .iter()
+                .map(|(name, value)| (name.as_str(), value.clone())),
+        );
+
+        let mut lines = vec![];
+        match self.version {
+            SubViewerVersion::V1 => {
+                for (name, value) in fields {
+                    lines.push(format!("[{name}]"));
+                    lines.push(value)
+                }
+                lines.push("[BEGIN]".to_string());
+                lines.push("******** START SCRIPT ********".to_string());
+                for (i, line) in self.lines.iter().enumerate() {
+                    lines.push(format!("[{}]", line.start.format(TIME_FORMAT_V1).unwrap()));
+                    lines.push(line.text.replace("[br]", "|"));
+                    // the end is given by the start of the next line if they touch
+                    if !matches!(self.lines.get(i + 1), Some(n) if n.start == line.end) {
+                        lines.push(format!("[{}]", line.end.format(TIME_FORMAT_V1).unwrap()));
+                        lines.push(String::new())
+                    }
+                }
+                lines.push("[end]".to_string());
+                lines.push("******** END SCRIPT ********".to_string());
+            }
+            SubViewerVersion::V2 => {
+                lines.push("[INFORMATION]".to_string());
+                for (name, value) in fields {
+                    lines.push(format!("[{name}]{value}"))
+                }
+                lines.push("[END INFORMATION]".to_string());
+                lines.push("[SUBTITLE]".to_string());
+                if let Some(style) = &info.style {
+                    lines.push(style.clone())
+                }
+                for line in &self.lines {
+                    lines.push(format!(
+                        "{},{}",
+                        line.start.format(TIME_FORMAT_V2).unwrap(),
+                        line.end.format(TIME_FORMAT_V2).unwrap()
+                    ));
+                    lines.push(line.text.clone());
+                    lines.push(String::new())
+                }
+            }
+        }
+
+        write!(f, "{}", lines.join("\r\n"))
+    }
+}
+
+error! {
+    SubViewerError => SubViewerErrorKind {
+        Parse(String),
+    }
+}
+
+mod parse {
+    use super::*;
+
+    type Result<T> = std::result::Result<T, SubViewerError>;
+
+    /// Parses a `[00:00:01]` line.
+    pub(super) fn parse_v1_timestamp(line: &str) -> Option<Time> {
+        let timestamp = line.strip_prefix('[')?.strip_suffix(']')?;
+        Time::parse(timestamp, TIME_FORMAT_V1).ok()
+    }
+
+    /// Parses a `[NAME]value` header line.
+    fn parse_field(line: &str) -> Option<(&str, &str)> {
+        line.strip_prefix('[')?.split_once(']')
+    }
+
+    fn set_field(info: &mut SubViewerInfo, name: &str, value: &str) {
+        let value = value.to_string();
+        match name.to_uppercase().as_str() {
+            "TITLE" => info.title = Some(value),
+            "AUTHOR" => info.author = Some(value),
+            "SOURCE" => info.source = Some(value),
+            "PRG" => info.program = Some(value),
+            "FILEPATH" => info.file_path = Some(value),
+            "DELAY" => info.delay = Some(value),
+            "CD TRACK" => info.cd_track = Some(value),
+            "COMMENT" => info.comment = Some(value),
+            _ => info.additional_fields.push((name.to_string(), value)),
+        }
+    }
+
+    pub(super) fn parse_v1(lines: &[(usize, &str)]) -> Result<SubViewer> {
+        let mut info = SubViewerInfo::default();
+        let mut iter = lines.iter().peekable();
+
+        // header fields, the value is in the line after the field name
+        while let Some((_, line)) = iter.next() {
+            if line.starts_with("******** START SCRIPT") {
+                break;
+            }
+            let Some((name, "")) = parse_field(line) else {
+                continue;
+            };
+            if name.eq_ignore_ascii_case("BEGIN") {
+                continue;
+            }
+            let value = match iter.peek() {
+                Some((_, value)) if !value.starts_with('[') => iter.next().unwrap().1,
+                _ => "",
+            };
+            set_field(&mut info, name, value)
+        }
+
+        // timestamps and texts. every text is visible until the next timestamp
+        let mut entries: Vec<(Time, Vec<&str>)> = vec![];
+        for (i, line) in iter {
+            if line.eq_ignore_ascii_case("[end]") || line.starts_with("******** END SCRIPT") {
+                break;
+            }
+            if let Some(time) = parse_v1_timestamp(line) {
+                entries.push((time, vec![]))
+            } else if let Some((_, text)) = entries.last_mut() {
+                if !line.is_empty() {
+                    text.push(line)
+                }
+            } else if !line.is_empty() {
+                return Err(SubViewerError::new(
+                    SubViewerErrorKind::Parse("text without timestamp".to_string()),
+                    *i,
+                ));
+            }
+        }
+
+        let lines = entries
+            .iter()
+            .enumerate()
+            .filter(|(_, (_, text))| !text.is_empty())
+            .map(|(i, (start, text))| SubViewerLine {
+                start: *start,
+                end: entries.get(i + 1).map_or(*start, |(end, _)| *end),
+                text: text.join("[br]").replace('|', "[br]"),
+            })
+            .collect();
+
+        Ok(SubViewer {
+            version: SubViewerVersion::V1,
+            info,
+            lines,
+        })
+    }
+
+    pub(super) fn parse_v2(lines: &[(usize, &str)]) -> Result<SubViewer> {
+        let mut info = SubViewerInfo::default();
+        let mut iter = lines.iter().peekable();
+
+        // header fields
+        while let Some((_, line)) = iter.peek() {
+            if line.is_empty() || line.eq_ignore_ascii_case("[INFORMATION]") {
+                iter.next();
+                continue;
+            }
+            if line.eq_ignore_ascii_case("[END INFORMATION]") {
+                iter.next();
+                continue;
+            }
+            if line.eq_ignore_ascii_case("[SUBTITLE]") {
+                iter.next();
+                // an optional style line follows
+                if let Some((_, style)) = iter.peek().filter(|(_, l)| l.starts_with('[')) {
+                    info.style = Some(style.to_string());
+                    iter.next();
+                }
+                break;
+            }
+            match parse_field(line) {
+                Some((name, value)) => {
+                    set_field(&mut info, name, value);
+                    iter.next();
+                }
+                None => break,
+            }
+        }
+
+        let mut subtitle_lines = vec![];
+        while let Some((i, line)) = iter.next() {
+            if line.is_empty() {
+                continue;
+            }
+            let (start, end) = line.split_once(',').ok_or(SubViewerError::new(
+                SubViewerErrorKind::Parse("invalid time range".to_string()),
+                *i,
+            ))?;
+            let parse_time = |time: &str| {
+                Time::parse(time.trim(), TIME_FORMAT_V2)
+                    .map_err(|e| SubViewerError::new(SubViewerErrorKind::Parse(e.to_string()), *i))
+            };
+            let (start, end) = (parse_time(start)?, parse_time(end)?);
+
+            let mut text = vec![];
+            while let Some((_, line)) = iter.next_if(|(_, l)| !l.is_empty()) {
+                text.push(*line)
+            }
+
+            subtitle_lines.push(SubViewerLine {
+                start,
+                end,
+                text: text.join("[br]"),
+            })
+        }
+
+        Ok(SubViewer {
+            version: SubViewerVersion::V2,
+            info,
+            lines: subtitle_lines,
+        })
+    }
+}
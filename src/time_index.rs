@@ -0,0 +1,123 @@
+//! Implements fast lookups of cues by time.
+//!
+//! It describes the [TimeIndex] struct, an interval tree over the cues of a [Subtitle], which
+//! answers which cues are visible at a given time or intersect a time range in `O(log n + k)`.
+//!
+//! A cue is visible from its start up to, but not including, its end.
+
+use time::Time;
+
+use crate::cue::{Cue, Subtitle};
+
+/// An interval tree over the cues of a subtitle.
+///
+/// The index doesn't borrow the subtitle, it has to be rebuilt after cues are changed.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct TimeIndex {
+    /// All cues, ordered by start. The tree is implicit: the root of every range is its middle
+    /// element.
+    entries: Vec<Entry>,
+    /// The latest end of all entries in the subtree rooted at the same position.
+    max_ends: Vec<Time>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Entry {
+    start: Time,
+    end: Time,
+    layer: u32,
+    index: usize,
+}
+
+impl TimeIndex {
+    /// Builds the index over all cues of `subtitle`.
+    pub fn new<S: Subtitle>(subtitle: &S) -> Self {
+        let mut entries: Vec<Entry> = subtitle
+            .cues()
+            .iter()
+            .enumerate()
+            .map(|(index, cue)| Entry {
+                start: cue.start(),
+                end: cue.end(),
+                layer: cue.layer(),
+                index,
+            })
+            .collect();
+        entries.sort_by_key(|e| e.start);
+
+        let mut index = Self {
+            max_ends: vec![Time::MIDNIGHT; entries.len()],
+            entries,
+        };
+        index.build(0, index.entries.len());
+        index
+    }
+
+    /// Number of indexed cues.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Whether no cues are indexed.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Returns the indices (in [Subtitle::cues]) of all cues visible at `time`, in drawing order:
+    /// ordered by [Cue::layer], cues on the same layer in the order they are stored.
+    pub fn at(&self, time: Time) -> Vec<usize> {
+        self.query(|e| e.start <= time, |end| end > time)
+    }
+
+    /// Returns the indices (in [Subtitle::cues]) of all cues visible at any time between `start`
+    /// and `end` (inclusive), in the same order as [TimeIndex::at].
+    pub fn between(&self, start: Time, end: Time) -> Vec<usize> {
+        self.query(|e| e.start <= end, |e| e > start)
+    }
+
+    /// Computes [TimeIndex::max_ends] for the subtree of `lo..hi`.
+    fn build(&mut self, lo: usize, hi: usize) -> Time {
+        if lo >= hi {
+            return Time::MIDNIGHT;
+        }
+        let mid = lo + (hi - lo) / 2;
+        let max_end = self
+            .build(lo, mid)
+            .max(self.build(mid + 1, hi))
+            .max(self.entries[mid].end);
+        self.max_ends[mid] = max_end;
+        max_end
+    }
+
+    /// Returns all entries which start early enough and end late enough.
+    fn query<S: Fn(&Entry) -> bool, E: Fn(Time) -> bool>(
+        &self,
+        starts_before: S,
+        ends_after: E,
+    ) -> Vec<usize> {
+        let mut found = vec![];
+        let mut stack = vec![(0, self.entries.len())];
+        while let Some((lo, hi)) = stack.pop() {
+            if lo >= hi {
+                continue;
+            }
+            let mid = lo + (hi - lo) / 2;
+            // no entry in this subtree ends late enough
+            if !ends_after(self.max_ends[mid]) {
+                continue;
+            }
+            stack.push((lo, mid));
+            let entry = &self.entries[mid];
+            // entries are ordered by start, so the right subtree starts too late as well
+            if starts_before(entry) {
+                if ends_after(entry.end) {
+                    found.push(entry)
+                }
+                stack.push((mid + 1, hi))
+            }
+        }
+
+        found.sort_by_key(|e| (e.layer, e.index));
+        found.into_iter().map(|e| e.index).collect()
+    }
+}
@@ -0,0 +1,280 @@
+//! Implements readers for the YouTube timed text formats JSON3 and SRV3.
+//!
+//! It describes the [TimedText], [TimedTextEvent] and [TimedTextSegment] structs and provides the
+//! [TimedText::parse_json3] and [TimedText::parse_srv3] functions.
+//!
+//! Both formats split events into segments, which carry the timing of single words in automatic
+//! captions. They can't be written, convert them to [VTT] (keeping the word timing as timestamp
+//! tags) or [SRT] instead.
+
+use roxmltree::Document;
+use serde::{Deserialize, Serialize};
+use time::Time;
+
+use crate::error;
+use crate::util::millis_to_time;
+
+use super::srt::{SRTLine, SRT};
+use super::strip_bom;
+use super::vtt::{VTTLine, VTT};
+
+/// Contains a Vec<[TimedTextEvent]>
+///
+/// JSON3 (`fmt=json3`) generally looks like:
+///```text
+/// {"wireMagic": "pb3", "events": [
+///   {"tStartMs": 1000, "dDurationMs": 2500, "segs": [{"utf8": "Hello"}, {"utf8": " world", "tOffsetMs": 600}]}
+/// ]}
+/// ```
+/// SRV3 (`fmt=srv3`) generally looks like:
+///```text
+/// <timedtext format="3"><body>
+/// <p t="1000" d="2500"><s>Hello</s><s t="600"> world</s></p>
+/// </body></timedtext>
+/// ```
+#[derive(Clone, Debug, Default, Eq, PartialEq, Deserialize, Serialize)]
+pub struct TimedText {
+    pub events: Vec<TimedTextEvent>,
+}
+
+/// Describes each event
+#[derive(Clone, Debug, Eq, PartialEq, Deserialize, Serialize)]
+pub struct TimedTextEvent {
+    pub start: Time,
+    pub end: Time,
+    pub segments: Vec<TimedTextSegment>,
+}
+
+/// A part of the text of an event, usually a single word in automatic captions. Displayed lines
+/// are separated by `\n`.
+#[derive(Clone, Debug, Eq, PartialEq, Deserialize, Serialize)]
+pub struct TimedTextSegment {
+    /// The time the segment appears at.
+    pub start: Time,
+    pub text: String,
+}
+
+impl TimedTextEvent {
+    /// Returns the text of all segments.
+    pub fn text(&self) -> String {
+        self.segments.iter().map(|s| s.text.as_str()).collect()
+    }
+}
+
+impl TimedText {
+    /// Parses the given JSON3 [String] into a [TimedText].
+    ///
+    /// Events without segments (which only define windows) or only containing whitespace are
+    /// skipped. Segments of events appended to the previous one (`"aAppend": 1`) are added to it.
+    pub fn parse_json3<S: AsRef<str>>(content: S) -> Result<TimedText, TimedTextError> {
+        let json3: parse::Json3 = serde_json::from_str(strip_bom(&content))
+            .map_err(|e| TimedTextError::new(TimedTextErrorKind::Json(e.to_string()), e.line()))?;
+
+        let mut timed_text = TimedText::default();
+        for event in json3.events {
+            let start = event.t_start_ms;
+            let segments = event
+                .segs
+                .unwrap_or_default()
+                .into_iter()
+                .map(|s| (start.saturating_add(s.t_offset_ms.unwrap_or(0)), s.utf8))
+                .collect();
+            timed_text.push(
+                start,
+                start.saturating_add(event.d_duration_ms.unwrap_or(0)),
+                segments,
+                event.a_append.is_some_and(|a| a != 0),
+            )
+        }
+
+        Ok(timed_text)
+    }
+
+    /// Parses the given SRV3 [String] into a [TimedText]. See [TimedText::parse_json3].
+    pub fn parse_srv3<S: AsRef<str>>(content: S) -> Result<TimedText, TimedTextError> {
+        let document = Document::parse(strip_bom(&content)).map_err(|e| {
+            TimedTextError::new(TimedTextErrorKind::Xml(e.to_string()), e.pos().row as usize)
+        })?;
+        let root = document.root_element();
+        if root.tag_name().name() != "timedtext" {
+            return Err(TimedTextError::new(
+                TimedTextErrorKind::Invalid,
+                parse::line(root),
+            ));
+        }
+
+        let mut timed_text = TimedText::default();
+        for p in root.descendants().filter(|n| {
+            n.has_tag_name("p") && n.parent_element().is_some_and(|b| b.has_tag_name("body"))
+        }) {
+            let number = |node, name| parse::number(node, name);
+            let start = number(p, "t")?.unwrap_or(0);
+            let end = start.saturating_add(number(p, "d")?.unwrap_or(0));
+
+            let mut segments = vec![];
+            for child in p.children() {
+                if child.is_text() {
+                    segments.push((start, child.text().unwrap_or_default().to_string()))
+                } else if child.has_tag_name("s") {
+                    let offset = number(child, "t")?.unwrap_or(0);
+                    let text = child
+                        .descendants()
+                        .filter(|n| n.is_text())
+                        .filter_map(|n| n.text())
+                        .collect::<String>();
+                    segments.push((start.saturating_add(offset), text))
+                } else if child.has_tag_name("br") {
+                    segments.push((start, "\n".to_string()))
+                }
+            }
+            timed_text.push(
+                start,
+                end,
+                segments,
+                number(p, "a")?.is_some_and(|a| a != 0),
+            )
+        }
+
+        Ok(timed_text)
+    }
+
+    /// Adds an event, with all times in milliseconds.
+    fn push(&mut self, start: i64, end: i64, segments: Vec<(i64, String)>, append: bool) {
+        let segments: Vec<TimedTextSegment> = segments
+            .into_iter()
+            .filter(|(_, text)| !text.is_empty())
+            .map(|(start, text)| TimedTextSegment {
+                start: millis_to_time(start),
+                text,
+            })
+            .collect();
+        if segments.iter().all(|s| s.text.trim().is_empty()) {
+            return;
+        }
+        let (start, end) = (millis_to_time(start), millis_to_time(end));
+
+        match self.events.last_mut() {
+            Some(previous) if append => {
+                previous.end = previous.end.max(end);
+                previous.segments.extend(segments)
+            }
+            _ => self.events.push(TimedTextEvent {
+                start,
+                end,
+                segments,
+            }),
+        }
+    }
+
+    /// Converts to [SRT]. Empty lines are removed.
+    pub fn to_srt(&self) -> SRT {
+        SRT {
+            lines: self
+                .events
+                .iter()
+                .enumerate()
+                .map(|(i, event)| SRTLine {
+                    sequence_number: i as u32 + 1,
+                    start: event.start,
+                    end: event.end,
+                    text: parse::trim_lines(&event.text()).join("\r\n"),
+                })
+                .collect(),
+        }
+    }
+
+    /// Converts to [VTT]. The start of every segment (except the first one of an event) is
+    /// converted to a timestamp tag (`<00:00:01.600>`) if the event is shown at that time. Empty
+    /// lines are removed.
+    pub fn to_vtt(&self) -> VTT {
+        VTT {
+            lines: self
+                .events
+                .iter()
+                .map(|event| {
+                    let mut text = String::new();
+                    for (i, segment) in event.segments.iter().enumerate() {
+                        if i > 0 && segment.start > event.start && segment.start < event.end {
+                            let (hour, minute, second, milli) = segment.start.as_hms_milli();
+                            text.push_str(&format!(
+                                "<{hour:0>2}:{minute:0>2}:{second:0>2}.{milli:0>3}>"
+                            ))
+                        }
+                        text.push_str(&segment.text.replace('&', "&amp;").replace('<', "&lt;"))
+                    }
+                    VTTLine {
+                        start: event.start,
+                        end: event.end,
+                        text: parse::trim_lines(&text).join("\n"),
+                        ..Default::default()
+                    }
+                })
+                .collect(),
+            ..Default::default()
+        }
+    }
+}
+
+error! {
+    TimedTextError => TimedTextErrorKind {
+        Json(String),
+        Xml(String),
+        Invalid,
+        Parse(String),
+    }
+}
+
+mod parse {
+    use super::*;
+    use roxmltree::Node;
+
+    #[derive(Deserialize)]
+    pub(super) struct Json3 {
+        #[serde(default)]
+        pub(super) events: Vec<Json3Event>,
+    }
+
+    #[derive(Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub(super) struct Json3Event {
+        #[serde(default)]
+        pub(super) t_start_ms: i64,
+        pub(super) d_duration_ms: Option<i64>,
+        pub(super) a_append: Option<i64>,
+        pub(super) segs: Option<Vec<Json3Segment>>,
+    }
+
+    #[derive(Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub(super) struct Json3Segment {
+        #[serde(default)]
+        pub(super) utf8: String,
+        pub(super) t_offset_ms: Option<i64>,
+    }
+
+    pub(super) fn line(node: Node) -> usize {
+        node.document().text_pos_at(node.range().start).row as usize
+    }
+
+    /// Parses the attribute `name` of `node` as number of milliseconds.
+    pub(super) fn number(node: Node, name: &str) -> Result<Option<i64>, TimedTextError> {
+        node.attribute(name)
+            .map(|value| {
+                value.trim().parse::<i64>().map_err(|_| {
+                    TimedTextError::new(
+                        TimedTextErrorKind::Parse(format!("invalid number '{value}'")),
+                        line(node),
+                    )
+                })
+            })
+            .transpose()
+    }
+
+    /// Splits `text` into lines without surrounding whitespace and removes empty ones.
+    pub(super) fn trim_lines(text: &str) -> Vec<&str> {
+        text.lines()
+            .map(str::trim)
+            .filter(|l| !l.is_empty())
+            .collect()
+    }
+}
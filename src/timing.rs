@@ -0,0 +1,216 @@
+//! Implements automatic timing corrections.
+//!
+//! It describes the [TimingFixer] struct, which adjusts the timing of all cues of a [Subtitle],
+//! the [snap_to_frames] function, which aligns all cues to the frames of a video, the [shift]
+//! function, which moves all cues by a fixed offset, and the [TimingChange] struct, which reports
+//! every adjustment made.
+//!
+//! Cues are expected to be in chronological order, the cue following another one is always the
+//! next one in [Subtitle::cues].
+
+use serde::{Deserialize, Serialize};
+use time::Time;
+
+use crate::cue::{Cue, Subtitle};
+use crate::metrics::count_characters;
+use crate::util::{millis_to_time, time_to_millis, FrameRate, FrameRounding, Timecode};
+
+/// Describes which timing corrections should be applied.
+#[derive(Clone, Debug, Default, PartialEq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct TimingFixer {
+    /// Cues shorter than this (in milliseconds) get extended.
+    pub min_duration_ms: Option<i64>,
+    /// Cues with more characters per second than this get extended until they match it. Values
+    /// that aren't positive are ignored.
+    pub target_cps: Option<f64>,
+    /// Minimum gap between two consecutive cues, in frames of [TimingFixer::frame_rate]. Cues
+    /// ending too close to the next one get shortened.
+    pub min_gap_frames: Option<u32>,
+    /// Frame rate used to convert [TimingFixer::min_gap_frames] to time.
+    pub frame_rate: FrameRate,
+    /// Shortens cues which end after the next cue starts. Overlaps are always trimmed if
+    /// [TimingFixer::min_gap_frames] is set.
+    pub trim_overlaps: bool,
+}
+
+/// A single adjustment of a cue's timing.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub struct TimingChange {
+    /// Index of the cue in [Subtitle::cues].
+    pub cue: usize,
+    pub reason: TimingChangeReason,
+    pub old_start: Time,
+    pub old_end: Time,
+    pub new_start: Time,
+    pub new_end: Time,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub enum TimingChangeReason {
+    /// The cue was extended to reach the minimum duration.
+    MinDuration,
+    /// The cue was extended to reach the target characters per second.
+    TargetCps,
+    /// The cue was shortened to keep the minimum gap to the next cue.
+    MinGap,
+    /// The cue was shortened because it overlapped the next cue.
+    Overlap,
+    /// The start and / or end of the cue was moved to a frame boundary.
+    FrameSnap,
+    /// The start and / or end of the cue was moved to a keyframe.
+    Keyframe,
+    /// The start and / or end of the cue was moved out of the forbidden zone around a shot
+    /// change.
+    ShotChange,
+}
+
+impl TimingFixer {
+    /// Applies all configured corrections to `subtitle` and returns the changes made, ordered by
+    /// cue.
+    ///
+    /// Extensions never make a cue overlap the next one or violate the minimum gap, so a cue
+    /// might still be too short afterward if there isn't enough room.
+    pub fn fix<S: Subtitle>(&self, subtitle: &mut S) -> Vec<TimingChange> {
+        let gap_ms = self
+            .min_gap_frames
+            .map(|frames| self.frame_rate.frames_to_millis(frames as i64));
+        let cues = subtitle.cues_mut();
+        let mut changes = vec![];
+
+        for i in 0..cues.len() {
+            let start = time_to_millis(cues[i].start());
+            let mut end = time_to_millis(cues[i].end());
+            let next_start = cues.get(i + 1).map(|n| time_to_millis(n.start()));
+            // the latest point the cue may end at without violating the gap / overlapping
+            let limit = next_start.and_then(|next_start| match gap_ms {
+                Some(gap_ms) => Some(next_start - gap_ms),
+                None if self.trim_overlaps => Some(next_start),
+                None => None,
+            });
+
+            let mut change = |cue: &mut S::Cue, reason, new_end: i64| {
+                let old_end = cue.end();
+                cue.set_end(millis_to_time(new_end));
+                changes.push(TimingChange {
+                    cue: i,
+                    reason,
+                    old_start: cue.start(),
+                    old_end,
+                    new_start: cue.start(),
+                    new_end: cue.end(),
+                })
+            };
+
+            // extend short / fast cues
+            let min_duration_end = self.min_duration_ms.map(|min| start.saturating_add(min));
+            let cps_end = self
+                .target_cps
+                .filter(|cps| cps.is_finite() && *cps > 0.0)
+                .map(|cps| {
+                    let characters = cues[i]
+                        .plain_text()
+                        .lines()
+                        .map(count_characters)
+                        .sum::<usize>();
+                    start.saturating_add((characters as f64 / cps * 1000.0).ceil() as i64)
+                });
+            let (desired_end, reason) = match (min_duration_end, cps_end) {
+                (Some(d), Some(c)) if c > d => (Some(c), TimingChangeReason::TargetCps),
+                (Some(d), _) => (Some(d), TimingChangeReason::MinDuration),
+                (None, c) => (c, TimingChangeReason::TargetCps),
+            };
+            if let Some(desired_end) = desired_end {
+                let new_end = limit.map_or(desired_end, |l| desired_end.min(l));
+                if new_end > end {
+                    change(&mut cues[i], reason, new_end);
+                    end = new_end
+                }
+            }
+
+            // shorten cues that are too close to / overlap the next one
+            if let (Some(limit), Some(next_start)) = (limit, next_start) {
+                if end > limit {
+                    let reason = if end > next_start {
+                        TimingChangeReason::Overlap
+                    } else {
+                        TimingChangeReason::MinGap
+                    };
+                    // if the cue would vanish by keeping the gap, at least remove the overlap
+                    let new_end = if limit > start { limit } else { next_start };
+                    if new_end > start && new_end < end {
+                        change(&mut cues[i], reason, new_end)
+                    }
+                }
+            }
+        }
+
+        changes
+    }
+}
+
+/// Moves the start and end of every cue in `subtitle` to frame boundaries (see
+/// [FrameRate::frame_boundary]) and returns the changes made.
+///
+/// Cues that would become empty because start and end snap to the same frame are kept visible for
+/// one frame.
+pub fn snap_to_frames<S: Subtitle>(
+    subtitle: &mut S,
+    frame_rate: FrameRate,
+    rounding: FrameRounding,
+) -> Vec<TimingChange> {
+    let mut changes = vec![];
+
+    for (i, cue) in subtitle.cues_mut().iter_mut().enumerate() {
+        let (old_start, old_end) = (cue.start(), cue.end());
+        let start_frame = frame_rate.boundary_frame(old_start, rounding);
+        let end_frame = frame_rate
+            .boundary_frame(old_end, rounding)
+            .max(start_frame + 1);
+        let (new_start, new_end) = (
+            frame_rate.frame_boundary(start_frame),
+            frame_rate.frame_boundary(end_frame),
+        );
+
+        if (new_start, new_end) != (old_start, old_end) {
+            cue.set_start(new_start);
+            cue.set_end(new_end);
+            changes.push(TimingChange {
+                cue: i,
+                reason: TimingChangeReason::FrameSnap,
+                old_start,
+                old_end,
+                new_start,
+                new_end,
+            })
+        }
+    }
+
+    changes
+}
+
+/// Moves every cue of `subtitle` by `offset_ms` milliseconds. Times that would become negative are
+/// clamped to zero, times beyond the end of the day to its last millisecond.
+pub fn shift<S: Subtitle>(subtitle: &mut S, offset_ms: i64) {
+    for cue in subtitle.cues_mut() {
+        cue.set_start(millis_to_time(
+            time_to_millis(cue.start()).saturating_add(offset_ms),
+        ));
+        cue.set_end(millis_to_time(
+            time_to_millis(cue.end()).saturating_add(offset_ms),
+        ));
+    }
+}
+
+/// Rebases `subtitle` from programme time (e.g. starting at the broadcast timecode `10:00:00:00`)
+/// to media time starting at zero.
+pub fn subtract_programme_start<S: Subtitle>(
+    subtitle: &mut S,
+    programme_start: Timecode,
+    frame_rate: FrameRate,
+) {
+    shift(
+        subtitle,
+        -time_to_millis(programme_start.to_time(frame_rate)),
+    )
+}
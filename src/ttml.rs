@@ -0,0 +1,1334 @@
+//! Implements helpers for TTML (also known as DFXP) `.ttml`, `.dfxp` and `.xml`.
+//!
+//! It describes the [TTML], [TTMLStyle], [TTMLRegion] and [TTMLParagraph] structs and
+//! provides the [TTML::parse] function.
+//!
+//! Attributes of the TTML namespaces are stored with their usual prefixes (`tts:color`,
+//! `ttp:cellResolution`, ...), regardless of the prefixes used in the parsed document. Attributes
+//! of unknown namespaces are dropped. `<div>`s are flattened: their timing, region and styling is
+//! applied to the `<p>`s they contain.
+
+use roxmltree::{Document, Node};
+use serde::Deserialize;
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::fmt::Display;
+use time::Time;
+
+use crate::error;
+use crate::util::{Alignment, Color, FrameRate, WHITE};
+
+use super::cue::{strip_markup, Cue, Subtitle};
+use super::ssa::{SSAEvent, SSAInfo, SSAStyle, SSA};
+use super::strip_bom;
+use super::vtt::{VTTLine, VTTRegion, VTTStyle, VTT};
+
+pub(crate) const TT_NAMESPACE: &str = "http://www.w3.org/ns/ttml";
+
+/// Namespaces whose attributes are kept, with the prefixes they are stored and written with.
+pub(crate) const NAMESPACES: [(&str, &str); 9] = [
+    ("xml", "http://www.w3.org/XML/1998/namespace"),
+    ("ttp", "http://www.w3.org/ns/ttml#parameter"),
+    ("tts", "http://www.w3.org/ns/ttml#styling"),
+    ("ttm", "http://www.w3.org/ns/ttml#metadata"),
+    ("ittp", "http://www.w3.org/ns/ttml/profile/imsc1#parameter"),
+    ("itts", "http://www.w3.org/ns/ttml/profile/imsc1#styling"),
+    ("ittm", "http://www.w3.org/ns/ttml/profile/imsc1#metadata"),
+    ("ebuttm", "urn:ebu:tt:metadata"),
+    ("ebutts", "urn:ebu:tt:style"),
+];
+
+/// Contains the styles, regions and [TTMLParagraph]s of a TTML document.
+///
+/// TTML is an XML format and generally looks like:
+///```text
+/// <tt xmlns="http://www.w3.org/ns/ttml" xmlns:tts="http://www.w3.org/ns/ttml#styling">
+///   <head>
+///     <styling>
+///       <style xml:id="s1" tts:color="yellow"/>
+///     </styling>
+///     <layout>
+///       <region xml:id="bottom" tts:origin="10% 80%" tts:extent="80% 15%"/>
+///     </layout>
+///   </head>
+///   <body>
+///     <div>
+///       <p begin="00:00:01.000" end="3s" region="bottom" style="s1">This is my<br/>text</p>
+///     </div>
+///   </body>
+/// </tt>
+/// ```
+#[derive(Clone, Debug, Default, Eq, PartialEq, Deserialize, Serialize)]
+pub struct TTML {
+    /// `xml:lang` of the document.
+    pub lang: Option<String>,
+    /// `ttp:frameRate` and `ttp:frameRateMultiplier`, used for frame based times.
+    pub frame_rate: Option<FrameRate>,
+    /// `ttp:tickRate`, used for tick based times.
+    pub tick_rate: Option<u32>,
+    /// All other attributes of the `<tt>` element, e.g. `ttp:cellResolution` or `tts:extent`.
+    pub attributes: Vec<(String, String)>,
+    /// `<ttm:title>` of the head.
+    pub title: Option<String>,
+    /// `<ttm:desc>` of the head.
+    pub description: Option<String>,
+    /// `<ttm:copyright>` of the head.
+    pub copyright: Option<String>,
+    pub styles: Vec<TTMLStyle>,
+    pub regions: Vec<TTMLRegion>,
+    pub paragraphs: Vec<TTMLParagraph>,
+}
+
+/// A `<style>` of the head.
+#[derive(Clone, Debug, Default, Eq, PartialEq, Deserialize, Serialize)]
+pub struct TTMLStyle {
+    /// `xml:id` of the style.
+    pub id: String,
+    /// Ids of the referenced styles (`style="a b"`), whose attributes are inherited.
+    pub style: Vec<String>,
+    /// Styling attributes like `("tts:color", "yellow")`.
+    pub attributes: Vec<(String, String)>,
+}
+
+/// A `<region>` of the head. The attributes of `<style>`s nested in the region are merged into
+/// its attributes.
+#[derive(Clone, Debug, Default, Eq, PartialEq, Deserialize, Serialize)]
+pub struct TTMLRegion {
+    /// `xml:id` of the region.
+    pub id: String,
+    /// Ids of the referenced styles (`style="a b"`), whose attributes are inherited.
+    pub style: Vec<String>,
+    /// Layout and styling attributes like `("tts:origin", "10% 80%")`.
+    pub attributes: Vec<(String, String)>,
+}
+
+/// Describes each `<p>`
+///
+/// The text keeps its markup (`<span>`s and `<br/>`s), insignificant whitespace is removed.
+#[derive(Clone, Debug, Eq, PartialEq, Deserialize, Serialize)]
+pub struct TTMLParagraph {
+    /// `xml:id` of the paragraph.
+    pub id: Option<String>,
+    pub begin: Time,
+    pub end: Time,
+    pub region: Option<String>,
+    /// Ids of the referenced styles (`style="a b"`).
+    pub style: Vec<String>,
+    /// All other attributes, e.g. `("tts:textAlign", "center")`.
+    pub attributes: Vec<(String, String)>,
+    pub text: String,
+}
+
+impl Default for TTMLParagraph {
+    fn default() -> Self {
+        Self {
+            id: None,
+            begin: Time::MIDNIGHT,
+            end: Time::MIDNIGHT,
+            region: None,
+            style: vec![],
+            attributes: vec![],
+            text: "".to_string(),
+        }
+    }
+}
+
+impl TTML {
+    /// Parses the given [String] into a [TTML].
+    ///
+    /// Times may be clock times (`00:00:01.500`, `00:00:01:12` with frames) or offset times
+    /// (`1.5s`, `36f`, `15000t`). Frames use the `ttp:frameRate` of the document (`30` by default),
+    /// ticks its `ttp:tickRate`.
+    pub fn parse<S: AsRef<str>>(content: S) -> Result<TTML, TTMLError> {
+        let document = Document::parse(strip_bom(&content))
+            .map_err(|e| TTMLError::new(TTMLErrorKind::Xml(e.to_string()), e.pos().row as usize))?;
+        let root = document.root_element();
+        if root.tag_name().name() != "tt" {
+            return Err(TTMLError::new(TTMLErrorKind::Invalid, parse::line(root)));
+        }
+
+        let mut ttml = TTML::default();
+        let mut frame_rate = None;
+        let mut frame_rate_multiplier = None;
+        for (name, value) in attributes(root) {
+            match name.as_str() {
+                "xml:lang" => ttml.lang = Some(value),
+                "ttp:frameRate" => frame_rate = Some(parse::number::<u32>(root, &value)?),
+                "ttp:frameRateMultiplier" => frame_rate_multiplier = Some(value),
+                "ttp:tickRate" => ttml.tick_rate = Some(parse::number::<u32>(root, &value)?),
+                _ => ttml.attributes.push((name, value)),
+            }
+        }
+        if let Some(frame_rate) = frame_rate {
+            ttml.frame_rate = Some(parse::frame_rate(
+                root,
+                frame_rate,
+                frame_rate_multiplier.as_deref(),
+            )?)
+        }
+
+        for child in root.children().filter(|n| n.is_element()) {
+            match child.tag_name().name() {
+                "head" => parse::parse_head(child, &mut ttml),
+                "body" => {
+                    let timing = parse::Timing::new(&ttml);
+                    parse::parse_container(
+                        child,
+                        &timing,
+                        &parse::Inherited::default(),
+                        &mut ttml.paragraphs,
+                    )?
+                }
+                _ => (),
+            }
+        }
+
+        Ok(ttml)
+    }
+
+    /// Returns the attributes of the styles with the ids in `style` (including the styles they
+    /// reference), overridden by `attributes`.
+    pub fn resolve_style(
+        &self,
+        style: &[String],
+        attributes: &[(String, String)],
+    ) -> HashMap<String, String> {
+        let mut resolved = HashMap::new();
+        self.collect_style(style, &mut resolved, &mut HashSet::new());
+        resolved.extend(attributes.iter().cloned());
+        resolved
+    }
+
+    /// Collects every style once, which guards against styles referencing each other.
+    fn collect_style<'a>(
+        &'a self,
+        ids: &'a [String],
+        resolved: &mut HashMap<String, String>,
+        visited: &mut HashSet<&'a str>,
+    ) {
+        for id in ids {
+            if !visited.insert(id) {
+                continue;
+            }
+            if let Some(style) = self.styles.iter().find(|s| &s.id == id) {
+                self.collect_style(&style.style, resolved, visited);
+                resolved.extend(style.attributes.iter().cloned())
+            }
+        }
+    }
+
+    /// Returns the computed attributes of `paragraph`: the ones of its region (including the
+    /// layout), overridden by the ones of its referenced styles and its own attributes.
+    pub fn paragraph_style(&self, paragraph: &TTMLParagraph) -> HashMap<String, String> {
+        let mut resolved = self
+            .region(paragraph)
+            .map(|r| self.resolve_style(&r.style, &r.attributes))
+            .unwrap_or_default();
+        resolved.extend(self.resolve_style(&paragraph.style, &paragraph.attributes));
+        resolved
+    }
+
+    fn region(&self, paragraph: &TTMLParagraph) -> Option<&TTMLRegion> {
+        let id = paragraph.region.as_ref()?;
+        self.regions.iter().find(|r| &r.id == id)
+    }
+
+    /// Converts a pair of lengths, e.g. the `tts:origin` `10% 80%`, to percentages of the root
+    /// container. Pixels are relative to the `tts:extent` of the `<tt>` element, cells to its
+    /// `ttp:cellResolution` (`32 15` by default).
+    pub fn percentages(&self, value: &str) -> Option<(f32, f32)> {
+        let root_extent = attribute(&self.attributes, "tts:extent")
+            .and_then(|e| e.split_once(' '))
+            .and_then(|(w, h)| {
+                Some((
+                    w.trim().strip_suffix("px")?.parse::<f32>().ok()?,
+                    h.trim().strip_suffix("px")?.parse::<f32>().ok()?,
+                ))
+            });
+        let cells = self.cell_resolution();
+
+        let convert = |length: &str, size: Option<f32>, cells: f32| {
+            if let Some(percent) = length.strip_suffix('%') {
+                percent.parse::<f32>().ok()
+            } else if let Some(pixels) = length.strip_suffix("px") {
+                Some(pixels.parse::<f32>().ok()? * 100.0 / size?)
+            } else if let Some(cell) = length.strip_suffix('c') {
+                Some(cell.parse::<f32>().ok()? * 100.0 / cells)
+            } else {
+                None
+            }
+        };
+        let mut lengths = value.split_whitespace();
+        let (x, y) = (lengths.next()?, lengths.next()?);
+        Some((
+            convert(x, root_extent.map(|e| e.0), cells.0)?,
+            convert(y, root_extent.map(|e| e.1), cells.1)?,
+        ))
+    }
+
+    /// Columns and rows of `ttp:cellResolution`.
+    pub(crate) fn cell_resolution(&self) -> (f32, f32) {
+        attribute(&self.attributes, "ttp:cellResolution")
+            .and_then(|c| c.split_once(' '))
+            .and_then(|(c, r)| Some((c.trim().parse().ok()?, r.trim().parse().ok()?)))
+            .unwrap_or((32.0, 15.0))
+    }
+
+    /// Converts a `tts:fontSize` to a percentage of the root container height. Percentages are
+    /// relative to the default font size of one cell.
+    pub(crate) fn font_size(&self, value: &str) -> Option<f32> {
+        // with two lengths, the second one is the height
+        let size = value.split_whitespace().last()?;
+        match size.strip_suffix('%') {
+            Some(percent) => Some(percent.parse::<f32>().ok()? / self.cell_resolution().1),
+            None => self.percentages(&format!("{size} {size}")).map(|(_, h)| h),
+        }
+    }
+
+    /// Converts to [VTT]. Styles are converted to [VTTStyle]s with a class selector (`.id`), which
+    /// are applied to the cues with `<c.id>` tags. Regions are converted to [VTTRegion]s, the
+    /// italic, bold and underline styling of `<span>`s to `<i>`, `<b>` and `<u>` tags.
+    pub fn to_vtt(&self) -> VTT {
+        let styles = self
+            .styles
+            .iter()
+            .map(|style| VTTStyle {
+                selector: Some(format!(".{}", style.id)),
+                entries: css(
+                    self,
+                    &self.resolve_style(std::slice::from_ref(&style.id), &[]),
+                ),
+            })
+            .collect();
+
+        let regions = self
+            .regions
+            .iter()
+            .map(|region| {
+                let layout = self.resolve_style(&region.style, &region.attributes);
+                let (x, y) = layout
+                    .get("tts:origin")
+                    .and_then(|o| self.percentages(o))
+                    .unwrap_or((0.0, 0.0));
+                let (width, height) = layout
+                    .get("tts:extent")
+                    .and_then(|e| self.percentages(e))
+                    .unwrap_or((100.0, 100.0));
+                // the region grows from the edge the text is aligned to
+                let anchor = match layout.get("tts:displayAlign").map(String::as_str) {
+                    Some("center") => 50.0,
+                    Some("after") => 100.0,
+                    _ => 0.0,
+                };
+                VTTRegion {
+                    id: Some(region.id.clone()),
+                    width: Some(width),
+                    // 5.33% of the video height is the line height of VTT
+                    lines: Some(((height / 5.33).round() as u32).max(1)),
+                    region_anchor: Some((0.0, anchor)),
+                    viewport_anchor: Some((x, y + height * anchor / 100.0)),
+                    scroll: false,
+                }
+            })
+            .collect();
+
+        let lines = self
+            .paragraphs
+            .iter()
+            .map(|paragraph| {
+                let mut settings = HashMap::new();
+                if let Some(region) = self.region(paragraph) {
+                    settings.insert("region".to_string(), Some(region.id.clone()));
+                }
+                let align = self
+                    .paragraph_style(paragraph)
+                    .get("tts:textAlign")
+                    .map(|a| match a.as_str() {
+                        "left" | "start" | "right" | "end" => a.clone(),
+                        _ => "center".to_string(),
+                    });
+                if let Some(align) = align {
+                    settings.insert("align".to_string(), Some(align));
+                }
+
+                let mut text = vtt_tags(
+                    &self.resolve_style(&[], &paragraph.attributes),
+                    with_fragment(&paragraph.text, |node| self.vtt_text(node)),
+                );
+                if !paragraph.style.is_empty() {
+                    text = format!("<c.{}>{text}</c>", paragraph.style.join("."))
+                }
+
+                VTTLine {
+                    identifier: paragraph.id.clone(),
+                    start: paragraph.begin,
+                    end: paragraph.end,
+                    settings,
+                    text,
+                }
+            })
+            .collect();
+
+        VTT {
+            regions,
+            styles,
+            lines,
+        }
+    }
+
+    fn vtt_text(&self, node: Node) -> String {
+        let mut text = String::new();
+        for child in node.children() {
+            if child.is_text() {
+                text.push_str(&escape_text(child.text().unwrap_or_default()))
+            } else if child.tag_name().name() == "br" {
+                text.push('\n')
+            } else if child.tag_name().name() == "span" {
+                let (style, attributes) = span_attributes(child);
+                let mut span =
+                    vtt_tags(&self.resolve_style(&[], &attributes), self.vtt_text(child));
+                if !style.is_empty() {
+                    span = format!("<c.{}>{span}</c>", style.join("."))
+                }
+                text.push_str(&span)
+            } else if child.is_element() && !parse::is_ignored(child) {
+                text.push_str(&self.vtt_text(child))
+            }
+        }
+        text
+    }
+
+    /// Converts to [SSA] with a resolution of 1920x1080. Styles are converted to [SSAStyle]s, the
+    /// layout of regions to alignment overrides (`{\an7}`) and margins of the events and the
+    /// styling of `<span>`s to override tags.
+    ///
+    /// A paragraph gets the first of its referenced styles, the styling of all other ones is
+    /// applied with override tags.
+    pub fn to_ssa(&self) -> SSA {
+        let mut styles = vec![self.ssa_style("Default", &HashMap::new())];
+        for style in &self.styles {
+            styles.push(self.ssa_style(
+                &style.id,
+                &self.resolve_style(std::slice::from_ref(&style.id), &[]),
+            ))
+        }
+
+        let mut events = vec![];
+        for paragraph in &self.paragraphs {
+            let style = paragraph
+                .style
+                .iter()
+                .find(|id| self.styles.iter().any(|s| &&s.id == id))
+                .cloned()
+                .unwrap_or("Default".to_string());
+            let style_attributes = self.resolve_style(std::slice::from_ref(&style), &[]);
+            let style_alignment = styles.iter().find(|s| s.name == style).unwrap().alignment;
+            let computed = self.paragraph_style(paragraph);
+
+            let mut overrides = String::new();
+            let (vertical, alignment) = ssa_alignment(&computed, paragraph.region.is_some());
+            if alignment != style_alignment {
+                overrides.push_str(&format!("\\an{}", alignment as u8))
+            }
+            let base = SSAState::default().apply(&style_attributes);
+            let state = base.apply(&computed);
+            overrides.push_str(&base.overrides(&state));
+
+            let mut event = SSAEvent {
+                start: paragraph.begin,
+                end: paragraph.end,
+                style,
+                text: with_fragment(&paragraph.text, |node| self.ssa_text(node, &state)),
+                ..Default::default()
+            };
+            if !overrides.is_empty() {
+                event.text = format!("{{{overrides}}}{}", event.text)
+            }
+            if self.region(paragraph).is_some() {
+                let (x, y) = computed
+                    .get("tts:origin")
+                    .and_then(|o| self.percentages(o))
+                    .unwrap_or((0.0, 0.0));
+                let (width, height) = computed
+                    .get("tts:extent")
+                    .and_then(|e| self.percentages(e))
+                    .unwrap_or((100.0, 100.0));
+                event.margin_l = (x * 19.2).round();
+                event.margin_r = ((100.0 - x - width) * 19.2).round().max(0.0);
+                event.margin_v = match vertical {
+                    0 => ((100.0 - y - height) * 10.8).round().max(0.0),
+                    2 => (y * 10.8).round(),
+                    _ => 0.0,
+                };
+            }
+            events.push(event)
+        }
+
+        SSA {
+            info: SSAInfo {
+                title: self.title.clone(),
+                play_res_x: Some(1920),
+                play_res_y: Some(1080),
+                ..Default::default()
+            },
+            styles,
+            events,
+            ..Default::default()
+        }
+    }
+
+    fn ssa_style(&self, name: &str, attributes: &HashMap<String, String>) -> SSAStyle {
+        let state = SSAState::default().apply(attributes);
+        let font_size = attributes
+            .get("tts:fontSize")
+            .and_then(|s| self.font_size(s))
+            .unwrap_or(100.0 / self.cell_resolution().1);
+
+        SSAStyle {
+            name: name.to_string(),
+            fontname: attributes
+                .get("tts:fontFamily")
+                .map(|f| font_family(f).1)
+                .unwrap_or("Arial".to_string()),
+            fontsize: (font_size * 10.8).round(),
+            primary_color: attributes
+                .get("tts:color")
+                .and_then(|c| Color::from_ttml(c).ok()),
+            back_color: attributes
+                .get("tts:backgroundColor")
+                .and_then(|c| Color::from_ttml(c).ok()),
+            bold: state.bold,
+            italic: state.italic,
+            underline: state.underline,
+            strikeout: state.strikeout,
+            scale_x: 100.0,
+            scale_y: 100.0,
+            alignment: ssa_alignment(attributes, false).1,
+            ..Default::default()
+        }
+    }
+
+    fn ssa_text(&self, node: Node, state: &SSAState) -> String {
+        let mut text = String::new();
+        for child in node.children() {
+            if child.is_text() {
+                text.push_str(child.text().unwrap_or_default())
+            } else if child.tag_name().name() == "br" {
+                text.push_str("\\N")
+            } else if child.tag_name().name() == "span" {
+                let (style, attributes) = span_attributes(child);
+                let span_state = state.apply(&self.resolve_style(&style, &attributes));
+                let (open, close) = (state.overrides(&span_state), span_state.overrides(state));
+                if !open.is_empty() {
+                    text.push_str(&format!("{{{open}}}"))
+                }
+                text.push_str(&self.ssa_text(child, &span_state));
+                if !close.is_empty() {
+                    text.push_str(&format!("{{{close}}}"))
+                }
+            } else if child.is_element() && !parse::is_ignored(child) {
+                text.push_str(&self.ssa_text(child, state))
+            }
+        }
+        text
+    }
+}
+
+/// The styling of text which can be expressed with SSA override tags.
+#[derive(Clone, PartialEq)]
+struct SSAState {
+    italic: bool,
+    bold: bool,
+    underline: bool,
+    strikeout: bool,
+    color: Color,
+}
+
+impl Default for SSAState {
+    fn default() -> Self {
+        Self {
+            italic: false,
+            bold: false,
+            underline: false,
+            strikeout: false,
+            color: WHITE,
+        }
+    }
+}
+
+impl SSAState {
+    fn apply(&self, attributes: &HashMap<String, String>) -> Self {
+        let mut state = self.clone();
+        if let Some(style) = attributes.get("tts:fontStyle") {
+            state.italic = style == "italic" || style == "oblique"
+        }
+        if let Some(weight) = attributes.get("tts:fontWeight") {
+            state.bold = weight == "bold"
+        }
+        if let Some(decoration) = attributes.get("tts:textDecoration") {
+            for decoration in decoration.split_whitespace() {
+                match decoration {
+                    "none" => (state.underline, state.strikeout) = (false, false),
+                    "underline" => state.underline = true,
+                    "noUnderline" => state.underline = false,
+                    "lineThrough" => state.strikeout = true,
+                    "noLineThrough" => state.strikeout = false,
+                    _ => (),
+                }
+            }
+        }
+        if let Some(color) = attributes
+            .get("tts:color")
+            .and_then(|c| Color::from_ttml(c).ok())
+        {
+            state.color = color
+        }
+        state
+    }
+
+    /// Returns the override tags which change `self` into `other`.
+    fn overrides(&self, other: &Self) -> String {
+        let mut overrides = String::new();
+        for (tag, from, to) in [
+            ('i', self.italic, other.italic),
+            ('b', self.bold, other.bold),
+            ('u', self.underline, other.underline),
+            ('s', self.strikeout, other.strikeout),
+        ] {
+            if from != to {
+                overrides.push_str(&format!("\\{tag}{}", to as u8))
+            }
+        }
+        if self.color != other.color {
+            overrides.push_str(&format!("\\c{}&", other.color.to_ssa_string()))
+        }
+        overrides
+    }
+}
+
+/// Returns the vertical position (`0` bottom, `1` middle, `2` top) and the [Alignment] described
+/// by `attributes`. Without a region, text is placed at the bottom.
+pub(crate) fn ssa_alignment(attributes: &HashMap<String, String>, region: bool) -> (u8, Alignment) {
+    let vertical = match attributes.get("tts:displayAlign").map(String::as_str) {
+        _ if !region => 0,
+        Some("after") => 0,
+        Some("center") => 1,
+        _ => 2,
+    };
+    let horizontal = match attributes.get("tts:textAlign").map(String::as_str) {
+        Some("left") | Some("start") => 0,
+        Some("right") | Some("end") => 2,
+        _ => 1,
+    };
+    let alignment = match (vertical, horizontal) {
+        (0, 0) => Alignment::BottomLeft,
+        (0, 1) => Alignment::BottomCenter,
+        (0, _) => Alignment::BottomRight,
+        (1, 0) => Alignment::MiddleLeft,
+        (1, 1) => Alignment::MiddleCenter,
+        (1, _) => Alignment::MiddleRight,
+        (_, 0) => Alignment::TopLeft,
+        (_, 1) => Alignment::TopCenter,
+        (_, _) => Alignment::TopRight,
+    };
+    (vertical, alignment)
+}
+
+/// Converts the first family of a `tts:fontFamily` to a CSS and a SSA font name. Generic TTML
+/// families are mapped to their CSS counterparts or common fonts.
+fn font_family(family: &str) -> (String, String) {
+    let family = family
+        .split(',')
+        .next()
+        .unwrap_or_default()
+        .trim()
+        .trim_matches(['"', '\''])
+        .to_string();
+    let (css, ssa) = match family.as_str() {
+        "default" | "sansSerif" | "proportionalSansSerif" => ("sans-serif", "Arial"),
+        "serif" | "proportionalSerif" => ("serif", "Times New Roman"),
+        "monospace" | "monospaceSansSerif" | "monospaceSerif" => ("monospace", "Courier New"),
+        _ => return (family.clone(), family),
+    };
+    (css.to_string(), ssa.to_string())
+}
+
+/// Converts styling attributes to CSS declarations.
+fn css(ttml: &TTML, attributes: &HashMap<String, String>) -> HashMap<String, String> {
+    let mut entries = HashMap::new();
+    for (name, value) in attributes {
+        let entry = match name.as_str() {
+            "tts:color" => Color::from_ttml(value)
+                .ok()
+                .map(|c| ("color", c.to_vtt_string())),
+            "tts:backgroundColor" => Color::from_ttml(value)
+                .ok()
+                .map(|c| ("background-color", c.to_vtt_string())),
+            "tts:fontFamily" => Some(("font-family", font_family(value).0)),
+            "tts:fontSize" => ttml
+                .font_size(value)
+                .map(|s| ("font-size", format!("{}vh", (s * 100.0).round() / 100.0))),
+            "tts:fontStyle" => Some(("font-style", value.clone())),
+            "tts:fontWeight" => Some(("font-weight", value.clone())),
+            "tts:textDecoration" => Some((
+                "text-decoration",
+                value
+                    .split_whitespace()
+                    .filter_map(|d| match d {
+                        "underline" => Some("underline"),
+                        "lineThrough" => Some("line-through"),
+                        "overline" => Some("overline"),
+                        _ => None,
+                    })
+                    .collect::<Vec<&str>>()
+                    .join(" "),
+            ))
+            .filter(|(_, d)| !d.is_empty()),
+            _ => None,
+        };
+        if let Some((name, value)) = entry {
+            entries.insert(name.to_string(), value);
+        }
+    }
+    entries
+}
+
+/// Wraps `text` in `<i>`, `<b>` and `<u>` tags as described by `attributes`.
+fn vtt_tags(attributes: &HashMap<String, String>, mut text: String) -> String {
+    let state = SSAState::default().apply(attributes);
+    for (tag, active) in [
+        ("u", state.underline),
+        ("b", state.bold),
+        ("i", state.italic),
+    ] {
+        if active {
+            text = format!("<{tag}>{text}</{tag}>")
+        }
+    }
+    text
+}
+
+/// Returns the referenced styles and the other attributes of a `<span>`.
+pub(crate) fn span_attributes(node: Node) -> (Vec<String>, Vec<(String, String)>) {
+    let mut style = vec![];
+    let mut other = vec![];
+    for (name, value) in attributes(node) {
+        if name == "style" {
+            style.extend(value.split_whitespace().map(str::to_string))
+        } else {
+            other.push((name, value))
+        }
+    }
+    (style, other)
+}
+
+/// Calls `f` with the `<p>` element containing the markup of `text`. Invalid markup is treated as
+/// plain text.
+pub(crate) fn with_fragment<R>(text: &str, f: impl FnOnce(Node) -> R) -> R {
+    let namespaces: String = NAMESPACES[1..]
+        .iter()
+        .map(|(prefix, namespace)| format!(" xmlns:{prefix}=\"{namespace}\""))
+        .collect();
+    let xml = format!("<p xmlns=\"{TT_NAMESPACE}\"{namespaces}>{text}</p>");
+    match Document::parse(&xml) {
+        Ok(document) => f(document.root_element()),
+        Err(_) => {
+            let xml = format!("<p>{}</p>", escape_text(text));
+            // safe unwrap: the text is escaped
+            let document = Document::parse(&xml).unwrap();
+            f(document.root_element())
+        }
+    }
+}
+
+/// Returns the prefix `namespace` is stored with. The namespaces of the DFXP drafts (e.g.
+/// `http://www.w3.org/2006/10/ttaf1#styling`) are treated like the final ones.
+fn prefix_of(namespace: &str) -> Option<&'static str> {
+    if let Some((_, kind)) = namespace
+        .split_once('#')
+        .filter(|(base, _)| base.starts_with("http://www.w3.org/") && base.ends_with("ttaf1"))
+    {
+        return match kind {
+            "parameter" => Some("ttp"),
+            "styling" => Some("tts"),
+            "metadata" => Some("ttm"),
+            _ => None,
+        };
+    }
+    NAMESPACES
+        .iter()
+        .find(|(_, n)| *n == namespace)
+        .map(|(prefix, _)| *prefix)
+}
+
+/// Returns all attributes of `node` with known namespaces, named with their prefix.
+pub(crate) fn attributes(node: Node) -> Vec<(String, String)> {
+    node.attributes()
+        .filter_map(|attribute| {
+            let name = match attribute.namespace() {
+                None => attribute.name().to_string(),
+                Some(namespace) => format!("{}:{}", prefix_of(namespace)?, attribute.name()),
+            };
+            Some((name, attribute.value().to_string()))
+        })
+        .collect()
+}
+
+/// Returns the value of the attribute `name`.
+pub(crate) fn attribute<'a>(attributes: &'a [(String, String)], name: &str) -> Option<&'a str> {
+    attributes
+        .iter()
+        .find(|(n, _)| n == name)
+        .map(|(_, v)| v.as_str())
+}
+
+/// Sets the attribute `name`, replacing an existing one.
+fn set_attribute(attributes: &mut Vec<(String, String)>, name: String, value: String) {
+    attributes.retain(|(n, _)| n != &name);
+    attributes.push((name, value))
+}
+
+pub(crate) fn escape_text(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+pub(crate) fn escape_attribute(value: &str) -> String {
+    escape_text(value).replace('"', "&quot;")
+}
+
+fn unescape(text: &str) -> String {
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
+
+impl Cue for TTMLParagraph {
+    const LINE_BREAK: &'static str = "<br/>";
+
+    fn start(&self) -> Time {
+        self.begin
+    }
+    fn end(&self) -> Time {
+        self.end
+    }
+    fn set_start(&mut self, start: Time) {
+        self.begin = start
+    }
+    fn set_end(&mut self, end: Time) {
+        self.end = end
+    }
+    fn text(&self) -> &str {
+        &self.text
+    }
+    fn set_text(&mut self, text: String) {
+        self.text = text
+    }
+    /// Converts `<br/>`s to newlines and removes all other markup.
+    fn plain_text(&self) -> String {
+        unescape(&strip_markup(&self.text.replace("<br/>", "\n"), '<', '>'))
+    }
+    fn style(&self) -> Option<&str> {
+        self.style.first().map(String::as_str)
+    }
+    fn set_style(&mut self, style: String) {
+        self.style = vec![style]
+    }
+}
+
+impl Subtitle for TTML {
+    type Cue = TTMLParagraph;
+
+    fn cues(&self) -> &[TTMLParagraph] {
+        &self.paragraphs
+    }
+    fn cues_mut(&mut self) -> &mut Vec<TTMLParagraph> {
+        &mut self.paragraphs
+    }
+}
+
+impl Display for TTML {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let element = |name: &str, attributes: &[(String, String)]| {
+            let attributes: String = attributes
+                .iter()
+                .map(|(n, v)| format!(" {n}=\"{}\"", escape_attribute(v)))
+                .collect();
+            format!("<{name}{attributes}")
+        };
+        let with_style = |id: Option<&String>, style: &[String], attributes| {
+            let mut all = vec![];
+            all.extend(id.map(|id| ("xml:id".to_string(), id.clone())));
+            if !style.is_empty() {
+                all.push(("style".to_string(), style.join(" ")))
+            }
+            all.extend_from_slice(attributes);
+            all
+        };
+
+        let mut lines = vec![];
+        if self.title.is_some() || self.description.is_some() || self.copyright.is_some() {
+            lines.push("    <metadata>".to_string());
+            for (name, value) in [
+                ("title", &self.title),
+                ("desc", &self.description),
+                ("copyright", &self.copyright),
+            ] {
+                if let Some(value) = value {
+                    lines.push(format!(
+                        "      <ttm:{name}>{}</ttm:{name}>",
+                        escape_text(value)
+                    ))
+                }
+            }
+            lines.push("    </metadata>".to_string())
+        }
+        if !self.styles.is_empty() {
+            lines.push("    <styling>".to_string());
+            for style in &self.styles {
+                let attributes = with_style(Some(&style.id), &style.style, &style.attributes);
+                lines.push(format!("      {}/>", element("style", &attributes)))
+            }
+            lines.push("    </styling>".to_string())
+        }
+        if !self.regions.is_empty() {
+            lines.push("    <layout>".to_string());
+            for region in &self.regions {
+                let attributes = with_style(Some(&region.id), &region.style, &region.attributes);
+                lines.push(format!("      {}/>", element("region", &attributes)))
+            }
+            lines.push("    </layout>".to_string())
+        }
+        if !lines.is_empty() {
+            lines.insert(0, "  <head>".to_string());
+            lines.push("  </head>".to_string())
+        }
+
+        lines.push("  <body>".to_string());
+        lines.push("    <div>".to_string());
+        for paragraph in &self.paragraphs {
+            let mut attributes = with_style(paragraph.id.as_ref(), &[], &[]);
+            attributes.push(("begin".to_string(), parse::format_time(paragraph.begin)));
+            attributes.push(("end".to_string(), parse::format_time(paragraph.end)));
+            attributes.extend(paragraph.region.clone().map(|r| ("region".to_string(), r)));
+            attributes.extend(with_style(None, &paragraph.style, &paragraph.attributes));
+            lines.push(format!(
+                "      {}>{}</p>",
+                element("p", &attributes),
+                paragraph.text
+            ))
+        }
+        lines.push("    </div>".to_string());
+        lines.push("  </body>".to_string());
+        lines.push("</tt>".to_string());
+
+        let mut root = vec![];
+        root.extend(self.lang.clone().map(|l| ("xml:lang".to_string(), l)));
+        // times are written as clock times, so rates which can't be written are left out
+        if let Some((fps, multiplier)) = self.frame_rate.and_then(parse::frame_rate_parameters) {
+            root.push(("ttp:frameRate".to_string(), fps.to_string()));
+            if let Some((numerator, denominator)) = multiplier {
+                root.push((
+                    "ttp:frameRateMultiplier".to_string(),
+                    format!("{numerator} {denominator}"),
+                ))
+            }
+        }
+        if let Some(tick_rate) = self.tick_rate {
+            root.push(("ttp:tickRate".to_string(), tick_rate.to_string()))
+        }
+        root.extend_from_slice(&self.attributes);
+
+        // only namespaces which are used get declared
+        let content = format!("{}{}", element("", &root), lines.join("\n"));
+        let mut namespaces = vec![("xmlns".to_string(), TT_NAMESPACE.to_string())];
+        for (prefix, namespace) in &NAMESPACES[1..] {
+            if content.contains(&format!(" {prefix}:")) || content.contains(&format!("<{prefix}:"))
+            {
+                namespaces.push((format!("xmlns:{prefix}"), namespace.to_string()))
+            }
+        }
+        namespaces.extend(root);
+        lines.insert(0, format!("{}>", element("tt", &namespaces)));
+        lines.insert(0, r#"<?xml version="1.0" encoding="UTF-8"?>"#.to_string());
+
+        write!(f, "{}", lines.join("\n"))
+    }
+}
+
+error! {
+    TTMLError => TTMLErrorKind {
+        Xml(String),
+        Invalid,
+        Parse(String),
+    }
+}
+
+mod parse {
+    use super::*;
+    use crate::util::millis_to_time;
+
+    type Result<T> = std::result::Result<T, TTMLError>;
+
+    pub(super) fn line(node: Node) -> usize {
+        node.document().text_pos_at(node.range().start).row as usize
+    }
+
+    pub(super) fn number<T: std::str::FromStr>(node: Node, value: &str) -> Result<T>
+    where
+        T::Err: Display,
+    {
+        value
+            .trim()
+            .parse::<T>()
+            .map_err(|e| TTMLError::new(TTMLErrorKind::Parse(e.to_string()), line(node)))
+    }
+
+    pub(super) fn frame_rate(
+        node: Node,
+        frame_rate: u32,
+        multiplier: Option<&str>,
+    ) -> Result<FrameRate> {
+        let (numerator, denominator) = match multiplier.map(|m| m.split_once(' ')) {
+            None => (1, 1),
+            Some(Some((n, d))) => (number::<u32>(node, n)?, number::<u32>(node, d)?),
+            Some(None) => {
+                return Err(TTMLError::new(
+                    TTMLErrorKind::Parse("invalid frame rate multiplier".to_string()),
+                    line(node),
+                ))
+            }
+        };
+        let numerator = frame_rate
+            .checked_mul(numerator)
+            .filter(|n| *n != 0 && denominator != 0)
+            .ok_or_else(|| {
+                TTMLError::new(
+                    TTMLErrorKind::Parse("invalid frame rate".to_string()),
+                    line(node),
+                )
+            })?;
+        let gcd = gcd(numerator, denominator).max(1);
+        Ok(FrameRate::new(numerator / gcd, denominator / gcd))
+    }
+
+    /// Splits `frame_rate` into `ttp:frameRate` and `ttp:frameRateMultiplier` (if it isn't an
+    /// integer rate). Returns [None] if the rate has no such representation.
+    pub(super) fn frame_rate_parameters(
+        frame_rate: FrameRate,
+    ) -> Option<(u32, Option<(u32, u32)>)> {
+        let fps = frame_rate.fps().round() as u32;
+        if fps == 0 || frame_rate.numerator == 0 || frame_rate.denominator == 0 {
+            return None;
+        }
+        if frame_rate.denominator == 1 {
+            return Some((fps, None));
+        }
+        let (numerator, denominator) = (
+            frame_rate.numerator,
+            fps.checked_mul(frame_rate.denominator)?,
+        );
+        let gcd = gcd(numerator, denominator);
+        Some((fps, Some((numerator / gcd, denominator / gcd))))
+    }
+
+    pub(super) fn gcd(a: u32, b: u32) -> u32 {
+        if b == 0 {
+            a
+        } else {
+            gcd(b, a % b)
+        }
+    }
+
+    /// Elements of the body whose content isn't text.
+    pub(super) fn is_ignored(node: Node) -> bool {
+        matches!(node.tag_name().name(), "metadata" | "set" | "animate")
+    }
+
+    pub(super) fn parse_head(head: Node, ttml: &mut TTML) {
+        for node in head.descendants().filter(|n| n.is_element()) {
+            let parent = node.parent_element().map(|p| p.tag_name().name());
+            let in_ttm = node.tag_name().namespace().and_then(prefix_of) == Some("ttm");
+            match (node.tag_name().name(), parent) {
+                ("style", Some("styling")) => {
+                    let (id, style, attributes) = parse_styled(node);
+                    ttml.styles.push(TTMLStyle {
+                        id: id.unwrap_or_default(),
+                        style,
+                        attributes,
+                    })
+                }
+                ("region", Some("layout")) => {
+                    let (id, mut style, mut attributes) = parse_styled(node);
+                    for nested in node.children().filter(|n| n.tag_name().name() == "style") {
+                        let (_, nested_style, nested_attributes) = parse_styled(nested);
+                        style.extend(nested_style);
+                        for (name, value) in nested_attributes {
+                            set_attribute(&mut attributes, name, value)
+                        }
+                    }
+                    ttml.regions.push(TTMLRegion {
+                        id: id.unwrap_or_default(),
+                        style,
+                        attributes,
+                    })
+                }
+                ("title", _) if in_ttm => ttml.title = Some(text(node)),
+                ("desc", _) if in_ttm => ttml.description = Some(text(node)),
+                ("copyright", _) if in_ttm => ttml.copyright = Some(text(node)),
+                _ => (),
+            }
+        }
+    }
+
+    fn text(node: Node) -> String {
+        node.descendants()
+            .filter(|n| n.is_text())
+            .filter_map(|n| n.text())
+            .collect::<String>()
+            .trim()
+            .to_string()
+    }
+
+    /// Returns the `xml:id`, the referenced styles and all other attributes of `node`.
+    fn parse_styled(node: Node) -> (Option<String>, Vec<String>, Vec<(String, String)>) {
+        let mut id = None;
+        let mut style = vec![];
+        let mut other = vec![];
+        for (name, value) in attributes(node) {
+            match name.as_str() {
+                "xml:id" => id = Some(value),
+                "style" => style.extend(value.split_whitespace().map(str::to_string)),
+                _ => other.push((name, value)),
+            }
+        }
+        (id, style, other)
+    }
+
+    /// Converts times to milliseconds.
+    pub(super) struct Timing {
+        frame_rate: FrameRate,
+        tick_rate: f64,
+    }
+
+    impl Timing {
+        pub(super) fn new(ttml: &TTML) -> Self {
+            let frame_rate = ttml.frame_rate.unwrap_or(FrameRate::FPS_30);
+            let tick_rate = match (ttml.tick_rate, ttml.frame_rate) {
+                (Some(tick_rate), _) => tick_rate as f64,
+                (None, Some(frame_rate)) => frame_rate.fps(),
+                (None, None) => 1.0,
+            };
+            Self {
+                frame_rate,
+                tick_rate,
+            }
+        }
+
+        /// Parses a clock time (`01:02:03.456`, `01:02:03:12`) or an offset time (`1.5s`, `36f`,
+        /// `15000t`).
+        fn parse(&self, value: &str) -> std::result::Result<i64, String> {
+            let value = value.trim();
+            let invalid = || format!("invalid time '{value}'");
+
+            if value.contains(':') {
+                let parts: Vec<&str> = value.split(':').collect();
+                let (hours, minutes, seconds, frames) = match parts.as_slice() {
+                    [h, m, s] => (h, m, s, None),
+                    [h, m, s, f] => (h, m, s, Some(f)),
+                    _ => return Err(invalid()),
+                };
+                let hours = hours.parse::<i64>().map_err(|_| invalid())?;
+                let minutes = minutes.parse::<i64>().map_err(|_| invalid())?;
+                let seconds = seconds.parse::<f64>().map_err(|_| invalid())?;
+                // sub frames are ignored
+                let frames = frames
+                    .map(|f| f.split('.').next().unwrap_or_default().parse::<i64>())
+                    .transpose()
+                    .map_err(|_| invalid())?
+                    .unwrap_or(0);
+                hours
+                    .checked_mul(3_600_000)
+                    .and_then(|h| h.checked_add(minutes.checked_mul(60_000)?))
+                    .and_then(|t| t.checked_add((seconds * 1000.0).round() as i64))
+                    .and_then(|t| t.checked_add(self.frame_rate.frames_to_millis(frames)))
+                    .ok_or_else(invalid)
+            } else {
+                let split = value
+                    .find(|c: char| c.is_ascii_alphabetic())
+                    .ok_or_else(invalid)?;
+                let (number, metric) = value.split_at(split);
+                let number = number.parse::<f64>().map_err(|_| invalid())?;
+                let millis = match metric {
+                    "h" => number * 3_600_000.0,
+                    "m" => number * 60_000.0,
+                    "s" => number * 1000.0,
+                    "ms" => number,
+                    "f" => number * 1000.0 / self.frame_rate.fps(),
+                    "t" => number * 1000.0 / self.tick_rate,
+                    _ => return Err(invalid()),
+                };
+                Ok(millis.round() as i64)
+            }
+        }
+    }
+
+    pub(super) fn format_time(time: Time) -> String {
+        let (hour, minute, second, milli) = time.as_hms_milli();
+        format!("{hour:0>2}:{minute:0>2}:{second:0>2}.{milli:0>3}")
+    }
+
+    /// What a `<p>` inherits from the `<body>` and `<div>`s containing it.
+    #[derive(Clone, Default)]
+    pub(super) struct Inherited {
+        begin: i64,
+        end: Option<i64>,
+        region: Option<String>,
+        style: Vec<String>,
+        attributes: Vec<(String, String)>,
+    }
+
+    /// Returns the absolute begin and end of `node` in milliseconds. Times are relative to the
+    /// begin of the parent, which also limits the end.
+    fn times(node: Node, timing: &Timing, parent: &Inherited) -> Result<(i64, Option<i64>)> {
+        let time = |name| {
+            node.attribute(name)
+                .map(|v| timing.parse(v))
+                .transpose()
+                .map_err(|e| TTMLError::new(TTMLErrorKind::Parse(e), line(node)))
+        };
+        let begin = parent.begin.saturating_add(time("begin")?.unwrap_or(0));
+        let end = match (time("end")?, time("dur")?) {
+            (Some(end), Some(dur)) => Some(
+                parent
+                    .begin
+                    .saturating_add(end)
+                    .min(begin.saturating_add(dur)),
+            ),
+            (Some(end), None) => Some(parent.begin.saturating_add(end)),
+            (None, Some(dur)) => Some(begin.saturating_add(dur)),
+            (None, None) => parent.end,
+        };
+        let end = match (end, parent.end) {
+            (Some(end), Some(parent_end)) => Some(end.min(parent_end)),
+            (end, _) => end,
+        };
+        Ok((begin, end))
+    }
+
+    /// Parses the `<p>`s of the `<body>` or a `<div>`.
+    pub(super) fn parse_container(
+        node: Node,
+        timing: &Timing,
+        parent: &Inherited,
+        paragraphs: &mut Vec<TTMLParagraph>,
+    ) -> Result<()> {
+        let (begin, end) = times(node, timing, parent)?;
+        let mut inherited = Inherited {
+            begin,
+            end,
+            ..parent.clone()
+        };
+        for (name, value) in attributes(node) {
+            match name.as_str() {
+                "region" => inherited.region = Some(value),
+                "style" => inherited
+                    .style
+                    .extend(value.split_whitespace().map(str::to_string)),
+                _ if name.starts_with("tts:") => {
+                    set_attribute(&mut inherited.attributes, name, value)
+                }
+                _ => (),
+            }
+        }
+
+        for child in node.children().filter(|n| n.is_element()) {
+            match child.tag_name().name() {
+                "div" => parse_container(child, timing, &inherited, paragraphs)?,
+                "p" => paragraphs.push(parse_paragraph(child, timing, &inherited)?),
+                _ => (),
+            }
+        }
+        Ok(())
+    }
+
+    fn parse_paragraph(node: Node, timing: &Timing, parent: &Inherited) -> Result<TTMLParagraph> {
+        let (begin, end) = times(node, timing, parent)?;
+        let end = end.ok_or(TTMLError::new(
+            TTMLErrorKind::Parse("missing end time".to_string()),
+            line(node),
+        ))?;
+
+        let mut paragraph = TTMLParagraph {
+            begin: millis_to_time(begin),
+            end: millis_to_time(end),
+            region: parent.region.clone(),
+            style: parent.style.clone(),
+            attributes: parent.attributes.clone(),
+            ..Default::default()
+        };
+        for (name, value) in attributes(node) {
+            match name.as_str() {
+                "xml:id" => paragraph.id = Some(value),
+                "region" => paragraph.region = Some(value),
+                "style" => paragraph
+                    .style
+                    .extend(value.split_whitespace().map(str::to_string)),
+                "begin" | "end" | "dur" => (),
+                _ => set_attribute(&mut paragraph.attributes, name, value),
+            }
+        }
+
+        let mut text = String::new();
+        write_content(node, &mut text);
+        while text.contains("  ") {
+            text = text.replace("  ", " ")
+        }
+        paragraph.text = text
+            .trim()
+            .replace(" <br/>", "<br/>")
+            .replace("<br/> ", "<br/>");
+        Ok(paragraph)
+    }
+
+    /// Writes the content of `node` as markup, collapsing whitespace.
+    fn write_content(node: Node, out: &mut String) {
+        for child in node.children() {
+            if child.is_text() {
+                let text = child.text().unwrap_or_default();
+                let mut collapsed = String::with_capacity(text.len());
+                let mut space = false;
+                for c in text.chars() {
+                    if matches!(c, ' ' | '\t' | '\n' | '\r') {
+                        space = true
+                    } else {
+                        if space {
+                            collapsed.push(' ')
+                        }
+                        space = false;
+                        collapsed.push(c)
+                    }
+                }
+                if space {
+                    collapsed.push(' ')
+                }
+                out.push_str(&escape_text(&collapsed))
+            } else if child.tag_name().name() == "br" {
+                out.push_str("<br/>")
+            } else if child.tag_name().name() == "span" {
+                out.push_str("<span");
+                for (name, value) in attributes(child) {
+                    out.push_str(&format!(" {name}=\"{}\"", escape_attribute(&value)))
+                }
+                out.push('>');
+                write_content(child, out);
+                out.push_str("</span>")
+            } else if child.is_element() && !is_ignored(child) {
+                write_content(child, out)
+            }
+        }
+    }
+}
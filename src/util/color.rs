@@ -5,9 +5,13 @@
 //! `&HRR`,`&HGGRR`,`&HBBGGRR` or `&HAABBGGRR`.
 //!
 //! VTT Colors start with `#` and are the usual ARGB or RGB hex formats.
+//!
+//! MicroDVD Colors start with `$` and look like `$BBGGRR`.
+//!
+//! TTML Colors are `#RRGGBB` / `#RRGGBBAA` hex colors, `rgb(r,g,b)` / `rgba(r,g,b,a)` or named
+//! colors.
 use serde::Deserialize;
 use serde::Serialize;
-use std::cmp::Ordering;
 
 /// Generic ARGB color struct.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
@@ -62,17 +66,21 @@ impl Color {
 
     pub(crate) fn from_vtt(color: &str) -> Result<Self, String> {
         if let Some(color) = color.strip_prefix('#') {
-            let color = match color.len().cmp(&8) {
-                Ordering::Greater => return Err(format!("invalid hex color: #{color}")),
-                Ordering::Less if color.len() < 7 => format!("#{:0>6}FF", color),
-                Ordering::Less => format!("#{:F>8}", color),
-                _ => color.to_string(),
+            if !color.chars().all(|c| c.is_ascii_hexdigit()) {
+                return Err(format!("invalid hex color: #{color}"));
+            }
+            // the 3 and 4 digit shorthands repeat every digit
+            let color = match color.len() {
+                3 | 4 => color.chars().flat_map(|c| [c, c]).collect(),
+                6 | 8 => color.to_string(),
+                _ => return Err(format!("invalid hex color: #{color}")),
             };
+            let alpha = color.get(6..8).unwrap_or("FF");
             Ok(Self {
                 r: u8::from_str_radix(&color[0..2], 16).map_err(|e| e.to_string())?,
                 g: u8::from_str_radix(&color[2..4], 16).map_err(|e| e.to_string())?,
                 b: u8::from_str_radix(&color[4..6], 16).map_err(|e| e.to_string())?,
-                a: u8::from_str_radix(&color[6..8], 16).map_err(|e| e.to_string())?,
+                a: u8::from_str_radix(alpha, 16).map_err(|e| e.to_string())?,
             })
         } else {
             // command to get all colors:
@@ -229,6 +237,48 @@ impl Color {
         }
     }
 
+    /// Parses MicroDVD colors, which look like `$BBGGRR`.
+    pub(crate) fn from_microdvd(color: &str) -> Result<Self, String> {
+        let Some(color) = color
+            .strip_prefix('$')
+            .filter(|c| c.len() == 6 && c.bytes().all(|b| b.is_ascii_hexdigit()))
+        else {
+            return Err(format!("invalid color: {color}"));
+        };
+        Ok(Self {
+            r: u8::from_str_radix(&color[4..6], 16).map_err(|e| e.to_string())?,
+            g: u8::from_str_radix(&color[2..4], 16).map_err(|e| e.to_string())?,
+            b: u8::from_str_radix(&color[0..2], 16).map_err(|e| e.to_string())?,
+            a: 255,
+        })
+    }
+
+    /// Parses TTML colors, which look like `#RRGGBB`, `#RRGGBBAA`, `rgb(r,g,b)`, `rgba(r,g,b,a)`
+    /// or are named colors like `white`.
+    pub(crate) fn from_ttml(color: &str) -> Result<Self, String> {
+        let color = color.trim();
+        if color.eq_ignore_ascii_case("transparent") {
+            return Ok(TRANSPARENT);
+        }
+        let Some(components) = color
+            .strip_prefix("rgba(")
+            .or(color.strip_prefix("rgb("))
+            .and_then(|c| c.strip_suffix(')'))
+        else {
+            return Self::from_vtt(color);
+        };
+
+        let components = components
+            .split(',')
+            .map(|c| c.trim().parse::<u8>().map_err(|e| e.to_string()))
+            .collect::<Result<Vec<u8>, String>>()?;
+        match (color.starts_with("rgba"), components.as_slice()) {
+            (false, [r, g, b]) => Ok(Self::new(*r, *g, *b, 255)),
+            (true, [r, g, b, a]) => Ok(Self::new(*r, *g, *b, *a)),
+            _ => Err(format!("invalid color: {color}")),
+        }
+    }
+
     pub fn to_ssa_string(&self) -> String {
         if self.a == 255 {
             format!("&H{:0>2X}{:0>2X}{:0>2X}", self.b, self.g, self.r)
@@ -240,6 +290,16 @@ impl Color {
         }
     }
 
+    /// MicroDVD colors don't support transparency, the alpha channel is ignored.
+    pub fn to_microdvd_string(&self) -> String {
+        format!("${:0>2X}{:0>2X}{:0>2X}", self.b, self.g, self.r)
+    }
+
+    /// TTML colors look like `#RRGGBB`, or `#RRGGBBAA` if the color is transparent.
+    pub fn to_ttml_string(&self) -> String {
+        self.to_vtt_string()
+    }
+
     pub fn to_vtt_string(&self) -> String {
         if self.a == 255 {
             format!("#{:0>2X}{:0>2X}{:0>2X}", self.r, self.g, self.b)
@@ -0,0 +1,157 @@
+//! Helpers for frame based timing.
+//!
+//! Frame `n` of a video with a constant [FrameRate] is presented at `n / fps` seconds. A cue is
+//! visible in frame `n` if `start <= n / fps < end`.
+use serde::Deserialize;
+use serde::Serialize;
+use time::Time;
+
+use super::timestamp::{millis_to_time, time_to_millis};
+
+/// A rational frame rate, e.g. `24000/1001` for NTSC film.
+///
+/// Both parts must be positive. [FrameRate::try_new] and deserializing reject zero, conversions
+/// treat a zero part of a rate built otherwise as one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "parse::FrameRate")]
+pub struct FrameRate {
+    pub numerator: u32,
+    pub denominator: u32,
+}
+
+impl Default for FrameRate {
+    fn default() -> Self {
+        Self::FPS_25
+    }
+}
+
+impl FrameRate {
+    pub const FPS_23_976: FrameRate = FrameRate::new(24000, 1001);
+    pub const FPS_24: FrameRate = FrameRate::new(24, 1);
+    pub const FPS_25: FrameRate = FrameRate::new(25, 1);
+    pub const FPS_29_97: FrameRate = FrameRate::new(30000, 1001);
+    pub const FPS_30: FrameRate = FrameRate::new(30, 1);
+    pub const FPS_50: FrameRate = FrameRate::new(50, 1);
+    pub const FPS_59_94: FrameRate = FrameRate::new(60000, 1001);
+    pub const FPS_60: FrameRate = FrameRate::new(60, 1);
+
+    pub const fn new(numerator: u32, denominator: u32) -> Self {
+        Self {
+            numerator,
+            denominator,
+        }
+    }
+
+    /// Like [FrameRate::new], but fails if `numerator` or `denominator` is zero.
+    pub fn try_new(numerator: u32, denominator: u32) -> Result<Self, String> {
+        if numerator == 0 || denominator == 0 {
+            return Err(format!("invalid frame rate: {numerator}/{denominator}"));
+        }
+        Ok(Self::new(numerator, denominator))
+    }
+
+    /// Frames per second as floating point number.
+    pub fn fps(&self) -> f64 {
+        let (numerator, denominator) = self.parts();
+        numerator as f64 / denominator as f64
+    }
+
+    /// Numerator and denominator, with zero replaced by one so that they can be divided by.
+    fn parts(&self) -> (i64, i64) {
+        (self.numerator.max(1) as i64, self.denominator.max(1) as i64)
+    }
+
+    /// Returns the duration of the given amount of frames in milliseconds, rounded to the nearest
+    /// millisecond.
+    pub fn frames_to_millis(&self, frames: i64) -> i64 {
+        let (numerator, denominator) = self.parts();
+        (frames as f64 * 1000.0 * denominator as f64 / numerator as f64).round() as i64
+    }
+
+    /// Returns the frame presented at `millis`. If `millis` lies between two frames, `rounding`
+    /// decides which one is returned.
+    pub fn millis_to_frame(&self, millis: i64, rounding: FrameRounding) -> i64 {
+        let (numerator, denominator) = self.parts();
+        let (numerator, denominator) = (millis * numerator, 1000 * denominator);
+        match rounding {
+            FrameRounding::Down => numerator.div_euclid(denominator),
+            FrameRounding::Up => (numerator + denominator - 1).div_euclid(denominator),
+            FrameRounding::Nearest => (2 * numerator + denominator).div_euclid(2 * denominator),
+        }
+    }
+
+    /// Returns the frame presented at `time`. See [FrameRate::millis_to_frame] for the meaning of
+    /// `rounding`.
+    pub fn time_to_frame(&self, time: Time, rounding: FrameRounding) -> i64 {
+        self.millis_to_frame(time_to_millis(time), rounding)
+    }
+
+    /// Returns the time at which `frame` is presented, rounded to the nearest millisecond.
+    pub fn frame_to_time(&self, frame: i64) -> Time {
+        millis_to_time(self.frames_to_millis(frame))
+    }
+
+    /// Returns the time a cue must start (or end) at to become visible (or hidden) exactly at
+    /// `frame`.
+    ///
+    /// Like Aegisub, this is the middle between the presentation time of the previous frame and
+    /// `frame`, so that the cue still switches at the correct frame after being rounded to the
+    /// precision of a subtitle format (e.g. centiseconds in `.ass`).
+    pub fn frame_boundary(&self, frame: i64) -> Time {
+        if frame <= 0 {
+            return Time::MIDNIGHT;
+        }
+        let (numerator, denominator) = self.parts();
+        millis_to_time(
+            ((2 * frame - 1) as f64 * 500.0 * denominator as f64 / numerator as f64).round() as i64,
+        )
+    }
+
+    /// Returns the frame whose boundary (see [FrameRate::frame_boundary]) `time` should be moved
+    /// to. Times that already are a frame boundary keep their frame, as the boundary is rounded to
+    /// milliseconds and `rounding` could pick the neighbouring frame.
+    pub fn boundary_frame(&self, time: Time, rounding: FrameRounding) -> i64 {
+        let frame = self.time_to_frame(time, rounding);
+        [frame, frame + 1, frame - 1]
+            .into_iter()
+            .find(|f| *f >= 0 && self.frame_boundary(*f) == time)
+            .unwrap_or(frame)
+    }
+
+    /// Moves `time` to the boundary of the frame chosen by `rounding`. See
+    /// [FrameRate::boundary_frame].
+    pub fn snap(&self, time: Time, rounding: FrameRounding) -> Time {
+        self.frame_boundary(self.boundary_frame(time, rounding))
+    }
+}
+
+/// Decides which frame is chosen if a time lies between two frames.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum FrameRounding {
+    /// The frame presented closest to the time.
+    #[default]
+    Nearest,
+    /// The last frame presented at or before the time.
+    Down,
+    /// The first frame presented at or after the time.
+    Up,
+}
+
+mod parse {
+    use super::*;
+
+    /// The unvalidated fields of a [FrameRate](super::FrameRate).
+    #[derive(Deserialize)]
+    pub(super) struct FrameRate {
+        numerator: u32,
+        denominator: u32,
+    }
+
+    impl TryFrom<FrameRate> for super::FrameRate {
+        type Error = String;
+
+        fn try_from(value: FrameRate) -> Result<Self, Self::Error> {
+            Self::try_new(value.numerator, value.denominator)
+        }
+    }
+}
@@ -1,5 +1,11 @@
 //! Helper module that provides color and timestamp structs and functions for
 //! modifying subtitles.
 mod color;
+mod frame;
+mod smpte;
+mod timestamp;
 
 pub use color::*;
+pub use frame::*;
+pub use smpte::*;
+pub use timestamp::*;
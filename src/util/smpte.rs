@@ -0,0 +1,171 @@
+//! SMPTE timecode helpers.
+//!
+//! Timecodes look like `HH:MM:SS:FF`. Drop-frame timecodes (used for 29.97 and 59.94 fps) separate
+//! the frames with `;` instead and skip the first frame numbers of every minute that isn't
+//! divisible by ten, so that the timecode stays in sync with the wall clock.
+use serde::Deserialize;
+use serde::Serialize;
+use std::fmt::Display;
+use time::Time;
+
+use super::frame::{FrameRate, FrameRounding};
+use crate::error;
+
+/// A SMPTE timecode.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Timecode {
+    pub hours: u32,
+    pub minutes: u32,
+    pub seconds: u32,
+    pub frames: u32,
+    pub drop_frame: bool,
+}
+
+impl Timecode {
+    pub fn new(hours: u32, minutes: u32, seconds: u32, frames: u32, drop_frame: bool) -> Self {
+        Self {
+            hours,
+            minutes,
+            seconds,
+            frames,
+            drop_frame,
+        }
+    }
+
+    /// Parses `HH:MM:SS:FF` (non-drop) or `HH:MM:SS;FF` (drop-frame) timecodes. `.` is accepted as
+    /// drop-frame separator as well. Drop-frame timecodes are only valid at 29.97 and 59.94 fps.
+    ///
+    /// Minutes and seconds must be below 60 and frames below the frame rate, drop-frame timecodes
+    /// must not use one of the skipped frame numbers.
+    pub fn parse(timecode: &str, frame_rate: FrameRate) -> Result<Self, TimecodeError> {
+        let timecode = timecode.trim();
+        let error = || TimecodeError::Parse(format!("invalid timecode '{timecode}'"));
+        let parts: Vec<&str> = timecode.split([':', ';', '.']).collect();
+        if parts.len() != 4 {
+            return Err(error());
+        }
+        let parse = |s: &str| s.parse::<u32>().map_err(|_| error());
+
+        let drop_frame = timecode.contains([';', '.']);
+        if drop_frame && !is_drop_frame_rate(frame_rate) {
+            return Err(TimecodeError::DropFrame);
+        }
+
+        let parsed = Self {
+            hours: parse(parts[0])?,
+            minutes: parse(parts[1])?,
+            seconds: parse(parts[2])?,
+            frames: parse(parts[3])?,
+            drop_frame,
+        };
+        // labels out of range or skipped by drop-frame don't survive the round trip
+        if Self::from_frame(parsed.to_frame(frame_rate), frame_rate, drop_frame) != parsed {
+            return Err(error());
+        }
+        Ok(parsed)
+    }
+
+    /// Creates the timecode of the given frame number. `drop_frame` is ignored at frame rates
+    /// other than 29.97 and 59.94 fps.
+    pub fn from_frame(frame: i64, frame_rate: FrameRate, drop_frame: bool) -> Self {
+        let nominal = nominal_fps(frame_rate);
+        let drop_frame = drop_frame && is_drop_frame_rate(frame_rate);
+        let mut frame = frame.max(0);
+
+        if drop_frame {
+            let drop = drop_frames(frame_rate);
+            let frames_per_minute = nominal * 60 - drop;
+            let frames_per_10_minutes = nominal * 600 - drop * 9;
+
+            let tens = frame / frames_per_10_minutes;
+            let rest = frame % frames_per_10_minutes;
+            frame += drop * 9 * tens;
+            if rest > drop {
+                frame += drop * ((rest - drop) / frames_per_minute)
+            }
+        }
+
+        Self {
+            hours: (frame / (nominal * 3600)) as u32,
+            minutes: (frame / (nominal * 60) % 60) as u32,
+            seconds: (frame / nominal % 60) as u32,
+            frames: (frame % nominal) as u32,
+            drop_frame,
+        }
+    }
+
+    /// Returns the frame number the timecode refers to. Drop-frame timecodes are counted like
+    /// non-drop ones at frame rates other than 29.97 and 59.94 fps.
+    pub fn to_frame(&self, frame_rate: FrameRate) -> i64 {
+        let nominal = nominal_fps(frame_rate);
+        let total_minutes = self.hours as i64 * 60 + self.minutes as i64;
+        let frame = (total_minutes * 60 + self.seconds as i64) * nominal + self.frames as i64;
+
+        if self.drop_frame && is_drop_frame_rate(frame_rate) {
+            frame - drop_frames(frame_rate) * (total_minutes - total_minutes / 10)
+        } else {
+            frame
+        }
+    }
+
+    /// Creates the timecode of the frame presented at `time`. See [Timecode::from_frame].
+    pub fn from_time(
+        time: Time,
+        frame_rate: FrameRate,
+        drop_frame: bool,
+        rounding: FrameRounding,
+    ) -> Self {
+        Self::from_frame(
+            frame_rate.time_to_frame(time, rounding),
+            frame_rate,
+            drop_frame,
+        )
+    }
+
+    /// Returns the time at which the frame of this timecode is presented.
+    pub fn to_time(&self, frame_rate: FrameRate) -> Time {
+        frame_rate.frame_to_time(self.to_frame(frame_rate))
+    }
+}
+
+impl Display for Timecode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{:02}:{:02}:{:02}{}{:02}",
+            self.hours,
+            self.minutes,
+            self.seconds,
+            if self.drop_frame { ';' } else { ':' },
+            self.frames
+        )
+    }
+}
+
+error! {
+    TimecodeError {
+        Parse(String),
+        DropFrame,
+    }
+}
+
+/// The integer frame rate timecodes count with, e.g. `30` for 29.97 fps.
+fn nominal_fps(frame_rate: FrameRate) -> i64 {
+    (frame_rate.fps().round() as i64).max(1)
+}
+
+/// Frame numbers skipped every minute in drop-frame timecodes: 2 for 29.97 and 4 for 59.94 fps.
+fn drop_frames(frame_rate: FrameRate) -> i64 {
+    nominal_fps(frame_rate) / 15
+}
+
+/// Whether drop-frame timecodes exist at `frame_rate`, which is only the case for 29.97 and 59.94
+/// fps.
+fn is_drop_frame_rate(frame_rate: FrameRate) -> bool {
+    [FrameRate::FPS_29_97, FrameRate::FPS_59_94]
+        .iter()
+        .any(|rate| {
+            frame_rate.numerator as u64 * rate.denominator as u64
+                == rate.numerator as u64 * frame_rate.denominator as u64
+        })
+}
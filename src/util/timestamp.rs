@@ -0,0 +1,25 @@
+//! Helpers for converting the [Time] values used by all subtitle types to and from plain
+//! millisecond counts, which makes arithmetic across cue boundaries a lot easier.
+use time::{Duration, Time};
+
+/// The largest timestamp that can be represented by [Time].
+const MAX_MILLIS: i64 = 24 * 60 * 60 * 1000 - 1;
+
+/// Returns the amount of milliseconds since midnight of the given [Time].
+pub fn time_to_millis(time: Time) -> i64 {
+    let (h, m, s, ms) = time.as_hms_milli();
+    h as i64 * 3_600_000 + m as i64 * 60_000 + s as i64 * 1000 + ms as i64
+}
+
+/// Creates a [Time] from the given milliseconds. Values below zero or above `23:59:59.999` are
+/// clamped, as [Time] cannot represent them.
+pub fn millis_to_time(millis: i64) -> Time {
+    let millis = millis.clamp(0, MAX_MILLIS);
+    Time::MIDNIGHT + Duration::milliseconds(millis)
+}
+
+/// Returns the duration between `start` and `end` in milliseconds. The value is negative if `end`
+/// lies before `start`.
+pub fn millis_between(start: Time, end: Time) -> i64 {
+    time_to_millis(end) - time_to_millis(start)
+}
@@ -3,6 +3,7 @@
 //! It describes the [VTTStyle], [VTT] and [VTTLine] structs and
 //! provides the [parse] function.
 
+use super::cue::{strip_markup, Cue, Subtitle};
 use super::srt::{SRTLine, SRT};
 use super::ssa::{SSAEvent, SSAInfo, SSAStyle, SSA};
 use super::strip_bom;
@@ -277,6 +278,78 @@ impl VTT {
     }
 }
 
+impl Cue for VTTLine {
+    const LINE_BREAK: &'static str = "\n";
+
+    fn start(&self) -> Time {
+        self.start
+    }
+    fn end(&self) -> Time {
+        self.end
+    }
+    fn set_start(&mut self, start: Time) {
+        self.start = start
+    }
+    fn set_end(&mut self, end: Time) {
+        self.end = end
+    }
+    fn text(&self) -> &str {
+        &self.text
+    }
+    fn set_text(&mut self, text: String) {
+        self.text = text
+    }
+    /// Removes all cue tags (`<v Speaker>`, `<i>`, `<00:00:01.000>`, ...) and resolves the
+    /// character references allowed in cue text.
+    fn plain_text(&self) -> String {
+        strip_markup(&self.text, '<', '>')
+            .lines()
+            .collect::<Vec<&str>>()
+            .join("\n")
+            .replace("&lt;", "<")
+            .replace("&gt;", ">")
+            .replace("&nbsp;", "\u{a0}")
+            .replace("&lrm;", "\u{200e}")
+            .replace("&rlm;", "\u{200f}")
+            .replace("&amp;", "&")
+    }
+    /// Returns the position and time of all timestamp tags (`<00:00:01.000>`).
+    fn inline_timestamps(&self) -> Vec<(usize, Time)> {
+        let mut timestamps = vec![];
+        let mut offset = 0;
+        while let Some(start) = self.text[offset..].find('<').map(|s| offset + s) {
+            let Some(len) = self.text[start..].find('>') else {
+                break;
+            };
+            let tag = &self.text[start + 1..start + len];
+            if tag.starts_with(|c: char| c.is_ascii_digit()) {
+                // like in the timing line, hours are optional
+                let tag = if tag.matches(':').count() < 2 {
+                    format!("00:{tag}")
+                } else {
+                    tag.to_string()
+                };
+                if let Ok(time) = Time::parse(&tag, parse::TIME_FORMAT) {
+                    timestamps.push((start, time))
+                }
+            }
+            offset = start + len + 1
+        }
+        timestamps
+    }
+}
+
+impl Subtitle for VTT {
+    type Cue = VTTLine;
+
+    fn cues(&self) -> &[VTTLine] {
+        &self.lines
+    }
+    fn cues_mut(&mut self) -> &mut Vec<VTTLine> {
+        &mut self.lines
+    }
+}
+
 impl Display for VTT {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let mut blocks = vec![];
@@ -474,7 +547,7 @@ mod parse {
         // extract selector in brackets if existent
         block = block.trim_start();
         if block.starts_with('(') {
-            let Some(closing_idx) = block.find(|c| c == ')') else {
+            let Some(closing_idx) = block.find(')') else {
                 return Err(Error {
                     line: 1,
                     kind: VTTErrorKind::Parse("selector isn't closed".to_string()),
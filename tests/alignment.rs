@@ -0,0 +1,95 @@
+use rsubs_lib::{CueAligner, CuePair, SRT, VTT};
+
+const SOURCE: &str = "1
+00:00:01,000 --> 00:00:04,000
+This is a rather long sentence that got split.
+
+2
+00:00:05,000 --> 00:00:06,000
+Short one.
+
+3
+00:00:07,000 --> 00:00:08,000
+Two
+
+4
+00:00:08,000 --> 00:00:09,000
+parts.
+
+5
+00:00:20,000 --> 00:00:21,000
+Untranslated.";
+
+const TARGET: &str = "WEBVTT
+
+00:01.000 --> 00:02.400
+Das ist ein ziemlich langer Satz,
+
+00:02.500 --> 00:04.000
+der geteilt wurde.
+
+00:05.100 --> 00:06.000
+Kurz.
+
+00:07.000 --> 00:09.000
+Zwei Teile.
+
+00:12.000 --> 00:13.000
+Zusätzlich.";
+
+fn pair(source: &[usize], target: &[usize]) -> (Vec<usize>, Vec<usize>) {
+    (source.to_vec(), target.to_vec())
+}
+
+#[test]
+fn align() {
+    let source = SRT::parse(SOURCE).unwrap();
+    let target = VTT::parse(TARGET).unwrap();
+    let pairs = CueAligner::default().align(&source, &target);
+
+    assert_eq!(
+        pairs
+            .iter()
+            .map(|p| (p.source.clone(), p.target.clone()))
+            .collect::<Vec<_>>(),
+        vec![
+            pair(&[0], &[0, 1]),
+            pair(&[1], &[2]),
+            pair(&[2, 3], &[3]),
+            pair(&[], &[4]),
+            pair(&[4], &[]),
+        ]
+    );
+    assert!(pairs[0].score > 0.9);
+    assert!(pairs[2].is_matched());
+    assert!(!pairs[3].is_matched());
+    assert_eq!(pairs[4].score, 0.0);
+}
+
+#[test]
+fn length_ratio() {
+    let source = SRT::parse(SOURCE).unwrap();
+    let target = VTT::parse(TARGET).unwrap();
+    let aligner = CueAligner {
+        length_ratio: Some(1.0),
+        ..Default::default()
+    };
+    let pairs = aligner.align(&source, &target);
+
+    assert_eq!(pairs.iter().filter(|p| p.is_matched()).count(), 3);
+    assert!(pairs[1].score < CueAligner::default().align(&source, &target)[1].score);
+}
+
+#[test]
+fn empty() {
+    let source = SRT::parse(SOURCE).unwrap();
+    let pairs = CueAligner::default().align(&source, &SRT::default());
+
+    assert_eq!(pairs.len(), 5);
+    assert!(pairs.iter().all(|p| p
+        == &CuePair {
+            source: p.source.clone(),
+            target: vec![],
+            score: 0.0
+        }));
+}
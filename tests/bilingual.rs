@@ -0,0 +1,106 @@
+use rsubs_lib::util::Alignment;
+use rsubs_lib::{bilingual_ssa, bilingual_vtt, SRT, VTT};
+use time::macros::time;
+
+const ENGLISH: &str = "1
+00:00:01,000 --> 00:00:03,000
+<i>Hello</i> there.
+
+2
+00:00:03,000 --> 00:00:05,000
+How are you?
+
+3
+00:00:08,000 --> 00:00:09,000
+Fine.";
+
+const GERMAN: &str = "WEBVTT
+
+00:01.100 --> 00:04.900
+Hallo, wie geht's?
+
+00:10.000 --> 00:11.000
+Tschüss & bis bald.";
+
+#[test]
+fn ssa() {
+    let english = SRT::parse(ENGLISH).unwrap();
+    let german = VTT::parse(GERMAN).unwrap();
+    let ssa = bilingual_ssa(&german, &english);
+
+    assert_eq!(ssa.styles.len(), 2);
+    assert_eq!(ssa.styles[0].alignment, Alignment::TopCenter);
+    assert_eq!(ssa.styles[1].alignment, Alignment::BottomCenter);
+
+    assert_eq!(ssa.events.len(), 4);
+    assert_eq!(ssa.events[0].style, "Top");
+    assert_eq!(ssa.events[0].start, time!(00:00:01));
+    assert_eq!(ssa.events[0].end, time!(00:00:05));
+    assert_eq!(ssa.events[0].text, "Hallo, wie geht's?");
+    assert_eq!(ssa.events[1].style, "Bottom");
+    assert_eq!(ssa.events[1].start, time!(00:00:01));
+    assert_eq!(ssa.events[1].end, time!(00:00:05));
+    assert_eq!(ssa.events[1].text, "Hello there.\\NHow are you?");
+    assert_eq!(ssa.events[2].style, "Bottom");
+    assert_eq!(ssa.events[2].text, "Fine.");
+    assert_eq!(ssa.events[3].style, "Top");
+    assert_eq!(ssa.events[3].start, time!(00:00:10));
+}
+
+#[test]
+fn vtt() {
+    let english = SRT::parse(ENGLISH).unwrap();
+    let german = VTT::parse(GERMAN).unwrap();
+    let vtt = bilingual_vtt(&english, &german);
+
+    assert_eq!(vtt.lines.len(), 4);
+    assert_eq!(
+        vtt.lines[0].settings.get("line"),
+        Some(&Some("0".to_string()))
+    );
+    assert_eq!(
+        vtt.lines[1].settings.get("line"),
+        Some(&Some("-1".to_string()))
+    );
+    assert_eq!(vtt.lines[3].text, "Tschüss &amp; bis bald.");
+}
+
+#[test]
+fn offset() {
+    // every cue overlaps two cues of the other subtitle for exactly half of its duration
+    let top = SRT::parse(
+        "1
+00:00:00,000 --> 00:00:02,000
+A
+
+2
+00:00:02,000 --> 00:00:04,000
+B",
+    )
+    .unwrap();
+    let bottom = SRT::parse(
+        "1
+00:00:01,000 --> 00:00:03,000
+X
+
+2
+00:00:03,000 --> 00:00:05,000
+Y",
+    )
+    .unwrap();
+    let ssa = bilingual_ssa(&top, &bottom);
+
+    let events: Vec<(&str, &str)> = ssa
+        .events
+        .iter()
+        .map(|e| (e.style.as_str(), e.text.as_str()))
+        .collect();
+    assert_eq!(
+        events,
+        [("Top", "A"), ("Bottom", "X"), ("Top", "B"), ("Bottom", "Y")]
+    );
+    assert_eq!(ssa.events[0].start, time!(00:00:00));
+    assert_eq!(ssa.events[0].end, time!(00:00:02));
+    assert_eq!(ssa.events[2].start, time!(00:00:02));
+    assert_eq!(ssa.events[2].end, time!(00:00:05));
+}
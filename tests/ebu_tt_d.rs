@@ -0,0 +1,222 @@
+use rsubs_lib::util::{Alignment, Color, BLACK, TRANSPARENT, WHITE};
+use rsubs_lib::{EBUTTDErrorKind, EBUTTDLine, EBUTTD, SRT, SSA, TTML, VTT};
+use time::macros::time;
+
+const EBU_TT_D_DOCUMENT: &str = r##"<?xml version="1.0" encoding="UTF-8"?>
+<tt xmlns="http://www.w3.org/ns/ttml" xmlns:ttp="http://www.w3.org/ns/ttml#parameter"
+    xmlns:tts="http://www.w3.org/ns/ttml#styling" xmlns:ebuttm="urn:ebu:tt:metadata"
+    xml:lang="en-GB" ttp:timeBase="media" ttp:cellResolution="50 30">
+  <head>
+    <metadata>
+      <ebuttm:documentMetadata>
+        <ebuttm:conformsToStandard>urn:ebu:tt:distribution:2014-01</ebuttm:conformsToStandard>
+      </ebuttm:documentMetadata>
+    </metadata>
+    <styling>
+      <style xml:id="base" tts:fontSize="100%"/>
+      <style xml:id="white" tts:color="#FFFFFF" tts:backgroundColor="#000000"/>
+      <style xml:id="yellow" tts:color="#FFFF00" tts:backgroundColor="#000000"/>
+      <style xml:id="left" tts:textAlign="left"/>
+      <style xml:id="centre" tts:textAlign="center"/>
+    </styling>
+    <layout>
+      <region xml:id="bottom" tts:origin="10% 10%" tts:extent="80% 80%" tts:displayAlign="after"/>
+      <region xml:id="top" tts:origin="10% 10%" tts:extent="80% 80%" tts:displayAlign="before"/>
+    </layout>
+  </head>
+  <body>
+    <div style="base">
+      <p xml:id="sub1" begin="00:00:01.000" end="00:00:03.500" region="bottom" style="centre">
+        <span style="white">Hello</span><br/><span style="white">world &amp; more</span>
+      </p>
+      <p xml:id="sub2" begin="4s" end="6000ms" region="top" style="left">
+        <span style="yellow" tts:fontStyle="italic">Yellow</span>
+      </p>
+    </div>
+  </body>
+</tt>"##;
+
+#[test]
+fn parse() {
+    let ebu_tt_d = EBUTTD::parse(EBU_TT_D_DOCUMENT).unwrap();
+
+    assert_eq!(ebu_tt_d.lang, "en-GB");
+    assert_eq!(
+        ebu_tt_d.lines,
+        vec![
+            EBUTTDLine {
+                start: time!(00:00:01),
+                end: time!(00:00:03.500),
+                text: "Hello\nworld & more".to_string(),
+                ..Default::default()
+            },
+            EBUTTDLine {
+                start: time!(00:00:04),
+                end: time!(00:00:06),
+                text: "Yellow".to_string(),
+                alignment: Alignment::TopLeft,
+                color: Color::new(255, 255, 0, 255),
+                italic: true,
+                ..Default::default()
+            },
+        ]
+    );
+
+    // the background defaults to black if no style sets it
+    let ebu_tt_d =
+        EBUTTD::parse(EBU_TT_D_DOCUMENT.replace(r##"tts:backgroundColor="#000000""##, "")).unwrap();
+    assert_eq!(ebu_tt_d.lines[0].background_color, BLACK);
+}
+
+#[test]
+fn parse_error() {
+    let err = EBUTTD::parse(EBU_TT_D_DOCUMENT.replace("\"media\"", "\"smpte\"")).unwrap_err();
+    assert_eq!(err.line(), 2);
+    assert_eq!(
+        err.kind(),
+        &EBUTTDErrorKind::Constraint("time base 'smpte' is not allowed".to_string())
+    );
+
+    let err = EBUTTD::parse(EBU_TT_D_DOCUMENT.replace("6000ms", "150f")).unwrap_err();
+    assert_eq!(err.line(), 28);
+    assert_eq!(
+        err.kind(),
+        &EBUTTDErrorKind::Constraint("time '150f' is not allowed".to_string())
+    );
+
+    let err = EBUTTD::parse(EBU_TT_D_DOCUMENT.replace("end=\"6000ms\"", "dur=\"2s\"")).unwrap_err();
+    assert_eq!(
+        err.kind(),
+        &EBUTTDErrorKind::Constraint("dur is not allowed".to_string())
+    );
+
+    let err = EBUTTD::parse("<html/>").unwrap_err();
+    assert_eq!(err.kind(), &EBUTTDErrorKind::Invalid);
+}
+
+#[test]
+fn display() {
+    let ebu_tt_d = EBUTTD {
+        lang: "en".to_string(),
+        lines: vec![
+            EBUTTDLine {
+                start: time!(00:00:01),
+                end: time!(00:00:02),
+                text: "First <line>\nsecond".to_string(),
+                ..Default::default()
+            },
+            EBUTTDLine {
+                start: time!(00:00:03),
+                end: time!(00:00:03),
+                text: "No duration".to_string(),
+                ..Default::default()
+            },
+            EBUTTDLine {
+                start: time!(00:00:04),
+                end: time!(00:00:05),
+                text: "Top".to_string(),
+                alignment: Alignment::TopRight,
+                color: Color::new(0, 255, 255, 255),
+                background_color: TRANSPARENT,
+                italic: true,
+            },
+        ],
+    };
+    let written = ebu_tt_d.to_string();
+
+    assert!(written.contains(r#"ttp:timeBase="media""#));
+    assert!(written.contains(
+        "<ebuttm:conformsToStandard>urn:ebu:tt:distribution:2018-04</ebuttm:conformsToStandard>"
+    ));
+    assert!(written.contains(
+        r##"<style xml:id="s2" tts:color="#00FFFF" tts:backgroundColor="#00000000" tts:fontStyle="italic"/>"##
+    ));
+    assert!(written.contains(
+        r#"<p xml:id="sub1" begin="00:00:01.000" end="00:00:02.000" region="bottom" style="center"><span style="s1">First &lt;line&gt;</span><br/><span style="s1">second</span></p>"#
+    ));
+    assert!(written.contains(r#"<p xml:id="sub2" begin="00:00:04.000""#));
+    assert!(!written.contains("No duration"));
+    assert!(!written.contains(r#"xml:id="middle""#));
+
+    let parsed = EBUTTD::parse(&written).unwrap();
+    assert_eq!(parsed.lines.len(), 2);
+    assert_eq!(parsed.lines[0], ebu_tt_d.lines[0]);
+    assert_eq!(parsed.lines[1], ebu_tt_d.lines[2]);
+
+    // also a valid TTML document
+    assert_eq!(TTML::parse(&written).unwrap().paragraphs.len(), 2);
+}
+
+#[test]
+fn convert_to_other() {
+    let ebu_tt_d = EBUTTD::parse(EBU_TT_D_DOCUMENT).unwrap();
+
+    let srt = ebu_tt_d.to_srt();
+    assert_eq!(srt.lines[0].text, "Hello\r\nworld & more");
+    assert_eq!(
+        srt.lines[1].text,
+        "{\\an7}<i><font color=\"#FFFF00\">Yellow</font></i>"
+    );
+
+    let vtt = ebu_tt_d.to_vtt();
+    assert_eq!(vtt.lines[0].text, "Hello\nworld &amp; more");
+    assert!(vtt.lines[0].settings.is_empty());
+    assert_eq!(vtt.lines[1].text, "<c.s1><i>Yellow</i></c>");
+    assert_eq!(vtt.lines[1].settings["line"].as_deref(), Some("0"));
+    assert_eq!(vtt.lines[1].settings["align"].as_deref(), Some("left"));
+    assert_eq!(vtt.styles[0].selector.as_deref(), Some(".s1"));
+    assert_eq!(vtt.styles[0].entries["color"], "#FFFF00");
+
+    let ssa = ebu_tt_d.to_ssa();
+    assert_eq!(ssa.events[0].text, "Hello\\Nworld & more");
+    assert_eq!(ssa.events[1].text, "{\\an7\\i1\\c&H00FFFF&}Yellow");
+}
+
+#[test]
+fn convert_from_other() {
+    let srt = SRT::parse("1\n00:00:01,000 --> 00:00:02,000\n<i>Italic</i>\n").unwrap();
+    let ebu_tt_d = srt.to_ebu_tt_d("en");
+    assert_eq!(ebu_tt_d.lines[0].text, "Italic");
+    assert!(ebu_tt_d.lines[0].italic);
+    assert_eq!(ebu_tt_d.lines[0].background_color, BLACK);
+
+    let vtt = VTT::parse(
+        "WEBVTT\n\nSTYLE\n::cue(.red) {\n  color: red;\n}\n\n00:00:01.000 --> 00:00:02.000 line:0 align:end\n<c.red>Red</c>\n",
+    )
+    .unwrap();
+    let line = &vtt.to_ebu_tt_d("de").lines[0];
+    assert_eq!(line.text, "Red");
+    assert_eq!(line.alignment, Alignment::TopRight);
+    assert_eq!(line.color, Color::new(255, 0, 0, 255));
+
+    let ssa = SSA::parse(
+        r#"[Script Info]
+ScriptType: v4.00+
+
+[V4+ Styles]
+Format: Name, Fontname, Fontsize, PrimaryColour, SecondaryColour, OutlineColour, BackColour, Bold, Italic, Underline, StrikeOut, ScaleX, ScaleY, Spacing, Angle, BorderStyle, Outline, Shadow, Alignment, MarginL, MarginR, MarginV, Encoding
+Style: Box,Arial,20,&H0000FFFF,&H000000FF,&H00000000,&H80000000,0,-1,0,0,100,100,0,0,3,0,0,2,10,10,10,1
+
+[Events]
+Format: Layer, Start, End, Style, Name, MarginL, MarginR, MarginV, Effect, Text
+Dialogue: 0,0:00:01.00,0:00:02.00,Box,,0,0,0,,{\an8}Top\Nline
+Comment: 0,0:00:03.00,0:00:04.00,Box,,0,0,0,,Comment
+"#,
+    )
+    .unwrap();
+    let ebu_tt_d = ssa.to_ebu_tt_d("en");
+    assert_eq!(ebu_tt_d.lines.len(), 1);
+    assert_eq!(
+        ebu_tt_d.lines[0],
+        EBUTTDLine {
+            start: time!(00:00:01),
+            end: time!(00:00:02),
+            text: "Top\nline".to_string(),
+            alignment: Alignment::TopCenter,
+            color: Color::new(255, 255, 0, 255),
+            background_color: Color::new(0, 0, 0, 127),
+            italic: true,
+        }
+    );
+    assert_ne!(ebu_tt_d.lines[0].color, WHITE);
+}
@@ -0,0 +1,104 @@
+use rsubs_lib::{
+    delete_cues, insert_cue, join_cues, split_cue, split_cue_at_line_breaks, SRTLine, SRT, SSA,
+};
+use time::macros::time;
+
+const SRT: &str = "1
+00:00:01,000 --> 00:00:04,000
+Twelve chars
+four
+
+2
+00:00:05,000 --> 00:00:06,000
+Second
+
+3
+00:00:07,000 --> 00:00:08,000
+Third";
+
+fn sequence_numbers(srt: &SRT) -> Vec<u32> {
+    srt.lines.iter().map(|l| l.sequence_number).collect()
+}
+
+#[test]
+fn split_at_time() {
+    let mut srt = SRT::parse(SRT).unwrap();
+
+    assert!(!split_cue(&mut srt, 1, time!(00:00:05)));
+    assert!(split_cue(&mut srt, 1, time!(00:00:05.400)));
+    assert_eq!(srt.lines.len(), 4);
+    assert_eq!(sequence_numbers(&srt), vec![1, 2, 3, 4]);
+    assert_eq!(srt.lines[1].end, time!(00:00:05.400));
+    assert_eq!(srt.lines[2].start, time!(00:00:05.400));
+    assert_eq!(srt.lines[2].text, "Second");
+}
+
+#[test]
+fn split_at_line_breaks() {
+    let mut srt = SRT::parse(SRT).unwrap();
+
+    assert_eq!(split_cue_at_line_breaks(&mut srt, 0), 2);
+    assert_eq!(sequence_numbers(&srt), vec![1, 2, 3, 4]);
+    assert_eq!(srt.lines[0].text, "Twelve chars");
+    assert_eq!(srt.lines[0].end, time!(00:00:03.250));
+    assert_eq!(srt.lines[1].text, "four");
+    assert_eq!(srt.lines[1].start, time!(00:00:03.250));
+    assert_eq!(srt.lines[1].end, time!(00:00:04));
+
+    assert_eq!(split_cue_at_line_breaks(&mut srt, 2), 1);
+}
+
+#[test]
+fn split_ssa_keeps_attributes() {
+    let mut ssa = SSA::parse(
+        r"[Script Info]
+
+[Events]
+Format: Layer,Start,End,Style,Name,MarginL,MarginR,MarginV,Effect,Text
+Dialogue: 3,0:00:00.00,0:00:02.00,Sign,Actor,0,0,0,,ab\Ncd",
+    )
+    .unwrap();
+
+    assert_eq!(split_cue_at_line_breaks(&mut ssa, 0), 2);
+    for event in &ssa.events {
+        assert_eq!(event.layer, 3);
+        assert_eq!(event.style, "Sign");
+        assert_eq!(event.name, "Actor");
+    }
+    assert_eq!(ssa.events[1].start, time!(00:00:01));
+}
+
+#[test]
+fn join() {
+    let mut srt = SRT::parse(SRT).unwrap();
+    join_cues(&mut srt, 1..3);
+
+    assert_eq!(srt.lines.len(), 2);
+    assert_eq!(sequence_numbers(&srt), vec![1, 2]);
+    assert_eq!(srt.lines[1].start, time!(00:00:05));
+    assert_eq!(srt.lines[1].end, time!(00:00:08));
+    assert_eq!(srt.lines[1].text, "Second\r\nThird");
+}
+
+#[test]
+fn insert_and_delete() {
+    let mut srt = SRT::parse(SRT).unwrap();
+    let index = insert_cue(
+        &mut srt,
+        SRTLine {
+            sequence_number: 0,
+            start: time!(00:00:05),
+            end: time!(00:00:05.500),
+            text: "Inserted".to_string(),
+        },
+    );
+
+    assert_eq!(index, 2);
+    assert_eq!(sequence_numbers(&srt), vec![1, 2, 3, 4]);
+    assert_eq!(srt.lines[2].text, "Inserted");
+
+    let deleted = delete_cues(&mut srt, ..2);
+    assert_eq!(deleted.len(), 2);
+    assert_eq!(sequence_numbers(&srt), vec![1, 2]);
+    assert_eq!(srt.lines[0].text, "Inserted");
+}
@@ -0,0 +1,132 @@
+use rsubs_lib::util::FrameRate;
+use rsubs_lib::{StyleGuide, Violation, ViolationKind, SRT, SSA};
+
+const SRT: &str = "1
+00:00:00,000 --> 00:00:00,500
+Too short
+
+2
+00:00:00,520 --> 00:00:02,000
+This text is way too fast to read for anyone at all
+
+3
+00:00:03,000 --> 00:00:06,000
+<b>Bold</b> and <i>italic</i>
+on more lines
+than allowed";
+
+#[test]
+fn netflix_profile() {
+    let srt = SRT::parse(SRT).unwrap();
+    let violations = StyleGuide::netflix().check(&srt);
+
+    assert_eq!(
+        violations,
+        vec![
+            Violation {
+                cue: 0,
+                kind: ViolationKind::MinDuration {
+                    duration_ms: 500,
+                    min_ms: 833
+                }
+            },
+            Violation {
+                cue: 0,
+                kind: ViolationKind::MinGap {
+                    gap_ms: 20,
+                    min_ms: 83
+                }
+            },
+            Violation {
+                cue: 1,
+                kind: ViolationKind::MaxCps {
+                    cps: 51.0 / 1.48,
+                    max: 20.0
+                }
+            },
+            Violation {
+                cue: 1,
+                kind: ViolationKind::MaxCharsPerLine {
+                    line: 0,
+                    length: 51,
+                    max: 42
+                }
+            },
+            Violation {
+                cue: 2,
+                kind: ViolationKind::MaxLines { lines: 3, max: 2 }
+            },
+            Violation {
+                cue: 2,
+                kind: ViolationKind::ForbiddenMarkup("b".to_string())
+            },
+        ]
+    )
+}
+
+#[test]
+fn deserialize_profile() {
+    let guide: StyleGuide = serde_json::from_str(
+        r#"{
+            "name": "client",
+            "max_lines": 1,
+            "min_gap_frames": 3,
+            "frame_rate": { "numerator": 30, "denominator": 1 },
+            "forbidden_characters": ["♪"]
+        }"#,
+    )
+    .unwrap();
+    assert_eq!(guide.name.as_deref(), Some("client"));
+    assert_eq!(guide.frame_rate, FrameRate::FPS_30);
+    assert_eq!(guide.max_cps, None);
+
+    let srt = SRT::parse(
+        "1
+00:00:00,000 --> 00:00:01,000
+♪ la la ♪
+
+2
+00:00:01,050 --> 00:00:02,000
+fine",
+    )
+    .unwrap();
+    let violations = guide.check(&srt);
+    assert_eq!(
+        violations.iter().map(|v| &v.kind).collect::<Vec<_>>(),
+        vec![
+            &ViolationKind::MinGap {
+                gap_ms: 50,
+                min_ms: 100
+            },
+            &ViolationKind::ForbiddenCharacter('♪'),
+        ]
+    )
+}
+
+#[test]
+fn ssa_override_tags() {
+    let ssa = SSA::parse(
+        r"[Script Info]
+
+[Events]
+Format: Layer,Start,End,Style,Name,MarginL,MarginR,MarginV,Effect,Text
+Dialogue: 0,0:00:00.00,0:00:02.00,Default,,0,0,0,,{\i1\pos(10,20)\fscx120}Text{\i0}",
+    )
+    .unwrap();
+    let guide = StyleGuide {
+        allowed_markup: Some(vec!["i".to_string()]),
+        ..Default::default()
+    };
+
+    assert_eq!(
+        guide
+            .check(&ssa)
+            .into_iter()
+            .map(|v| v.kind)
+            .collect::<Vec<_>>(),
+        vec![
+            ViolationKind::ForbiddenMarkup("pos".to_string()),
+            ViolationKind::ForbiddenMarkup("fscx".to_string()),
+        ]
+    )
+}
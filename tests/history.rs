@@ -0,0 +1,107 @@
+use rsubs_lib::{Editor, History, SRTLine, SSAEvent, SRT, SSA};
+use time::macros::time;
+
+const SRT: &str = "1
+00:00:01,000 --> 00:00:02,000
+First
+
+2
+00:00:03,000 --> 00:00:04,000
+Second";
+
+#[test]
+fn undo_redo() {
+    let srt = SRT::parse(SRT).unwrap();
+    let mut editor = Editor::new(srt.clone());
+    assert!(!editor.can_undo());
+
+    editor.set_text(0, "Changed".to_string());
+    editor.set_timing(1, time!(00:00:03.500), time!(00:00:05));
+    editor.insert(
+        1,
+        SRTLine {
+            sequence_number: 0,
+            start: time!(00:00:02.500),
+            end: time!(00:00:03),
+            text: "Inserted".to_string(),
+        },
+    );
+    assert_eq!(editor.subtitle().lines[1].sequence_number, 2);
+    assert_eq!(editor.subtitle().lines[2].sequence_number, 3);
+    assert_eq!(editor.delete(0).text, "Changed");
+    assert_eq!(editor.history().undo_stack().len(), 4);
+
+    while editor.undo() {}
+    assert_eq!(editor.subtitle(), &srt);
+    assert!(editor.can_redo());
+
+    assert!(editor.redo());
+    assert!(editor.redo());
+    assert_eq!(editor.subtitle().lines[0].text, "Changed");
+    assert_eq!(editor.subtitle().lines[1].start, time!(00:00:03.500));
+
+    // a new edit discards everything that could be redone
+    editor.set_text(1, "New".to_string());
+    assert!(!editor.can_redo());
+}
+
+#[test]
+fn groups() {
+    let srt = SRT::parse(SRT).unwrap();
+    let mut editor = Editor::new(srt.clone());
+
+    editor.begin_group();
+    editor.set_text(0, "F".to_string());
+    editor.begin_group();
+    editor.set_text(0, "Fi".to_string());
+    editor.end_group();
+    editor.set_text(0, "Fir".to_string());
+    editor.end_group();
+    editor.set_text(1, "S".to_string());
+
+    assert_eq!(editor.history().undo_stack().len(), 2);
+    assert!(editor.undo());
+    assert_eq!(editor.subtitle().lines[1].text, "Second");
+    assert_eq!(editor.subtitle().lines[0].text, "Fir");
+    assert!(editor.undo());
+    assert_eq!(editor.subtitle(), &srt);
+    assert!(!editor.undo());
+
+    // empty groups aren't recorded
+    editor.begin_group();
+    editor.end_group();
+    assert!(!editor.can_undo());
+}
+
+#[test]
+fn style() {
+    let mut ssa = SSA::default();
+    ssa.events.push(SSAEvent::default());
+    let mut editor = Editor::new(ssa);
+
+    assert!(editor.set_style(0, "Sign".to_string()));
+    assert_eq!(editor.subtitle().events[0].style, "Sign");
+    editor.undo();
+    assert_eq!(editor.subtitle().events[0].style, "Default");
+
+    let mut editor = Editor::new(SRT::parse(SRT).unwrap());
+    assert!(!editor.set_style(0, "Sign".to_string()));
+    assert!(!editor.can_undo());
+}
+
+#[test]
+fn serialize() {
+    let srt = SRT::parse(SRT).unwrap();
+    let mut editor = Editor::new(srt);
+    editor.set_text(0, "Changed".to_string());
+    editor.delete(1);
+
+    let (srt, history) = editor.into_parts();
+    let json = serde_json::to_string(&history).unwrap();
+    let history: History<SRTLine> = serde_json::from_str(&json).unwrap();
+
+    let mut editor = Editor::with_history(srt, history);
+    assert!(editor.undo());
+    assert!(editor.undo());
+    assert_eq!(editor.subtitle(), &SRT::parse(SRT).unwrap());
+}
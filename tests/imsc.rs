@@ -0,0 +1,231 @@
+use rsubs_lib::util::Color;
+use rsubs_lib::{
+    IMSCLocation, IMSCViolation, IMSCViolationKind, IMSC1_1_TEXT_PROFILE, SRT, SSA, TTML, VTT,
+};
+use time::macros::time;
+
+const SSA_DOCUMENT: &str = r#"[Script Info]
+ScriptType: v4.00+
+PlayResX: 1920
+PlayResY: 1080
+
+[V4+ Styles]
+Format: Name, Fontname, Fontsize, PrimaryColour, SecondaryColour, OutlineColour, BackColour, Bold, Italic, Underline, StrikeOut, ScaleX, ScaleY, Spacing, Angle, BorderStyle, Outline, Shadow, Alignment, MarginL, MarginR, MarginV, Encoding
+Style: Default,Arial,72,&H00FFFFFF,&H000000FF,&H00000000,&H00000000,0,0,0,0,100,100,0,0,1,3,0,2,192,192,54,1
+Style: Sign Box,Arial,54,&H0000FFFF,&H000000FF,&H00000000,&H80000000,-1,0,0,0,100,100,0,0,3,0,0,8,0,0,108,1
+
+[Events]
+Format: Layer, Start, End, Style, Name, MarginL, MarginR, MarginV, Effect, Text
+Dialogue: 0,0:00:01.00,0:00:03.00,Default,,0,0,0,,Hello {\i1}world{\i0}\Nsecond & line
+Dialogue: 0,0:00:02.00,0:00:04.00,Sign Box,,0,0,0,,Sign
+Dialogue: 0,0:00:05.00,0:00:06.00,Default,,0,0,0,,{\an7}Top {\c&H0000FF&}red
+Dialogue: 0,0:00:07.00,0:00:08.00,Default,,0,0,0,,{\pos(960,540)}Positioned
+Comment: 0,0:00:09.00,0:00:10.00,Default,,0,0,0,,Comment
+"#;
+
+const VTT_DOCUMENT: &str = r#"WEBVTT
+
+STYLE
+::cue {
+  color: yellow;
+  background-color: #000000cc;
+}
+
+STYLE
+::cue(.loud) {
+  font-weight: bold;
+}
+
+REGION
+id:speaker
+width:40%
+lines:3
+regionanchor:0%,100%
+viewportanchor:10%,90%
+
+1
+00:00:01.000 --> 00:00:02.000 region:speaker
+<v Bob>Hi <i>there</i></v>
+
+2
+00:00:03.000 --> 00:00:04.000 line:0 align:start
+<c.loud>Top</c> &amp; left
+
+00:00:05.000 --> 00:00:06.000
+Bottom
+text
+"#;
+
+#[test]
+fn convert_from_ssa() {
+    let ttml = SSA::parse(SSA_DOCUMENT).unwrap().to_imsc("en");
+
+    assert_eq!(ttml.lang.as_deref(), Some("en"));
+    assert_eq!(
+        ttml.attributes,
+        vec![(
+            "ttp:contentProfiles".to_string(),
+            IMSC1_1_TEXT_PROFILE.to_string()
+        )]
+    );
+
+    let default = &ttml.styles[0];
+    assert_eq!(default.id, "Default");
+    let style = ttml.resolve_style(std::slice::from_ref(&default.id), &[]);
+    assert_eq!(style["tts:fontSize"], "100%");
+    assert_eq!(style["tts:color"], "#FFFFFF");
+    assert_eq!(style["tts:textOutline"], "#000000 4.17%");
+    assert_eq!(style["tts:textAlign"], "center");
+    let sign = &ttml.styles[1];
+    assert_eq!(sign.id, "Sign_Box");
+    let style = ttml.resolve_style(std::slice::from_ref(&sign.id), &[]);
+    assert_eq!(style["tts:color"], "#FFFF00");
+    assert_eq!(style["tts:backgroundColor"], "#0000007F");
+    assert_eq!(style["tts:fontWeight"], "bold");
+
+    // the comment is skipped
+    assert_eq!(ttml.paragraphs.len(), 4);
+    let first = &ttml.paragraphs[0];
+    assert_eq!(first.begin, time!(00:00:01));
+    assert_eq!(
+        first.text,
+        r#"Hello <span tts:fontStyle="italic">world</span><br/>second &amp; line"#
+    );
+    let region = ttml.paragraph_style(first);
+    assert_eq!(region["tts:origin"], "10% 75%");
+    assert_eq!(region["tts:extent"], "80% 20%");
+    assert_eq!(region["tts:displayAlign"], "after");
+
+    let sign = ttml.paragraph_style(&ttml.paragraphs[1]);
+    assert_eq!(sign["tts:origin"], "0% 10%");
+    assert_eq!(sign["tts:displayAlign"], "before");
+
+    let top = &ttml.paragraphs[2];
+    assert_eq!(top.text, r##"Top <span tts:color="#FF0000">red</span>"##);
+    assert_eq!(ttml.paragraph_style(top)["tts:textAlign"], "left");
+
+    let positioned = ttml.paragraph_style(&ttml.paragraphs[3]);
+    assert_eq!(positioned["tts:origin"], "0% 30%");
+    assert_eq!(positioned["tts:extent"], "100% 20%");
+
+    assert_eq!(ttml.validate_imsc(), vec![]);
+    assert_eq!(TTML::parse(ttml.to_string()).unwrap(), ttml);
+}
+
+#[test]
+fn convert_from_vtt() {
+    let ttml = VTT::parse(VTT_DOCUMENT).unwrap().to_imsc("de");
+
+    assert_eq!(ttml.styles[0].id, "default");
+    assert_eq!(
+        ttml.styles[0].attributes,
+        vec![
+            (
+                "tts:backgroundColor".to_string(),
+                Color::new(0, 0, 0, 204).to_ttml_string()
+            ),
+            ("tts:color".to_string(), "#FFFF00".to_string()),
+        ]
+    );
+    assert_eq!(ttml.styles[1].id, "loud");
+
+    let speaker = ttml.paragraph_style(&ttml.paragraphs[0]);
+    assert_eq!(ttml.paragraphs[0].region.as_deref(), Some("speaker"));
+    assert_eq!(speaker["tts:origin"], "10% 74.01%");
+    assert_eq!(speaker["tts:extent"], "40% 15.99%");
+    assert_eq!(
+        ttml.paragraphs[0].text,
+        r#"Hi <span tts:fontStyle="italic">there</span>"#
+    );
+
+    let top = &ttml.paragraphs[1];
+    assert_eq!(top.id.as_deref(), Some("_2"));
+    assert_eq!(top.text, r#"<span style="loud">Top</span> &amp; left"#);
+    let style = ttml.paragraph_style(top);
+    assert_eq!(style["tts:origin"], "0% 0%");
+    assert_eq!(style["tts:displayAlign"], "before");
+    assert_eq!(style["tts:textAlign"], "start");
+
+    let bottom = &ttml.paragraphs[2];
+    assert_eq!(bottom.text, "Bottom<br/>text");
+    assert_eq!(ttml.paragraph_style(bottom)["tts:origin"], "0% 75%");
+
+    assert_eq!(ttml.validate_imsc(), vec![]);
+}
+
+#[test]
+fn convert_from_srt() {
+    let srt = SRT::parse("1\n00:00:01,000 --> 00:00:02,000\n<b>Bold</b>\n").unwrap();
+    let ttml = srt.to_imsc("en");
+
+    assert_eq!(
+        ttml.paragraphs[0].text,
+        r#"<span tts:fontWeight="bold">Bold</span>"#
+    );
+    assert_eq!(ttml.validate_imsc(), vec![]);
+}
+
+#[test]
+fn validate() {
+    let ttml = TTML::parse(
+        r#"<tt xmlns="http://www.w3.org/ns/ttml" xmlns:tts="http://www.w3.org/ns/ttml#styling"
+    xmlns:ttp="http://www.w3.org/ns/ttml#parameter" ttp:timeBase="smpte">
+  <head>
+    <styling>
+      <style xml:id="s1" tts:fontSize="1em" style="missing"/>
+    </styling>
+    <layout>
+      <region xml:id="r1" tts:origin="0% 50%" tts:extent="100% 60%"/>
+      <region xml:id="r2" tts:origin="0% 0%" tts:extent="100% 55%"/>
+    </layout>
+  </head>
+  <body>
+    <p begin="1s" end="3s" region="r1">One</p>
+    <p begin="2s" end="4s" region="r2"><span tts:textOutline="black 5% 2%">Two</span></p>
+  </body>
+</tt>"#,
+    )
+    .unwrap();
+
+    let violation = |location, kind| IMSCViolation { location, kind };
+    assert_eq!(
+        ttml.validate_imsc(),
+        vec![
+            violation(IMSCLocation::Document, IMSCViolationKind::MissingProfile),
+            violation(IMSCLocation::Document, IMSCViolationKind::MissingLanguage),
+            violation(
+                IMSCLocation::Document,
+                IMSCViolationKind::ProhibitedAttribute {
+                    attribute: "ttp:timeBase".to_string(),
+                    value: "smpte".to_string()
+                }
+            ),
+            violation(
+                IMSCLocation::Style("s1".to_string()),
+                IMSCViolationKind::InvalidLength {
+                    attribute: "tts:fontSize".to_string(),
+                    value: "1em".to_string()
+                }
+            ),
+            violation(
+                IMSCLocation::Style("s1".to_string()),
+                IMSCViolationKind::UnknownReference("missing".to_string())
+            ),
+            violation(
+                IMSCLocation::Region("r1".to_string()),
+                IMSCViolationKind::RegionOutsideRootContainer
+            ),
+            violation(
+                IMSCLocation::Paragraph(1),
+                IMSCViolationKind::ProhibitedAttribute {
+                    attribute: "tts:textOutline".to_string(),
+                    value: "black 5% 2%".to_string()
+                }
+            ),
+            violation(
+                IMSCLocation::Paragraph(1),
+                IMSCViolationKind::OverlappingRegions("r2".to_string(), "r1".to_string())
+            ),
+        ]
+    );
+}
@@ -0,0 +1,87 @@
+use rsubs_lib::util::FrameRate;
+use rsubs_lib::{snap_to_keyframes, Keyframes, KeyframesErrorKind, TimingChangeReason, SRT, VTT};
+use time::macros::time;
+
+#[test]
+fn parse_aegisub() {
+    let keyframes = Keyframes::parse("# keyframe format v1\nfps 0\n0\n48\n24\n").unwrap();
+    assert_eq!(keyframes.frames, vec![0, 24, 48]);
+}
+
+#[test]
+fn parse_xvid() {
+    let keyframes = Keyframes::parse(
+        "# XviD 2pass stat file (core version 1.1.2)
+# Please do not modify this file
+
+i 1 0 0 0 0 0 0
+p 1 0 0 0 0 0 0
+b 1 0 0 0 0 0 0
+i 1 0 0 0 0 0 0
+p 1 0 0 0 0 0 0",
+    )
+    .unwrap();
+    assert_eq!(keyframes.frames, vec![0, 3]);
+}
+
+#[test]
+fn parse_frame_list() {
+    let keyframes = Keyframes::parse("10\n\n# comment\n5\n").unwrap();
+    assert_eq!(keyframes.frames, vec![5, 10]);
+
+    let err = Keyframes::parse("10\nabc").unwrap_err();
+    assert_eq!(err.line(), 2);
+    assert!(matches!(err.kind(), KeyframesErrorKind::Parse(_)))
+}
+
+#[test]
+fn nearest() {
+    let keyframes = Keyframes {
+        frames: vec![10, 20, 30],
+    };
+    assert_eq!(keyframes.nearest(14, 5), Some(10));
+    assert_eq!(keyframes.nearest(16, 5), Some(20));
+    assert_eq!(keyframes.nearest(25, 3), None);
+    assert_eq!(keyframes.nearest(0, 10), Some(10));
+}
+
+#[test]
+fn snap() {
+    let mut srt = SRT::parse(
+        "1
+00:00:01,100 --> 00:00:02,900
+Near keyframes
+
+2
+00:00:05,000 --> 00:00:06,000
+Far away",
+    )
+    .unwrap();
+    let keyframes = Keyframes {
+        frames: vec![25, 75],
+    };
+    let changes = snap_to_keyframes(&mut srt, &keyframes, FrameRate::FPS_25, 3);
+
+    assert_eq!(changes.len(), 1);
+    assert_eq!(changes[0].cue, 0);
+    assert_eq!(changes[0].reason, TimingChangeReason::Keyframe);
+    assert_eq!(srt.lines[0].start, time!(00:00:00.980));
+    assert_eq!(srt.lines[0].end, time!(00:00:02.980));
+    assert_eq!(srt.lines[1].start, time!(00:00:05.000));
+}
+
+#[test]
+fn snap_does_not_empty_cues() {
+    let mut vtt = VTT::parse(
+        "WEBVTT
+
+00:01.000 --> 00:01.080
+Short",
+    )
+    .unwrap();
+    let keyframes = Keyframes { frames: vec![25] };
+    snap_to_keyframes(&mut vtt, &keyframes, FrameRate::FPS_25, 3);
+
+    assert_eq!(vtt.lines[0].start, time!(00:00:00.980));
+    assert_eq!(vtt.lines[0].end, time!(00:00:01.080));
+}
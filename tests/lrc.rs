@@ -0,0 +1,128 @@
+use rsubs_lib::{Cue, LRCErrorKind, LRCLine, LRC};
+use time::macros::time;
+
+const LRC_FILE: &str = "[ar:Artist]
+[ti:Title]
+[la:en]
+[offset:+500]
+[00:12.50]First line
+[00:15.50][00:30.50]<00:15.50>Re<00:16.00>peated <00:17.00>line<00:18.50>
+[00:20.50]
+[00:30.50]Second voice
+";
+
+#[test]
+fn parse() {
+    let lrc = LRC::parse(LRC_FILE).unwrap();
+
+    assert_eq!(lrc.metadata.artist.as_deref(), Some("Artist"));
+    assert_eq!(lrc.metadata.title.as_deref(), Some("Title"));
+    assert_eq!(
+        lrc.metadata.additional_fields,
+        vec![("la".to_string(), "en".to_string())]
+    );
+
+    // the offset is applied to all times, including the word tags
+    assert_eq!(
+        lrc.lines,
+        vec![
+            LRCLine {
+                start: time!(00:00:12),
+                end: time!(00:00:15),
+                text: "First line".to_string(),
+            },
+            LRCLine {
+                start: time!(00:00:15),
+                end: time!(00:00:20),
+                text: "<00:15.00>Re<00:15.50>peated <00:16.50>line<00:18.00>".to_string(),
+            },
+            LRCLine {
+                start: time!(00:00:30),
+                end: time!(00:00:35),
+                text: "<00:15.00>Re<00:15.50>peated <00:16.50>line<00:18.00>\nSecond voice"
+                    .to_string(),
+            },
+        ]
+    );
+    assert_eq!(lrc.lines[1].plain_text(), "Repeated line");
+    assert_eq!(
+        lrc.lines[1].inline_timestamps(),
+        vec![
+            (0, time!(00:00:15)),
+            (12, time!(00:00:15.500)),
+            (29, time!(00:00:16.500)),
+            (43, time!(00:00:18)),
+        ]
+    );
+}
+
+#[test]
+fn parse_error() {
+    let err = LRC::parse("[ar:Artist]\n[offset:abc]\n[00:01.00]Text").unwrap_err();
+    assert_eq!(err.line(), 2);
+    assert_eq!(
+        err.kind(),
+        &LRCErrorKind::Parse("invalid offset 'abc'".to_string())
+    );
+}
+
+#[test]
+fn parse_word_tags() {
+    // tags are kept as they are without offset
+    let lrc = LRC::parse("[00:01.00]<0:1.5>Word <00:02.345>tags").unwrap();
+    assert_eq!(lrc.lines[0].text, "<0:1.5>Word <00:02.345>tags");
+
+    // and keep their milliseconds with an offset
+    let lrc = LRC::parse("[offset:-5]\n[00:01.00]<00:01.00>Word <00:02.345>tags").unwrap();
+    assert_eq!(lrc.lines[0].start, time!(00:00:01.005));
+    assert_eq!(lrc.lines[0].text, "<00:01.01>Word <00:02.350>tags");
+
+    // timestamps which overflow aren't timestamps
+    let lrc = LRC::parse("[9999999999999999:00.00]Text\n[offset:-9223372036854775808]").unwrap();
+    assert!(lrc.lines.is_empty());
+    let lrc = LRC::parse("[offset:-9223372036854775808]\n[00:01.00]<00:02.00>Text").unwrap();
+    assert_eq!(lrc.lines[0].start, time!(23:59:59.999));
+}
+
+#[test]
+fn display() {
+    let lrc = LRC::parse(LRC_FILE).unwrap();
+    let written = lrc.to_string();
+
+    assert_eq!(
+        written,
+        "[ar:Artist]
+[ti:Title]
+[la:en]
+[00:12.00]First line
+[00:15.00]<00:15.00>Re<00:15.50>peated <00:16.50>line<00:18.00>
+[00:20.00]
+[00:30.00]<00:15.00>Re<00:15.50>peated <00:16.50>line<00:18.00>
+[00:30.00]Second voice
+[00:35.00]"
+    );
+    assert_eq!(LRC::parse(&written).unwrap(), lrc);
+}
+
+#[test]
+fn convert() {
+    let lrc = LRC::parse(
+        "[00:01.00]Plain line\n[00:03.00]Intro <00:03.50>sing <00:04.00>along<00:04.80>\n[00:06.00]",
+    )
+    .unwrap();
+
+    let ssa = lrc.to_ssa();
+    assert_eq!(ssa.events[0].text, "Plain line");
+    assert_eq!(ssa.events[1].text, "{\\k50}Intro {\\k50}sing {\\k80}along");
+    assert_eq!(ssa.events[1].end, time!(00:00:06));
+
+    let vtt = lrc.to_vtt();
+    assert_eq!(vtt.lines[0].text, "Plain line");
+    assert_eq!(
+        vtt.lines[1].text,
+        "Intro <00:00:03.500>sing <00:00:04.000>along<00:00:04.800>"
+    );
+
+    let srt = lrc.to_srt();
+    assert_eq!(srt.lines[1].text, "Intro sing along");
+}
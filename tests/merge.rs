@@ -0,0 +1,159 @@
+use rsubs_lib::{SSAStyle, SRT, SSA, VTT};
+use time::macros::time;
+
+const SRT: &str = "1
+00:00:01,000 --> 00:00:03,000
+First
+
+2
+00:00:04,000 --> 00:00:06,000
+Second";
+
+#[test]
+fn merge_srt() {
+    let srt = SRT::parse(SRT).unwrap();
+    let merged = SRT::merge([(srt.clone(), 0), (srt, 60_000)]);
+
+    assert_eq!(merged.lines.len(), 4);
+    assert_eq!(
+        merged
+            .lines
+            .iter()
+            .map(|l| l.sequence_number)
+            .collect::<Vec<u32>>(),
+        vec![1, 2, 3, 4]
+    );
+    assert_eq!(merged.lines[2].start, time!(00:01:01));
+    assert_eq!(merged.lines[3].end, time!(00:01:06));
+}
+
+#[test]
+fn merge_empty() {
+    assert!(SRT::merge([]).lines.is_empty());
+}
+
+#[test]
+fn merge_vtt_regions() {
+    let first = VTT::parse(
+        "WEBVTT
+
+REGION
+id:fred
+width:40%
+
+00:01.000 --> 00:03.000 region:fred
+First",
+    )
+    .unwrap();
+    let second = VTT::parse(
+        "WEBVTT
+
+REGION
+id:fred
+width:60%
+
+00:01.000 --> 00:03.000 region:fred
+Second",
+    )
+    .unwrap();
+
+    let mut merged = first.clone();
+    merged.append(first, 10_000);
+    merged.append(second, 20_000);
+
+    assert_eq!(merged.regions.len(), 2);
+    assert_eq!(merged.regions[1].id.as_deref(), Some("fred_2"));
+    assert_eq!(merged.lines.len(), 3);
+    assert_eq!(merged.lines[1].start, time!(00:00:11));
+    assert_eq!(
+        merged.lines[2].settings.get("region"),
+        Some(&Some("fred_2".to_string()))
+    );
+}
+
+#[test]
+fn merge_vtt_identifiers() {
+    let srt = SRT::parse(SRT).unwrap().to_vtt();
+    let named = VTT::parse(
+        "WEBVTT
+
+intro
+00:01.000 --> 00:02.000
+Intro",
+    )
+    .unwrap();
+
+    let mut merged = VTT::merge([(srt.clone(), 0), (srt, 60_000), (named.clone(), 120_000)]);
+    merged.append(named, 180_000);
+
+    assert_eq!(
+        merged
+            .lines
+            .iter()
+            .map(|l| l.identifier.as_deref())
+            .collect::<Vec<_>>(),
+        vec![
+            Some("1"),
+            Some("2"),
+            Some("3"),
+            Some("4"),
+            Some("intro"),
+            Some("intro_2")
+        ]
+    );
+}
+
+#[test]
+fn merge_ssa_styles() {
+    let mut first = SRT::parse(SRT).unwrap().to_ssa();
+    first.styles = vec![SSAStyle::default()];
+    first.info.play_res_x = Some(1920);
+    first.info.play_res_y = Some(1080);
+
+    let mut second = first.clone();
+    second.styles[0].fontname = "Arial".to_string();
+    second.styles.push(SSAStyle {
+        name: "Default_2".to_string(),
+        ..Default::default()
+    });
+    second.events[0].style = "Default_2".to_string();
+    second.events[1].text = r"{\rDefault}Second".to_string();
+
+    let merged = SSA::merge([(first.clone(), 0), (first, 10_000), (second, 20_000)]);
+
+    assert_eq!(merged.events.len(), 6);
+    assert_eq!(
+        merged
+            .styles
+            .iter()
+            .map(|s| s.name.as_str())
+            .collect::<Vec<&str>>(),
+        vec!["Default", "Default_3", "Default_2"]
+    );
+    assert_eq!(merged.events[4].style, "Default_2");
+    assert_eq!(merged.events[5].style, "Default_3");
+    assert_eq!(merged.events[5].text, r"{\rDefault_3}Second");
+}
+
+#[test]
+fn merge_ssa_play_res() {
+    let mut first = SSA::default();
+    first.info.play_res_x = Some(1920);
+    first.info.play_res_y = Some(1080);
+    let mut second = SRT::parse(SRT).unwrap().to_ssa();
+    second.styles = vec![SSAStyle {
+        fontsize: 20.0,
+        margin_v: 10.0,
+        ..Default::default()
+    }];
+    second.info.play_res_x = Some(640);
+    second.info.play_res_y = Some(360);
+    second.events[0].margin_l = 5.0;
+
+    first.append(second, 0);
+
+    assert_eq!(first.info.play_res_y, Some(1080));
+    assert_eq!(first.styles[0].fontsize, 60.0);
+    assert_eq!(first.styles[0].margin_v, 30.0);
+    assert_eq!(first.events[0].margin_l, 15.0);
+}
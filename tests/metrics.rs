@@ -0,0 +1,101 @@
+use rsubs_lib::{CueMetrics, Distribution, SubtitleMetrics, SRT, SSA, VTT};
+use time::Duration;
+
+const SRT: &str = "1
+00:00:00,000 --> 00:00:02,000
+<i>Hello</i> world
+
+2
+00:00:02,500 --> 00:00:04,500
+This line is longer
+than this one
+
+3
+00:00:04,000 --> 00:00:05,000
+日本語の字幕";
+
+#[test]
+fn srt_cue_metrics() {
+    let srt = SRT::parse(SRT).unwrap();
+    let metrics = SubtitleMetrics::new(&srt);
+
+    assert_eq!(
+        metrics.cues[0],
+        CueMetrics {
+            duration: Duration::seconds(2),
+            characters: 11,
+            words: 2,
+            line_count: 1,
+            max_line_length: 11,
+            cps: 5.5,
+            wpm: 60.0,
+            gap_to_next: Some(Duration::milliseconds(500)),
+        }
+    );
+    assert_eq!(metrics.cues[1].line_count, 2);
+    assert_eq!(metrics.cues[1].max_line_length, 19);
+    assert_eq!(metrics.cues[1].characters, 32);
+    assert_eq!(
+        metrics.cues[1].gap_to_next,
+        Some(Duration::milliseconds(-500))
+    );
+    assert_eq!(metrics.cues[2].gap_to_next, None);
+}
+
+#[test]
+fn cjk_metrics() {
+    let srt = SRT::parse(SRT).unwrap();
+    let metrics = SubtitleMetrics::new(&srt);
+
+    assert_eq!(metrics.cues[2].characters, 6);
+    assert_eq!(metrics.cues[2].words, 6);
+    assert_eq!(metrics.cues[2].cps, 6.0);
+}
+
+#[test]
+fn ssa_strips_tags_and_line_breaks() {
+    let ssa = SSA::parse(
+        r"[Script Info]
+
+[Events]
+Format: Layer,Start,End,Style,Name,MarginL,MarginR,MarginV,Effect,Text
+Dialogue: 0,0:00:00.00,0:00:02.00,Default,,0,0,0,,{\i1}First{\i0} line\NSecond line",
+    )
+    .unwrap();
+    let metrics = SubtitleMetrics::new(&ssa);
+
+    assert_eq!(metrics.cues[0].line_count, 2);
+    assert_eq!(metrics.cues[0].max_line_length, 11);
+    assert_eq!(metrics.cues[0].characters, 21);
+}
+
+#[test]
+fn vtt_strips_tags_and_entities() {
+    let vtt = VTT::parse(
+        "WEBVTT
+
+00:00.000 --> 00:01.000
+<v Roger>Fish &amp; chips",
+    )
+    .unwrap();
+    let metrics = SubtitleMetrics::new(&vtt);
+
+    assert_eq!(metrics.cues[0].characters, 12);
+    assert_eq!(metrics.cues[0].words, 3);
+}
+
+#[test]
+fn distribution() {
+    let distribution = Distribution::new([4.0, 1.0, 3.0, 2.0, 5.0]);
+    assert_eq!(
+        distribution,
+        Distribution {
+            min: 1.0,
+            max: 5.0,
+            mean: 3.0,
+            median: 3.0,
+            p90: 5.0,
+        }
+    );
+    assert_eq!(Distribution::new([]), Distribution::default());
+}
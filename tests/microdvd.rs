@@ -0,0 +1,131 @@
+use rsubs_lib::util::FrameRate;
+use rsubs_lib::{MicroDVD, MicroDVDErrorKind, SRT};
+use time::macros::time;
+
+const SUB: &str = "{1}{1}25
+{25}{75}Hello|world
+{100}{150}{y:i}Italic line|normal line
+{175}{200}{Y:b}{c:$0000FF}All bold|still bold";
+
+#[test]
+fn parse() {
+    let sub = MicroDVD::parse(SUB, None).unwrap();
+
+    assert_eq!(sub.frame_rate, FrameRate::FPS_25);
+    assert_eq!(sub.lines.len(), 3);
+    assert_eq!(sub.lines[0].start, time!(00:00:01));
+    assert_eq!(sub.lines[0].end, time!(00:00:03));
+    assert_eq!(sub.lines[1].text, "{y:i}Italic line|normal line");
+
+    let sub = MicroDVD::parse(SUB, Some(FrameRate::FPS_50)).unwrap();
+    assert_eq!(sub.lines[0].start, time!(00:00:00.500));
+}
+
+#[test]
+fn parse_ntsc_header() {
+    let sub = MicroDVD::parse("{1}{1}23.976\r\n{0}{24}Text", None).unwrap();
+    assert_eq!(sub.frame_rate, FrameRate::FPS_23_976);
+    assert_eq!(sub.lines[0].end, time!(00:00:01.001));
+}
+
+#[test]
+fn parse_error() {
+    let err = MicroDVD::parse("{0}{24}Text", None).unwrap_err();
+    assert_eq!(err.line(), 1);
+    assert_eq!(
+        err.kind(),
+        &MicroDVDErrorKind::Parse("missing frame rate".to_string())
+    );
+
+    let err = MicroDVD::parse("{1}{1}25\n{a}{24}Text", None).unwrap_err();
+    assert_eq!(err.line(), 2);
+}
+
+#[test]
+fn parse_invalid_header() {
+    for header in ["0", "-25", "NaN", "inf", "1e12"] {
+        let content = format!("{{1}}{{1}}{header}\n{{0}}{{24}}Text");
+        let err = MicroDVD::parse(&content, None).unwrap_err();
+        assert_eq!(err.line(), 1);
+        assert_eq!(
+            err.kind(),
+            &MicroDVDErrorKind::Parse(format!("invalid frame rate '{header}'"))
+        );
+
+        let sub = MicroDVD::parse(&content, Some(FrameRate::FPS_24)).unwrap();
+        assert_eq!(sub.frame_rate, FrameRate::FPS_24);
+        assert_eq!(sub.lines.len(), 1);
+    }
+}
+
+#[test]
+fn display() {
+    let sub = MicroDVD::parse(SUB, None).unwrap();
+    assert_eq!(sub.to_string(), SUB.replace('\n', "\r\n"));
+
+    let sub = MicroDVD::parse("{1}{1}23.976\r\n{0}{24}Text", None).unwrap();
+    assert_eq!(sub.to_string(), "{1}{1}23.976\r\n{0}{24}Text");
+}
+
+#[test]
+fn convert_to_srt() {
+    let srt = MicroDVD::parse(SUB, None).unwrap().to_srt();
+
+    assert_eq!(srt.lines[0].text, "Hello\r\nworld");
+    assert_eq!(srt.lines[1].text, "<i>Italic line</i>\r\nnormal line");
+    assert_eq!(
+        srt.lines[2].text,
+        "<font color=\"#FF0000\"><b>All bold</b></font>\r\n<b>still bold</b>"
+    );
+
+    // invalid colors are ignored, even if they contain multibyte characters
+    let srt = MicroDVD::parse("{1}{1}25\n{0}{25}{c:$aé€}Text", None)
+        .unwrap()
+        .to_srt();
+    assert_eq!(srt.lines[0].text, "Text");
+}
+
+#[test]
+fn convert_to_vtt() {
+    let vtt = MicroDVD::parse(SUB, None).unwrap().to_vtt();
+    assert_eq!(vtt.lines[1].text, "<i>Italic line</i>\nnormal line");
+    assert_eq!(vtt.lines[2].text, "<b>All bold</b>\n<b>still bold</b>");
+}
+
+#[test]
+fn convert_to_ssa() {
+    let ssa = MicroDVD::parse(SUB, None).unwrap().to_ssa();
+
+    assert_eq!(ssa.events[0].text, "Hello\\Nworld");
+    assert_eq!(ssa.events[1].text, "{\\i1}Italic line\\N{\\r}normal line");
+    assert_eq!(
+        ssa.events[2].text,
+        "{\\b1\\c&H0000FF&}All bold\\N{\\r\\b1}still bold"
+    );
+}
+
+#[test]
+fn convert_from_srt() {
+    let srt = SRT::parse(
+        "1
+00:00:01,000 --> 00:00:02,000
+<i>Italic
+still italic</i> normal
+
+2
+00:00:03,000 --> 00:00:04,000
+<font color=\"red\">Red</font> {\\an8}text",
+    )
+    .unwrap();
+    let sub = srt.to_microdvd(FrameRate::FPS_25);
+
+    assert_eq!(sub.lines[0].text, "{y:i}Italic|{y:i}still italic normal");
+    assert_eq!(sub.lines[1].text, "{c:$0000FF}Red text");
+    assert_eq!(
+        sub.to_string(),
+        "{1}{1}25\r\n{25}{50}{y:i}Italic|{y:i}still italic normal\r\n{75}{100}{c:$0000FF}Red text"
+    );
+
+    let ssa = MicroDVD::parse(SUB, None).unwrap().to_ssa();
+    assert_eq!(ssa.to_microdvd(FrameRate::FPS_25).lines.len(), 3);
+}
@@ -0,0 +1,131 @@
+use rsubs_lib::{
+    find_overlaps, resolve_overlaps, Overlap, OverlapStrategy, StackMode, SRT, SSA, VTT,
+};
+use time::macros::time;
+
+const SRT: &str = "1
+00:00:27,000 --> 00:00:30,000
+And I want to do a follow-up on the last conversation we did.
+
+2
+00:00:30,000 --> 00:00:31,500
+When we e-mailed—
+
+3
+00:00:30,500 --> 00:00:32,500
+Didn’t we talk about enough in that conversation?
+
+4
+00:00:32,000 --> 00:00:35,500
+No! No no no no; 'cos 'cos obviously 'cos
+
+5
+00:00:32,500 --> 00:00:33,500
+<i>Laughs</i>
+
+6
+00:00:35,500 --> 00:00:38,000
+You know I’m so excited my glasses are falling off here.";
+
+#[test]
+fn find() {
+    let srt = SRT::parse(SRT).unwrap();
+    assert_eq!(
+        find_overlaps(&srt),
+        vec![
+            Overlap {
+                first: 1,
+                second: 2
+            },
+            Overlap {
+                first: 2,
+                second: 3
+            },
+            Overlap {
+                first: 3,
+                second: 4
+            },
+        ]
+    )
+}
+
+#[test]
+fn truncate() {
+    let mut srt = SRT::parse(SRT).unwrap();
+    let overlaps = resolve_overlaps(&mut srt, OverlapStrategy::Truncate);
+
+    assert_eq!(overlaps.len(), 3);
+    assert!(find_overlaps(&srt).is_empty());
+    assert_eq!(srt.lines.len(), 6);
+    assert_eq!(srt.lines[1].end, time!(00:00:30.500));
+    assert_eq!(srt.lines[2].end, time!(00:00:32.000));
+    assert_eq!(srt.lines[3].end, time!(00:00:32.500));
+    assert_eq!(srt.lines[4].end, time!(00:00:33.500));
+}
+
+#[test]
+fn truncate_same_start() {
+    let mut vtt = VTT::parse(
+        "WEBVTT
+
+00:01.000 --> 00:03.000
+First
+
+00:01.000 --> 00:02.000
+Second",
+    )
+    .unwrap();
+    resolve_overlaps(&mut vtt, OverlapStrategy::Truncate);
+
+    assert_eq!(vtt.lines.len(), 1);
+    assert_eq!(vtt.lines[0].end, time!(00:00:03.000));
+    assert_eq!(vtt.lines[0].text, "- First\n- Second");
+}
+
+#[test]
+fn merge() {
+    let mut srt = SRT::parse(SRT).unwrap();
+    resolve_overlaps(&mut srt, OverlapStrategy::Merge);
+
+    assert_eq!(srt.lines.len(), 3);
+    assert_eq!(srt.lines[1].sequence_number, 2);
+    assert_eq!(srt.lines[1].start, time!(00:00:30.000));
+    assert_eq!(srt.lines[1].end, time!(00:00:35.500));
+    assert_eq!(
+        srt.lines[1].text,
+        "- When we e-mailed—\r\n- Didn’t we talk about enough in that conversation?\r\n- No! No no no no; 'cos 'cos obviously 'cos\r\n- <i>Laughs</i>"
+    );
+    assert_eq!(srt.lines[2].sequence_number, 3);
+}
+
+#[test]
+fn stack_ssa() {
+    let mut ssa = SRT::parse(SRT).unwrap().to_ssa();
+    let mut aligned = ssa.clone();
+
+    ssa.stack_overlaps(StackMode::Layers);
+    assert_eq!(
+        ssa.events.iter().map(|e| e.layer).collect::<Vec<u32>>(),
+        vec![0, 0, 1, 2, 3, 0]
+    );
+
+    aligned.stack_overlaps(StackMode::Alignment);
+    assert!(!aligned.events[1].text.starts_with("{\\an8}"));
+    assert!(aligned.events[2].text.starts_with("{\\an8}"));
+    assert!(!aligned.events[3].text.starts_with("{\\an8}"));
+    assert!(aligned.events[4].text.starts_with("{\\an8}"));
+}
+
+#[test]
+fn no_overlaps() {
+    let ssa = SSA::parse(
+        r"[Script Info]
+
+[Events]
+Format: Layer,Start,End,Style,Name,MarginL,MarginR,MarginV,Effect,Text
+Dialogue: 0,0:00:00.00,0:00:02.00,Default,,0,0,0,,A
+Dialogue: 0,0:00:02.00,0:00:04.00,Default,,0,0,0,,B",
+    )
+    .unwrap();
+    assert!(find_overlaps(&ssa).is_empty());
+}
@@ -0,0 +1,66 @@
+use rsubs_lib::{Playback, PlaybackEvent, PlaybackEventKind, VTT};
+use time::macros::time;
+
+const VTT: &str = "WEBVTT
+
+00:01.000 --> 00:03.000
+First
+
+00:03.000 --> 00:05.000
+<00:03.000>Never <00:03.500>gonna <00:04.000>give <00:06.000>you up
+
+00:04.000 --> 00:04.000
+Empty";
+
+fn event(time: time::Time, cue: usize, kind: PlaybackEventKind) -> PlaybackEvent {
+    PlaybackEvent { time, cue, kind }
+}
+
+#[test]
+fn events() {
+    let vtt = VTT::parse(VTT).unwrap();
+    let playback = Playback::new(&vtt);
+
+    assert_eq!(
+        playback.collect::<Vec<PlaybackEvent>>(),
+        vec![
+            event(time!(00:00:01), 0, PlaybackEventKind::Shown),
+            event(time!(00:00:03), 0, PlaybackEventKind::Hidden),
+            event(time!(00:00:03), 1, PlaybackEventKind::Shown),
+            event(time!(00:00:03), 1, PlaybackEventKind::Step { offset: 0 }),
+            event(
+                time!(00:00:03.500),
+                1,
+                PlaybackEventKind::Step { offset: 17 }
+            ),
+            event(time!(00:00:04), 1, PlaybackEventKind::Step { offset: 34 }),
+            event(time!(00:00:05), 1, PlaybackEventKind::Hidden),
+        ]
+    );
+    assert_eq!(
+        &vtt.lines[1].text[17..],
+        "<00:03.500>gonna <00:04.000>give <00:06.000>you up"
+    );
+}
+
+#[test]
+fn advance() {
+    let vtt = VTT::parse(VTT).unwrap();
+    let mut playback = Playback::new(&vtt);
+
+    assert!(playback.advance(time!(00:00:00.500)).is_empty());
+    assert_eq!(playback.advance(time!(00:00:01)).len(), 1);
+    assert_eq!(playback.advance(time!(00:00:03.600)).len(), 4);
+    assert_eq!(playback.advance(time!(00:00:10)).len(), 2);
+    assert!(playback.advance(time!(00:00:20)).is_empty());
+
+    playback.seek(time!(00:00:03.500));
+    assert_eq!(
+        playback.next(),
+        Some(event(
+            time!(00:00:03.500),
+            1,
+            PlaybackEventKind::Step { offset: 17 }
+        ))
+    );
+}
@@ -0,0 +1,154 @@
+use rsubs_lib::{Cue, SAMIClass, SAMIErrorKind, SAMILine, SAMI};
+use time::macros::time;
+
+const SAMI_FILE: &str = r##"<SAMI>
+<HEAD>
+<TITLE>Example</TITLE>
+<STYLE TYPE="text/css">
+<!--
+P { margin-left: 8pt; font-size: 20pt; text-align: center; }
+.KRCC { Name: Korean; lang: ko-KR; SAMIType: CC; }
+.ENCC { Name: English; lang: en-US; SAMIType: CC; }
+-->
+</STYLE>
+</HEAD>
+<BODY>
+<SYNC Start=1000><P Class=KRCC>안녕하세요
+<P Class=ENCC>Hello<br>
+  <i>world</i>
+<SYNC Start=3000><P Class=KRCC>&nbsp;
+<SYNC Start=3500><P Class=ENCC><font color="#ff0000">Red</font> &amp; more
+<SYNC Start=4000><P Class=ENCC>Replaced
+<SYNC Start=5000><P Class=ENCC>&nbsp;
+</BODY>
+</SAMI>"##;
+
+#[test]
+fn parse() {
+    let sami = SAMI::parse(SAMI_FILE).unwrap();
+
+    assert_eq!(sami.title.as_deref(), Some("Example"));
+    assert_eq!(
+        sami.classes,
+        vec![
+            SAMIClass {
+                name: "KRCC".to_string(),
+                display_name: Some("Korean".to_string()),
+                lang: Some("ko-KR".to_string()),
+            },
+            SAMIClass {
+                name: "ENCC".to_string(),
+                display_name: Some("English".to_string()),
+                lang: Some("en-US".to_string()),
+            },
+        ]
+    );
+
+    let line = |start, end, class: &str, text: &str| SAMILine {
+        start,
+        end,
+        class: Some(class.to_string()),
+        text: text.to_string(),
+    };
+    assert_eq!(
+        sami.lines,
+        vec![
+            line(time!(00:00:01), time!(00:00:03), "KRCC", "안녕하세요"),
+            line(
+                time!(00:00:01),
+                time!(00:00:03.500),
+                "ENCC",
+                "Hello<br><i>world</i>"
+            ),
+            line(
+                time!(00:00:03.500),
+                time!(00:00:04),
+                "ENCC",
+                "<font color=\"#ff0000\">Red</font> &amp; more"
+            ),
+            line(time!(00:00:04), time!(00:00:05), "ENCC", "Replaced"),
+        ]
+    );
+    assert_eq!(sami.lines[1].plain_text(), "Hello\nworld");
+    assert_eq!(sami.lines[2].plain_text(), "Red & more");
+}
+
+#[test]
+fn parse_without_class() {
+    let sami = SAMI::parse(
+        "<sami><body>\n<sync start=\"500\">First\n<sync start=\"1500\">Second\n</body></sami>",
+    )
+    .unwrap();
+
+    assert!(sami.classes.is_empty());
+    assert_eq!(sami.lines.len(), 2);
+    assert_eq!(sami.lines[0].end, time!(00:00:01.500));
+    assert_eq!(sami.lines[0].class, None);
+    // the last line isn't cleared
+    assert_eq!(sami.lines[1].end, time!(00:00:06.500));
+    assert_eq!(sami.split(), vec![sami.clone()]);
+}
+
+#[test]
+fn parse_error() {
+    let err = SAMI::parse("<html></html>").unwrap_err();
+    assert_eq!(err.kind(), &SAMIErrorKind::Invalid);
+
+    let err = SAMI::parse("<SAMI><BODY>\n<SYNC Start=1000>Text\n<SYNC Start=abc>Text").unwrap_err();
+    assert_eq!(err.line(), 3);
+    assert_eq!(
+        err.kind(),
+        &SAMIErrorKind::Parse("invalid start 'abc'".to_string())
+    );
+}
+
+#[test]
+fn select() {
+    let sami = SAMI::parse(SAMI_FILE).unwrap();
+
+    let korean = sami.select("krcc");
+    assert_eq!(korean.classes.len(), 1);
+    assert_eq!(korean.lines.len(), 1);
+
+    let split = sami.split();
+    assert_eq!(split.len(), 2);
+    assert_eq!(split[0], korean);
+    assert_eq!(split[1].classes[0].name, "ENCC");
+    assert_eq!(split[1].lines.len(), 3);
+}
+
+#[test]
+fn display() {
+    let sami = SAMI::parse(SAMI_FILE).unwrap();
+    let written = sami.to_string();
+
+    assert!(written.contains(".KRCC { Name: Korean; lang: ko-KR; }"));
+    assert!(written.contains(
+        "<SYNC Start=1000><P Class=KRCC>안녕하세요\r\n<SYNC Start=1000><P Class=ENCC>Hello<br><i>world</i>\r\n<SYNC Start=3000><P Class=KRCC>&nbsp;\r\n"
+    ));
+    // touching lines of the same class aren't cleared in between
+    assert!(!written.contains("<SYNC Start=4000><P Class=ENCC>&nbsp;"));
+    assert!(written.contains("<SYNC Start=5000><P Class=ENCC>&nbsp;"));
+    assert_eq!(SAMI::parse(&written).unwrap(), sami);
+}
+
+#[test]
+fn convert() {
+    let english = SAMI::parse(SAMI_FILE).unwrap().select("ENCC");
+
+    let srt = english.to_srt();
+    assert_eq!(srt.lines[0].text, "Hello\r\n<i>world</i>");
+    assert_eq!(
+        srt.lines[1].text,
+        "<font color=\"#FF0000\">Red</font> & more"
+    );
+
+    let vtt = english.to_vtt();
+    assert_eq!(vtt.lines[0].text, "Hello\n<i>world</i>");
+    assert_eq!(vtt.lines[1].text, "Red &amp; more");
+    assert_eq!(vtt.lines[2].start, time!(00:00:04));
+
+    let ssa = english.to_ssa();
+    assert_eq!(ssa.events[0].text, "Hello\\N{\\i1}world{\\i0}");
+    assert_eq!(ssa.events[1].text, "{\\c&H0000FF&}Red{\\c} & more");
+}
@@ -0,0 +1,79 @@
+use rsubs_lib::{SBVErrorKind, SBVLine, SBV, SRT};
+use time::macros::time;
+
+const SBV_FILE: &str = "0:00:01.000,0:00:03.500
+First line
+
+0:00:04.000,0:00:06.250
+Second line
+with two rows
+
+1:02:03.004,1:02:05.000
+Third line
+";
+
+#[test]
+fn parse() {
+    let sbv = SBV::parse(SBV_FILE).unwrap();
+
+    assert_eq!(
+        sbv.lines,
+        vec![
+            SBVLine {
+                start: time!(00:00:01),
+                end: time!(00:00:03.500),
+                text: "First line".to_string(),
+            },
+            SBVLine {
+                start: time!(00:00:04),
+                end: time!(00:00:06.250),
+                text: "Second line\nwith two rows".to_string(),
+            },
+            SBVLine {
+                start: time!(01:02:03.004),
+                end: time!(01:02:05),
+                text: "Third line".to_string(),
+            },
+        ]
+    );
+}
+
+#[test]
+fn parse_error() {
+    let err = SBV::parse("0:00:01.000,0:00:02.000\nText\n\n0:00:03,0:00:04.000\nText").unwrap_err();
+    assert_eq!(err.line(), 4);
+    assert_eq!(
+        err.kind(),
+        &SBVErrorKind::Parse("invalid timing line '0:00:03,0:00:04.000'".to_string())
+    );
+
+    let err = SBV::parse("9999999999999999:00:00.000,0:00:01.000\nText").unwrap_err();
+    assert_eq!(err.line(), 1);
+}
+
+#[test]
+fn display() {
+    let sbv = SBV::parse(SBV_FILE).unwrap();
+    let written = sbv.to_string();
+
+    assert_eq!(written, SBV_FILE);
+    assert_eq!(SBV::parse(&written).unwrap(), sbv);
+}
+
+#[test]
+fn convert() {
+    let sbv = SBV::parse(SBV_FILE).unwrap();
+
+    let srt = sbv.to_srt();
+    assert_eq!(srt.lines[1].sequence_number, 2);
+    assert_eq!(srt.lines[1].text, "Second line\r\nwith two rows");
+    assert_eq!(sbv.to_vtt().lines[1].text, "Second line\nwith two rows");
+    assert_eq!(sbv.to_ssa().events[1].text, "Second line\\Nwith two rows");
+
+    let srt = SRT::parse("1\n00:00:01,000 --> 00:00:02,000\n<i>Styled</i>\n").unwrap();
+    assert_eq!(
+        srt.to_sbv().to_string(),
+        "0:00:01.000,0:00:02.000\nStyled\n"
+    );
+    assert_eq!(sbv.to_vtt().to_sbv(), sbv);
+}
@@ -0,0 +1,143 @@
+use rsubs_lib::{SCCColor, SCCErrorKind, SCCLine, SCCRow, SCCSpan, SCC};
+use time::macros::time;
+
+const POP_ON_FILE: &str = "Scenarist_SCC V1.0
+
+00:00:01:00\t9420 9420 94ae 94ae 9452 9452 c8e5 ecec ef80 91ae 91ae f7ef f2ec 6480 9462 9462 d3e5 e3ef 6e64 942f 942f
+
+00:00:02:00\t1c20 1c20 1c2f 1c2f 1c25 1c25 4ff4 68e5 f280
+
+00:00:04:00\t942c 942c
+";
+
+fn span(text: &str, color: SCCColor, italic: bool) -> SCCSpan {
+    SCCSpan {
+        text: text.to_string(),
+        color,
+        italic,
+        underline: false,
+    }
+}
+
+#[test]
+fn parse_pop_on() {
+    let scc = SCC::parse(POP_ON_FILE).unwrap();
+
+    // the caption is shown with the first end of caption code, the second channel is ignored
+    assert_eq!(
+        scc.lines,
+        vec![SCCLine {
+            start: time!(00:00:01.635),
+            end: time!(00:00:04.004),
+            rows: vec![
+                SCCRow {
+                    row: 14,
+                    column: 4,
+                    spans: vec![
+                        span("Hello ", SCCColor::White, false),
+                        span("world", SCCColor::White, true),
+                    ],
+                },
+                SCCRow {
+                    row: 15,
+                    column: 0,
+                    spans: vec![span("Second", SCCColor::Green, false)],
+                },
+            ],
+        }]
+    );
+    assert_eq!(scc.lines[0].text(), "Hello world\nSecond");
+}
+
+#[test]
+fn parse_roll_up() {
+    let scc = SCC::parse(
+        "Scenarist_SCC V1.0
+
+00:00:10;00\t9425 9425 94ad 94ad 94e0 94e0 46e9 f273 f480
+
+00:00:12;00\t94ad 94ad 94e0 94e0 d3e5 e3ef 6e64
+",
+    )
+    .unwrap();
+
+    assert_eq!(scc.lines.len(), 2);
+    assert_eq!(scc.lines[0].start, time!(00:00:10.010));
+    assert_eq!(scc.lines[0].end, time!(00:00:12.012));
+    assert_eq!(scc.lines[0].text(), "First");
+    assert_eq!(scc.lines[0].rows[0].row, 15);
+    // the carriage return moves the first row up
+    assert_eq!(scc.lines[1].text(), "First\nSecond");
+    assert_eq!(scc.lines[1].rows[0].row, 14);
+    assert_eq!(scc.lines[1].end, time!(00:00:17.012));
+}
+
+#[test]
+fn parse_paint_on() {
+    let scc = SCC::parse(
+        "Scenarist_SCC V1.0
+
+00:00:20:00\t9429 9429 9140 9140 9137 9137 2043 c146 94a1 94a1 4645 92a1 92a1 97a2 97a2 2abc 2680
+",
+    )
+    .unwrap();
+
+    // special character, backspace, extended character replacing the previous one, tab offset
+    // and 0x2a of the standard character set
+    assert_eq!(scc.lines.len(), 1);
+    assert_eq!(scc.lines[0].start, time!(00:00:20.153));
+    assert_eq!(scc.lines[0].rows[0].row, 1);
+    assert_eq!(scc.lines[0].text(), "♪ CAFÉ  á<&");
+}
+
+#[test]
+fn parse_error() {
+    let err = SCC::parse("00:00:01:00\t9420 9420").unwrap_err();
+    assert_eq!(err.line(), 1);
+    assert_eq!(err.kind(), &SCCErrorKind::Invalid);
+
+    let err = SCC::parse("Scenarist_SCC V1.0\n\n00:00:01:00\t9420 94zz").unwrap_err();
+    assert_eq!(err.line(), 3);
+    assert_eq!(
+        err.kind(),
+        &SCCErrorKind::Parse("invalid byte pair '94zz'".to_string())
+    );
+
+    let err = SCC::parse("Scenarist_SCC V1.0\n\n00:00:01\t9420 9420").unwrap_err();
+    assert_eq!(
+        err.kind(),
+        &SCCErrorKind::Parse("invalid timecode '00:00:01'".to_string())
+    );
+}
+
+#[test]
+fn convert() {
+    let scc = SCC::parse(POP_ON_FILE).unwrap();
+
+    let vtt = scc.to_vtt();
+    assert_eq!(vtt.lines[0].text, "Hello <i>world</i>\n<c.lime>Second</c>");
+    assert_eq!(
+        vtt.lines[0].settings.get("line"),
+        Some(&Some("79.33%".to_string()))
+    );
+    assert_eq!(
+        vtt.lines[0].settings.get("position"),
+        Some(&Some("10%".to_string()))
+    );
+    assert_eq!(
+        vtt.lines[0].settings.get("align"),
+        Some(&Some("left".to_string()))
+    );
+
+    let srt = scc.to_srt();
+    assert_eq!(
+        srt.lines[0].text,
+        "Hello <i>world</i>\r\n<font color=\"#00FF00\">Second</font>"
+    );
+    assert_eq!(srt.lines[0].end, time!(00:00:04.004));
+
+    let paint_on =
+        SCC::parse("Scenarist_SCC V1.0\n\n00:00:20:00\t9429 9429 9140 9140 26bc").unwrap();
+    assert_eq!(paint_on.to_vtt().lines[0].text, "&amp;&lt;");
+    assert_eq!(paint_on.to_srt().lines[0].text, "{\\an8}&<");
+}
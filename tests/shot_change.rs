@@ -0,0 +1,79 @@
+use rsubs_lib::util::FrameRate;
+use rsubs_lib::{CueEdge, ShotChangeRules, ShotChangeViolation, SRT};
+use time::macros::time;
+
+// at 25 fps every frame lasts 40ms, frame boundaries lie 20ms before each frame
+const SRT: &str = "1
+00:00:00,500 --> 00:00:01,860
+Starts far away, ends 3 frames before the shot change at 2s
+
+2
+00:00:02,100 --> 00:00:03,000
+Starts 3 frames after the shot change at 2s
+
+3
+00:00:03,900 --> 00:00:04,100
+Too short to be fixed around the shot change at 4s";
+
+fn rules() -> ShotChangeRules {
+    ShotChangeRules {
+        frame_rate: FrameRate::FPS_25,
+        zone_before: 5,
+        zone_after: 5,
+        out_gap: 2,
+    }
+}
+
+#[test]
+fn check() {
+    let srt = SRT::parse(SRT).unwrap();
+    let violations = rules().check(&srt, &[time!(00:00:02.000), time!(00:00:04.000)]);
+
+    assert_eq!(
+        violations,
+        vec![
+            ShotChangeViolation {
+                cue: 0,
+                edge: CueEdge::End,
+                shot_change: time!(00:00:01.980)
+            },
+            ShotChangeViolation {
+                cue: 1,
+                edge: CueEdge::Start,
+                shot_change: time!(00:00:01.980)
+            },
+            ShotChangeViolation {
+                cue: 2,
+                edge: CueEdge::Start,
+                shot_change: time!(00:00:03.980)
+            },
+            ShotChangeViolation {
+                cue: 2,
+                edge: CueEdge::End,
+                shot_change: time!(00:00:03.980)
+            },
+        ]
+    )
+}
+
+#[test]
+fn fix() {
+    let mut srt = SRT::parse(SRT).unwrap();
+    let shot_changes = [time!(00:00:02.000), time!(00:00:04.000)];
+    let report = rules().fix(&mut srt, &shot_changes);
+
+    assert_eq!(report.changes.len(), 3);
+    assert_eq!(srt.lines[0].end, time!(00:00:01.900));
+    assert_eq!(srt.lines[1].start, time!(00:00:01.980));
+    // the start of the last cue moves onto the shot change, its end cannot be fixed anymore
+    assert_eq!(srt.lines[2].start, time!(00:00:03.980));
+    assert_eq!(
+        report.violations,
+        vec![ShotChangeViolation {
+            cue: 2,
+            edge: CueEdge::End,
+            shot_change: time!(00:00:03.980)
+        }]
+    );
+    assert_eq!(rules().check(&srt, &shot_changes), report.violations);
+}
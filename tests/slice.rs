@@ -0,0 +1,95 @@
+use rsubs_lib::{SRT, VTT};
+use time::macros::time;
+
+const SRT: &str = "1
+00:00:01,000 --> 00:00:03,000
+First
+
+2
+00:00:04,000 --> 00:00:06,000
+Second
+
+3
+00:00:09,000 --> 00:00:12,000
+Third
+
+4
+00:00:14,000 --> 00:00:15,000
+Fourth";
+
+#[test]
+fn slice() {
+    let srt = SRT::parse(SRT).unwrap();
+    let slice = srt.slice(time!(00:00:05), time!(00:00:10));
+
+    assert_eq!(slice.lines.len(), 2);
+    assert_eq!(slice.lines[0].sequence_number, 1);
+    assert_eq!(slice.lines[0].start, time!(00:00:00));
+    assert_eq!(slice.lines[0].end, time!(00:00:01));
+    assert_eq!(slice.lines[0].text, "Second");
+    assert_eq!(slice.lines[1].sequence_number, 2);
+    assert_eq!(slice.lines[1].start, time!(00:00:04));
+    assert_eq!(slice.lines[1].end, time!(00:00:05));
+    assert_eq!(slice.lines[1].text, "Third");
+}
+
+#[test]
+fn slice_outside() {
+    let srt = SRT::parse(SRT).unwrap();
+    assert!(srt.slice(time!(00:00:06), time!(00:00:09)).lines.is_empty());
+    assert!(srt.slice(time!(00:00:20), time!(00:00:30)).lines.is_empty());
+}
+
+#[test]
+fn split() {
+    let srt = SRT::parse(SRT).unwrap();
+    let parts = srt.split(&[time!(00:00:10), time!(00:00:04)]);
+
+    assert_eq!(parts.len(), 3);
+    assert_eq!(parts[0].lines.len(), 1);
+    assert_eq!(parts[1].lines.len(), 2);
+    assert_eq!(parts[1].lines[1].start, time!(00:00:05));
+    assert_eq!(parts[1].lines[1].end, time!(00:00:06));
+    assert_eq!(parts[2].lines.len(), 2);
+    assert_eq!(parts[2].lines[0].sequence_number, 1);
+    assert_eq!(parts[2].lines[0].start, time!(00:00:00));
+    assert_eq!(parts[2].lines[0].end, time!(00:00:02));
+    assert_eq!(parts[2].lines[1].start, time!(00:00:04));
+}
+
+#[test]
+fn keep_styles() {
+    let vtt = VTT::parse(
+        "WEBVTT
+
+REGION
+id:fred
+width:40%
+
+STYLE
+::cue {
+  color: yellow;
+}
+
+00:01.000 --> 00:03.000 region:fred
+First
+
+00:04.000 --> 00:06.000
+Second",
+    )
+    .unwrap();
+    let parts = vtt.split(&[time!(00:00:03.500)]);
+
+    assert_eq!(parts.len(), 2);
+    for part in &parts {
+        assert_eq!(part.regions, vtt.regions);
+        assert_eq!(part.styles, vtt.styles);
+        assert_eq!(part.lines.len(), 1);
+    }
+
+    let ssa = SRT::parse(SRT).unwrap().to_ssa();
+    let slice = ssa.slice(time!(00:00:00), time!(00:00:05));
+    assert_eq!(slice.info, ssa.info);
+    assert_eq!(slice.styles, ssa.styles);
+    assert_eq!(slice.events.len(), 2);
+}
@@ -0,0 +1,126 @@
+use rsubs_lib::util::{FrameRate, FrameRounding, Timecode, TimecodeError};
+use rsubs_lib::{subtract_programme_start, SRT};
+use time::macros::time;
+
+#[test]
+fn parse_and_display() {
+    let timecode = Timecode::parse("10:00:00:00", FrameRate::FPS_25).unwrap();
+    assert_eq!(timecode, Timecode::new(10, 0, 0, 0, false));
+    assert_eq!(timecode.to_string(), "10:00:00:00");
+
+    let timecode = Timecode::parse("01:02:03;04", FrameRate::FPS_29_97).unwrap();
+    assert_eq!(timecode, Timecode::new(1, 2, 3, 4, true));
+    assert_eq!(timecode.to_string(), "01:02:03;04");
+
+    assert!(Timecode::parse("01:02:03", FrameRate::FPS_25).is_err());
+    assert!(Timecode::parse("01:02:03:xx", FrameRate::FPS_25).is_err());
+}
+
+#[test]
+fn drop_frame_rate() {
+    assert!(Timecode::parse("01:02:03;04", FrameRate::FPS_59_94).is_ok());
+    assert!(Timecode::parse("01:02:03.04", FrameRate::new(60000, 2002)).is_ok());
+    let error = Timecode::parse("01:02:03;04", FrameRate::FPS_25).unwrap_err();
+    assert_eq!(error, TimecodeError::DropFrame);
+    assert!(Timecode::parse("01:02:03;04", FrameRate::FPS_30).is_err());
+}
+
+#[test]
+fn parse_out_of_range() {
+    let invalid = |timecode: &str| TimecodeError::Parse(format!("invalid timecode '{timecode}'"));
+    for (timecode, fps) in [
+        ("00:75:00:00", FrameRate::FPS_25),
+        ("00:00:60:00", FrameRate::FPS_25),
+        ("00:00:00:25", FrameRate::FPS_25),
+        ("00:00:00;30", FrameRate::FPS_29_97),
+        // frames ;00 and ;01 are skipped at the start of minute 1
+        ("00:01:00;00", FrameRate::FPS_29_97),
+        ("00:01:00;01", FrameRate::FPS_29_97),
+        ("00:01:00;03", FrameRate::FPS_59_94),
+    ] {
+        assert_eq!(Timecode::parse(timecode, fps), Err(invalid(timecode)));
+    }
+
+    assert!(Timecode::parse("00:00:00:24", FrameRate::FPS_25).is_ok());
+    assert!(Timecode::parse("00:01:00;02", FrameRate::FPS_29_97).is_ok());
+    assert!(Timecode::parse("00:10:00;00", FrameRate::FPS_29_97).is_ok());
+    assert!(Timecode::parse("00:01:00:00", FrameRate::FPS_29_97).is_ok());
+}
+
+#[test]
+fn drop_frame_ignored_at_other_rates() {
+    let fps = FrameRate::FPS_25;
+    let timecode = Timecode::from_frame(1510, fps, true);
+    assert_eq!(timecode, Timecode::new(0, 1, 0, 10, false));
+    assert_eq!(Timecode::new(0, 1, 0, 10, true).to_frame(fps), 1510);
+    assert_eq!(
+        Timecode::from_time(time!(00:01:00.400), fps, true, FrameRounding::Down),
+        timecode
+    );
+}
+
+#[test]
+fn non_drop_frame() {
+    let fps = FrameRate::FPS_25;
+    let timecode = Timecode::new(0, 1, 0, 10, false);
+    assert_eq!(timecode.to_frame(fps), 1510);
+    assert_eq!(Timecode::from_frame(1510, fps, false), timecode);
+    assert_eq!(timecode.to_time(fps), time!(00:01:00.400));
+}
+
+#[test]
+fn drop_frame() {
+    let fps = FrameRate::FPS_29_97;
+    // frames ;00 and ;01 don't exist at the start of minute 1
+    assert_eq!(Timecode::new(0, 1, 0, 2, true).to_frame(fps), 1800);
+    assert_eq!(
+        Timecode::from_frame(1799, fps, true),
+        Timecode::new(0, 0, 59, 29, true)
+    );
+    assert_eq!(
+        Timecode::from_frame(1800, fps, true),
+        Timecode::new(0, 1, 0, 2, true)
+    );
+    // every tenth minute isn't dropped
+    assert_eq!(Timecode::new(0, 10, 0, 0, true).to_frame(fps), 17982);
+    assert_eq!(
+        Timecode::from_frame(17982, fps, true),
+        Timecode::new(0, 10, 0, 0, true)
+    );
+    // drop-frame timecodes follow the wall clock within a few milliseconds per hour
+    let hour = Timecode::new(1, 0, 0, 0, true);
+    assert_eq!(hour.to_frame(fps), 107892);
+    assert_eq!(hour.to_time(fps), time!(00:59:59.996));
+
+    for frame in [0, 1, 1799, 1800, 1801, 17981, 17982, 17983, 107892, 123456] {
+        assert_eq!(Timecode::from_frame(frame, fps, true).to_frame(fps), frame);
+    }
+}
+
+#[test]
+fn from_time() {
+    let timecode = Timecode::from_time(
+        time!(00:00:01.500),
+        FrameRate::FPS_25,
+        false,
+        FrameRounding::Down,
+    );
+    assert_eq!(timecode, Timecode::new(0, 0, 1, 12, false));
+}
+
+#[test]
+fn programme_start() {
+    let mut srt = SRT::parse(
+        "1
+10:00:01,000 --> 10:00:02,000
+First line",
+    )
+    .unwrap();
+    subtract_programme_start(
+        &mut srt,
+        Timecode::parse("10:00:00:00", FrameRate::FPS_25).unwrap(),
+        FrameRate::FPS_25,
+    );
+    assert_eq!(srt.lines[0].start, time!(00:00:01.000));
+    assert_eq!(srt.lines[0].end, time!(00:00:02.000));
+}
@@ -0,0 +1,128 @@
+use rsubs_lib::{SubViewer, SubViewerErrorKind, SubViewerVersion, SRT};
+use time::macros::time;
+
+const V2: &str = "[INFORMATION]
+[TITLE]Example
+[AUTHOR]Someone
+[SOURCE]
+[PRG]
+[FILEPATH]
+[DELAY]0
+[CD TRACK]0
+[COMMENT]Nothing
+[VERSION]2
+[END INFORMATION]
+[SUBTITLE]
+[COLF]&HFFFFFF,[STYLE]bd,[SIZE]18,[FONT]Arial
+00:00:01.00,00:00:03.50
+First line[br]second line
+
+00:00:04.00,00:00:06.00
+Another
+text
+";
+
+const V1: &str = "[TITLE]
+Example
+[AUTHOR]
+Someone
+[BEGIN]
+******** START SCRIPT ********
+[00:00:01]
+First line|second line
+[00:00:03]
+Directly following
+[00:00:05]
+
+[00:00:07]
+Last
+[00:00:09]
+
+[end]
+******** END SCRIPT ********";
+
+#[test]
+fn parse_v2() {
+    let sub = SubViewer::parse(V2).unwrap();
+
+    assert_eq!(sub.version, SubViewerVersion::V2);
+    assert_eq!(sub.info.title.as_deref(), Some("Example"));
+    assert_eq!(sub.info.source.as_deref(), Some(""));
+    assert_eq!(sub.info.comment.as_deref(), Some("Nothing"));
+    assert_eq!(
+        sub.info.additional_fields,
+        vec![("VERSION".to_string(), "2".to_string())]
+    );
+    assert_eq!(
+        sub.info.style.as_deref(),
+        Some("[COLF]&HFFFFFF,[STYLE]bd,[SIZE]18,[FONT]Arial")
+    );
+    assert_eq!(sub.lines.len(), 2);
+    assert_eq!(sub.lines[0].start, time!(00:00:01));
+    assert_eq!(sub.lines[0].end, time!(00:00:03.500));
+    assert_eq!(sub.lines[0].text, "First line[br]second line");
+    assert_eq!(sub.lines[1].text, "Another[br]text");
+}
+
+#[test]
+fn display_v2() {
+    let sub = SubViewer::parse(V2).unwrap();
+    let expected = V2
+        .replace("Another\ntext", "Another[br]text")
+        .replace('\n', "\r\n");
+
+    assert_eq!(
+        sub.to_string(),
+        expected.trim_end_matches("\r\n").to_string() + "\r\n"
+    );
+    assert_eq!(SubViewer::parse(sub.to_string()).unwrap(), sub);
+}
+
+#[test]
+fn parse_v1() {
+    let sub = SubViewer::parse(V1).unwrap();
+
+    assert_eq!(sub.version, SubViewerVersion::V1);
+    assert_eq!(sub.info.author.as_deref(), Some("Someone"));
+    assert_eq!(sub.lines.len(), 3);
+    assert_eq!(sub.lines[0].end, time!(00:00:03));
+    assert_eq!(sub.lines[0].text, "First line[br]second line");
+    assert_eq!(sub.lines[1].start, time!(00:00:03));
+    assert_eq!(sub.lines[1].end, time!(00:00:05));
+    assert_eq!(sub.lines[2].start, time!(00:00:07));
+    assert_eq!(sub.lines[2].end, time!(00:00:09));
+}
+
+#[test]
+fn display_v1() {
+    let sub = SubViewer::parse(V1).unwrap();
+    assert_eq!(SubViewer::parse(sub.to_string()).unwrap(), sub);
+}
+
+#[test]
+fn parse_error() {
+    let err = SubViewer::parse("[SUBTITLE]\n00:00:01.00-00:00:02.00\nText").unwrap_err();
+    assert_eq!(err.line(), 2);
+    assert_eq!(
+        err.kind(),
+        &SubViewerErrorKind::Parse("invalid time range".to_string())
+    );
+}
+
+#[test]
+fn convert() {
+    let sub = SubViewer::parse(V2).unwrap();
+
+    let srt = sub.to_srt();
+    assert_eq!(srt.lines[0].text, "First line\r\nsecond line");
+    assert_eq!(srt.lines[1].sequence_number, 2);
+    assert_eq!(sub.to_vtt().lines[0].text, "First line\nsecond line");
+    assert_eq!(sub.to_ssa().events[0].text, "First line\\Nsecond line");
+
+    let srt = SRT::parse("1\n00:00:01,000 --> 00:00:02,000\n<i>Italic</i>\nline").unwrap();
+    let sub = srt.to_subviewer();
+    assert_eq!(sub.lines[0].text, "Italic[br]line");
+    assert!(sub
+        .to_string()
+        .ends_with("[SUBTITLE]\r\n00:00:01.00,00:00:02.00\r\nItalic[br]line\r\n"));
+}
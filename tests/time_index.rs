@@ -0,0 +1,88 @@
+use rsubs_lib::util::{millis_to_time, time_to_millis};
+use rsubs_lib::{Cue, SRTLine, Subtitle, TimeIndex, SRT, SSA};
+use time::macros::time;
+
+#[test]
+fn at() {
+    let srt = SRT::parse(
+        "1
+00:00:01,000 --> 00:00:03,000
+First
+
+2
+00:00:02,000 --> 00:00:04,000
+Second
+
+3
+00:00:05,000 --> 00:00:06,000
+Third",
+    )
+    .unwrap();
+    let index = TimeIndex::new(&srt);
+
+    assert_eq!(index.len(), 3);
+    assert!(index.at(time!(00:00:00.999)).is_empty());
+    assert_eq!(index.at(time!(00:00:01)), vec![0]);
+    assert_eq!(index.at(time!(00:00:02.500)), vec![0, 1]);
+    assert_eq!(index.at(time!(00:00:03)), vec![1]);
+    assert!(index.at(time!(00:00:04.500)).is_empty());
+    assert_eq!(
+        index.between(time!(00:00:03.500), time!(00:00:05)),
+        vec![1, 2]
+    );
+    assert!(index.between(time!(00:00:06), time!(00:00:10)).is_empty());
+}
+
+#[test]
+fn layers() {
+    let ssa = SSA::parse(
+        r"[Script Info]
+
+[Events]
+Format: Layer,Start,End,Style,Name,MarginL,MarginR,MarginV,Effect,Text
+Dialogue: 2,0:00:00.00,0:00:05.00,Default,,0,0,0,,Sign
+Dialogue: 0,0:00:01.00,0:00:03.00,Default,,0,0,0,,Dialogue
+Dialogue: 1,0:00:00.00,0:00:05.00,Default,,0,0,0,,Background",
+    )
+    .unwrap();
+    let index = TimeIndex::new(&ssa);
+
+    assert_eq!(index.at(time!(00:00:02)), vec![1, 2, 0]);
+    assert_eq!(index.at(time!(00:00:04)), vec![2, 0]);
+}
+
+#[test]
+fn brute_force() {
+    // deterministic pseudo random cues
+    let mut seed: i64 = 42;
+    let mut random = |max: i64| {
+        seed = (seed * 1103515245 + 12345) % 2147483648;
+        seed % max
+    };
+    let mut srt = SRT::default();
+    for i in 0..500 {
+        let start = random(600_000);
+        srt.lines.push(SRTLine {
+            sequence_number: i + 1,
+            start: millis_to_time(start),
+            end: millis_to_time(start + random(10_000)),
+            text: String::new(),
+        })
+    }
+    let index = TimeIndex::new(&srt);
+
+    for _ in 0..200 {
+        let a = millis_to_time(random(620_000));
+        let b = millis_to_time(time_to_millis(a) + random(5_000));
+
+        let expected_at: Vec<usize> = (0..srt.cues().len())
+            .filter(|i| srt.cues()[*i].start() <= a && srt.cues()[*i].end() > a)
+            .collect();
+        assert_eq!(index.at(a), expected_at);
+
+        let expected_between: Vec<usize> = (0..srt.cues().len())
+            .filter(|i| srt.cues()[*i].start() <= b && srt.cues()[*i].end() > a)
+            .collect();
+        assert_eq!(index.between(a, b), expected_between);
+    }
+}
@@ -0,0 +1,118 @@
+use rsubs_lib::{TimedText, TimedTextErrorKind, TimedTextEvent, TimedTextSegment};
+use time::macros::time;
+
+const JSON3_FILE: &str = r#"{
+  "wireMagic": "pb3",
+  "events": [
+    {"tStartMs": 0, "dDurationMs": 10000, "id": 1, "wpWinPosId": 1, "wsWinStyleId": 1},
+    {"tStartMs": 1000, "dDurationMs": 3000, "wWinId": 1, "segs": [
+      {"utf8": "Hello", "acAsrConf": 0},
+      {"utf8": " world", "tOffsetMs": 600, "acAsrConf": 0},
+      {"utf8": " & <you>", "tOffsetMs": 1200, "acAsrConf": 0}
+    ]},
+    {"tStartMs": 2500, "dDurationMs": 1500, "wWinId": 1, "aAppend": 1, "segs": [{"utf8": "\n"}]},
+    {"tStartMs": 4000, "dDurationMs": 2000, "wWinId": 1, "segs": [{"utf8": "Manual\nline"}]}
+  ]
+}"#;
+
+const SRV3_FILE: &str = r#"<?xml version="1.0" encoding="utf-8" ?>
+<timedtext format="3">
+<head><wp id="1" ap="7" ah="50" av="100"/></head>
+<body>
+<w t="0" id="1" wp="1"/>
+<p t="1000" d="3000" w="1"><s ac="0">Hello</s><s t="600" ac="0"> world</s><s t="1200" ac="0"> &amp; &lt;you&gt;</s></p>
+<p t="2500" d="1500" w="1" a="1">
+</p>
+<p t="4000" d="2000" w="1">Manual
+line</p>
+</body>
+</timedtext>"#;
+
+fn expected() -> TimedText {
+    let segment = |start, text: &str| TimedTextSegment {
+        start,
+        text: text.to_string(),
+    };
+    TimedText {
+        events: vec![
+            TimedTextEvent {
+                start: time!(00:00:01),
+                end: time!(00:00:04),
+                segments: vec![
+                    segment(time!(00:00:01), "Hello"),
+                    segment(time!(00:00:01.600), " world"),
+                    segment(time!(00:00:02.200), " & <you>"),
+                ],
+            },
+            TimedTextEvent {
+                start: time!(00:00:04),
+                end: time!(00:00:06),
+                segments: vec![segment(time!(00:00:04), "Manual\nline")],
+            },
+        ],
+    }
+}
+
+#[test]
+fn parse_json3() {
+    assert_eq!(TimedText::parse_json3(JSON3_FILE).unwrap(), expected());
+}
+
+#[test]
+fn parse_srv3() {
+    assert_eq!(TimedText::parse_srv3(SRV3_FILE).unwrap(), expected());
+}
+
+#[test]
+fn parse_error() {
+    let err = TimedText::parse_json3("{\n\"events\": [\n{\"tStartMs\": \"abc\"}]}").unwrap_err();
+    assert_eq!(err.line(), 3);
+    assert!(matches!(err.kind(), TimedTextErrorKind::Json(_)));
+
+    let err = TimedText::parse_srv3("<transcript></transcript>").unwrap_err();
+    assert_eq!(err.kind(), &TimedTextErrorKind::Invalid);
+
+    let err = TimedText::parse_srv3("<timedtext><body>\n<p t=\"1.5s\">Text</p></body></timedtext>")
+        .unwrap_err();
+    assert_eq!(err.line(), 2);
+    assert_eq!(
+        err.kind(),
+        &TimedTextErrorKind::Parse("invalid number '1.5s'".to_string())
+    );
+}
+
+#[test]
+fn parse_large_times() {
+    // times are clamped to the end of the day instead of overflowing
+    let timed_text = TimedText::parse_json3(
+        r#"{"events": [{"tStartMs": 9223372036854775000, "dDurationMs": 1000, "segs": [{"utf8": "Text", "tOffsetMs": 1000}]}]}"#,
+    )
+    .unwrap();
+    assert_eq!(timed_text.events[0].end, time!(23:59:59.999));
+    assert_eq!(timed_text.events[0].segments[0].start, time!(23:59:59.999));
+
+    let timed_text = TimedText::parse_srv3(
+        r#"<timedtext><body><p t="9223372036854775000" d="1000"><s t="1000">Text</s></p></body></timedtext>"#,
+    )
+    .unwrap();
+    assert_eq!(timed_text.events[0].end, time!(23:59:59.999));
+    assert_eq!(timed_text.events[0].segments[0].start, time!(23:59:59.999));
+}
+
+#[test]
+fn convert() {
+    let timed_text = TimedText::parse_json3(JSON3_FILE).unwrap();
+
+    let vtt = timed_text.to_vtt();
+    assert_eq!(
+        vtt.lines[0].text,
+        "Hello<00:00:01.600> world<00:00:02.200> &amp; &lt;you>"
+    );
+    assert_eq!(vtt.lines[1].text, "Manual\nline");
+    assert_eq!(vtt.lines[1].start, time!(00:00:04));
+
+    let srt = timed_text.to_srt();
+    assert_eq!(srt.lines[0].text, "Hello world & <you>");
+    assert_eq!(srt.lines[1].sequence_number, 2);
+    assert_eq!(srt.lines[1].text, "Manual\r\nline");
+}
@@ -0,0 +1,191 @@
+use rsubs_lib::util::{FrameRate, FrameRounding};
+use rsubs_lib::{shift, snap_to_frames, TimingChangeReason, TimingFixer, SRT};
+use time::macros::time;
+
+const SRT: &str = "1
+00:00:00,000 --> 00:00:00,300
+Short
+
+2
+00:00:01,000 --> 00:00:01,500
+This cue has way too many characters
+
+3
+00:00:02,000 --> 00:00:04,000
+Overlapping
+
+4
+00:00:03,500 --> 00:00:05,000
+Ends close
+
+5
+00:00:05,010 --> 00:00:07,000
+Last";
+
+#[test]
+fn fix_timings() {
+    let mut srt = SRT::parse(SRT).unwrap();
+    let fixer = TimingFixer {
+        min_duration_ms: Some(1000),
+        target_cps: Some(20.0),
+        min_gap_frames: Some(2),
+        frame_rate: FrameRate::FPS_25,
+        trim_overlaps: false,
+    };
+    let changes = fixer.fix(&mut srt);
+
+    assert_eq!(
+        changes
+            .iter()
+            .map(|c| (c.cue, c.reason.clone(), c.new_end))
+            .collect::<Vec<_>>(),
+        vec![
+            (0, TimingChangeReason::MinDuration, time!(00:00:00.920)),
+            (1, TimingChangeReason::TargetCps, time!(00:00:01.920)),
+            (2, TimingChangeReason::Overlap, time!(00:00:03.420)),
+            (3, TimingChangeReason::MinGap, time!(00:00:04.930)),
+        ]
+    );
+    assert_eq!(changes[0].old_end, time!(00:00:00.300));
+    assert_eq!(srt.lines[0].end, time!(00:00:00.920));
+    assert_eq!(srt.lines[4].end, time!(00:00:07.000));
+}
+
+#[test]
+fn trim_overlaps_only() {
+    let mut srt = SRT::parse(SRT).unwrap();
+    let fixer = TimingFixer {
+        trim_overlaps: true,
+        ..Default::default()
+    };
+    let changes = fixer.fix(&mut srt);
+
+    assert_eq!(changes.len(), 1);
+    assert_eq!(changes[0].reason, TimingChangeReason::Overlap);
+    assert_eq!(srt.lines[2].end, time!(00:00:03.500));
+}
+
+#[test]
+fn no_changes_without_rules() {
+    let mut srt = SRT::parse(SRT).unwrap();
+    assert!(TimingFixer::default().fix(&mut srt).is_empty());
+    assert_eq!(srt, SRT::parse(SRT).unwrap());
+}
+
+#[test]
+fn invalid_target_cps() {
+    let mut srt = SRT::parse(SRT).unwrap();
+    for target_cps in [0.0, -5.0, f64::NAN, f64::INFINITY] {
+        let fixer = TimingFixer {
+            target_cps: Some(target_cps),
+            ..Default::default()
+        };
+        assert!(fixer.fix(&mut srt).is_empty());
+    }
+
+    let fixer = TimingFixer {
+        min_duration_ms: Some(i64::MAX),
+        ..Default::default()
+    };
+    fixer.fix(&mut srt);
+    assert_eq!(srt.lines[4].end, time!(23:59:59.999));
+}
+
+#[test]
+fn shift_clamps() {
+    let mut srt = SRT::parse(SRT).unwrap();
+    shift(&mut srt, i64::MAX);
+    assert_eq!(srt.lines[0].start, time!(23:59:59.999));
+    shift(&mut srt, i64::MIN);
+    assert_eq!(srt.lines[4].end, time!(00:00:00));
+}
+
+#[test]
+fn frame_conversion() {
+    let fps = FrameRate::FPS_23_976;
+    assert_eq!(
+        fps.time_to_frame(time!(00:00:01.000), FrameRounding::Down),
+        23
+    );
+    assert_eq!(
+        fps.time_to_frame(time!(00:00:01.000), FrameRounding::Up),
+        24
+    );
+    assert_eq!(
+        fps.time_to_frame(time!(00:00:01.000), FrameRounding::Nearest),
+        24
+    );
+    assert_eq!(fps.frame_to_time(24), time!(00:00:01.001));
+    assert_eq!(fps.frame_boundary(24), time!(00:00:00.980));
+    assert_eq!(
+        fps.time_to_frame(fps.frame_boundary(24), FrameRounding::Up),
+        24
+    );
+}
+
+#[test]
+fn invalid_frame_rate() {
+    assert!(FrameRate::try_new(25, 0).is_err());
+    assert!(FrameRate::try_new(0, 1).is_err());
+    assert_eq!(FrameRate::try_new(25, 1), Ok(FrameRate::FPS_25));
+    assert!(serde_json::from_str::<FrameRate>(r#"{"numerator": 25, "denominator": 0}"#).is_err());
+    assert_eq!(
+        serde_json::from_str::<FrameRate>(r#"{"numerator": 25, "denominator": 1}"#).unwrap(),
+        FrameRate::FPS_25
+    );
+
+    // zero parts of rates built directly are treated as one instead of dividing by zero
+    let mut srt = SRT::parse(SRT).unwrap();
+    snap_to_frames(&mut srt, FrameRate::new(25, 0), FrameRounding::Nearest);
+    snap_to_frames(&mut srt, FrameRate::new(0, 1), FrameRounding::Nearest);
+    assert_eq!(FrameRate::new(0, 1).frames_to_millis(1), 1000);
+}
+
+#[test]
+fn snap_srt_to_frames() {
+    let mut srt = SRT::parse(SRT).unwrap();
+    let changes = snap_to_frames(&mut srt, FrameRate::FPS_25, FrameRounding::Nearest);
+
+    assert_eq!(changes.len(), 4);
+    assert_eq!(changes[0].cue, 1);
+    assert!(changes
+        .iter()
+        .all(|c| c.reason == TimingChangeReason::FrameSnap));
+    assert_eq!(srt.lines[0].start, time!(00:00:00.000));
+    assert_eq!(srt.lines[0].end, time!(00:00:00.300));
+    assert_eq!(srt.lines[4].start, time!(00:00:04.980));
+    assert_eq!(srt.lines[4].end, time!(00:00:06.980));
+}
+
+#[test]
+fn snap_twice() {
+    let mut srt = SRT::parse(SRT).unwrap();
+    srt.lines[0].start = time!(00:00:00.050);
+    let fps = FrameRate::FPS_29_97;
+
+    // 00:00:00.050 is the boundary of frame 2, even though it's closer to frame 1
+    let changes = snap_to_frames(&mut srt, fps, FrameRounding::Nearest);
+    assert_eq!(changes[0].new_start, time!(00:00:00.050));
+    assert_eq!(changes[0].new_end, time!(00:00:00.284));
+    let snapped = srt.clone();
+    assert!(snap_to_frames(&mut srt, fps, FrameRounding::Nearest).is_empty());
+    assert_eq!(srt, snapped);
+    assert_eq!(
+        fps.snap(time!(00:00:00.050), FrameRounding::Nearest),
+        time!(00:00:00.050)
+    );
+}
+
+#[test]
+fn snap_ssa_to_frames() {
+    let mut ssa = SRT::parse(SRT).unwrap().to_ssa();
+    snap_to_frames(&mut ssa, FrameRate::FPS_23_976, FrameRounding::Down);
+    // the snapped times still select the same frames after being written with centiseconds
+    let reparsed = rsubs_lib::SSA::parse(ssa.to_string()).unwrap();
+    for (event, original) in reparsed.events.iter().zip(&ssa.events) {
+        assert_eq!(
+            FrameRate::FPS_23_976.time_to_frame(event.start, FrameRounding::Up),
+            FrameRate::FPS_23_976.time_to_frame(original.start, FrameRounding::Up)
+        );
+    }
+}
@@ -0,0 +1,285 @@
+use rsubs_lib::util::{Alignment, Color, FrameRate};
+use rsubs_lib::{Cue, TTMLErrorKind, TTML};
+use time::macros::time;
+
+const TTML_DOCUMENT: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<tt xmlns="http://www.w3.org/ns/ttml" xmlns:s="http://www.w3.org/ns/ttml#styling"
+    xmlns:ttp="http://www.w3.org/ns/ttml#parameter" xmlns:ttm="http://www.w3.org/ns/ttml#metadata"
+    xml:lang="en" ttp:frameRate="30" ttp:frameRateMultiplier="1000 1001" ttp:tickRate="10000000">
+  <head>
+    <metadata>
+      <ttm:title>Example</ttm:title>
+    </metadata>
+    <styling>
+      <style xml:id="base" s:fontFamily="proportionalSansSerif" s:fontSize="100%"/>
+      <style xml:id="yellow" style="base" s:color="yellow"/>
+    </styling>
+    <layout>
+      <region xml:id="bottom" s:origin="10% 80%" s:extent="80% 10%" s:displayAlign="after">
+        <style s:textAlign="center"/>
+      </region>
+    </layout>
+  </head>
+  <body>
+    <div begin="1s">
+      <p begin="00:00:00.500" end="00:00:02.000" region="bottom">
+        Hello
+        <br/>
+        <span s:fontStyle="italic">world</span> &amp; more
+      </p>
+      <p begin="30f" dur="15000000t" style="yellow">Second</p>
+    </div>
+    <p begin="00:00:10:15" end="12s" s:textAlign="left">Third <span style="yellow">line</span></p>
+  </body>
+</tt>"#;
+
+#[test]
+fn parse() {
+    let ttml = TTML::parse(TTML_DOCUMENT).unwrap();
+
+    assert_eq!(ttml.lang.as_deref(), Some("en"));
+    assert_eq!(ttml.frame_rate, Some(FrameRate::FPS_29_97));
+    assert_eq!(ttml.tick_rate, Some(10000000));
+    assert_eq!(ttml.title.as_deref(), Some("Example"));
+
+    assert_eq!(ttml.styles.len(), 2);
+    assert_eq!(ttml.styles[1].id, "yellow");
+    assert_eq!(ttml.styles[1].style, vec!["base".to_string()]);
+    assert_eq!(
+        ttml.styles[1].attributes,
+        vec![("tts:color".to_string(), "yellow".to_string())]
+    );
+    assert_eq!(ttml.regions[0].id, "bottom");
+    assert_eq!(ttml.regions[0].attributes.len(), 4);
+
+    assert_eq!(ttml.paragraphs.len(), 3);
+    let first = &ttml.paragraphs[0];
+    assert_eq!(first.begin, time!(00:00:01.500));
+    assert_eq!(first.end, time!(00:00:03));
+    assert_eq!(first.region.as_deref(), Some("bottom"));
+    assert_eq!(
+        first.text,
+        r#"Hello<br/><span tts:fontStyle="italic">world</span> &amp; more"#
+    );
+    assert_eq!(first.plain_text(), "Hello\nworld & more");
+
+    // frames and ticks, relative to the begin of the div
+    assert_eq!(ttml.paragraphs[1].begin, time!(00:00:02.001));
+    assert_eq!(ttml.paragraphs[1].end, time!(00:00:03.501));
+    assert_eq!(ttml.paragraphs[2].begin, time!(00:00:10.501));
+    assert_eq!(ttml.paragraphs[2].end, time!(00:00:12));
+}
+
+#[test]
+fn resolve_style() {
+    let ttml = TTML::parse(TTML_DOCUMENT).unwrap();
+
+    let style = ttml.resolve_style(&["yellow".to_string()], &[]);
+    assert_eq!(style["tts:color"], "yellow");
+    assert_eq!(style["tts:fontFamily"], "proportionalSansSerif");
+
+    let computed = ttml.paragraph_style(&ttml.paragraphs[0]);
+    assert_eq!(computed["tts:textAlign"], "center");
+    assert_eq!(
+        ttml.percentages(&computed["tts:origin"]),
+        Some((10.0, 80.0))
+    );
+}
+
+#[test]
+fn parse_error() {
+    let err = TTML::parse("<tt><body>").unwrap_err();
+    assert!(matches!(err.kind(), TTMLErrorKind::Xml(_)));
+
+    let err = TTML::parse("<html/>").unwrap_err();
+    assert_eq!(err.kind(), &TTMLErrorKind::Invalid);
+
+    let err =
+        TTML::parse("<tt>\n<body>\n<p begin=\"1x\" end=\"2s\">Text</p></body></tt>").unwrap_err();
+    assert_eq!(err.line(), 3);
+    assert_eq!(
+        err.kind(),
+        &TTMLErrorKind::Parse("invalid time '1x'".to_string())
+    );
+
+    let err = TTML::parse("<tt><body><p begin=\"1s\">Text</p></body></tt>").unwrap_err();
+    assert_eq!(
+        err.kind(),
+        &TTMLErrorKind::Parse("missing end time".to_string())
+    );
+}
+
+#[test]
+fn parse_invalid_numbers() {
+    let document = |parameters: &str, begin: &str| {
+        format!(
+            r#"<tt xmlns:ttp="http://www.w3.org/ns/ttml#parameter" {parameters}><body><p begin="{begin}" end="2s">Text</p></body></tt>"#
+        )
+    };
+    let invalid_frame_rate = TTMLErrorKind::Parse("invalid frame rate".to_string());
+
+    for parameters in [
+        r#"ttp:frameRate="0""#,
+        r#"ttp:frameRate="30" ttp:frameRateMultiplier="0 1001""#,
+        r#"ttp:frameRate="30" ttp:frameRateMultiplier="1000 0""#,
+        r#"ttp:frameRate="4000000000" ttp:frameRateMultiplier="1000 1001""#,
+    ] {
+        let err = TTML::parse(document(parameters, "1s")).unwrap_err();
+        assert_eq!(err.kind(), &invalid_frame_rate);
+    }
+
+    let err = TTML::parse(document("", "9999999999999999:00:00")).unwrap_err();
+    assert_eq!(
+        err.kind(),
+        &TTMLErrorKind::Parse("invalid time '9999999999999999:00:00'".to_string())
+    );
+}
+
+#[test]
+fn resolve_style_cycles() {
+    let ttml = TTML::parse(
+        r#"<tt xmlns:tts="http://www.w3.org/ns/ttml#styling"><head><styling>
+<style xml:id="a" style="a a a a a a a a b" tts:color="red"/>
+<style xml:id="b" style="a b b b b b b b" tts:fontStyle="italic"/>
+</styling></head><body><p begin="1s" end="2s" style="a b">Text</p></body></tt>"#,
+    )
+    .unwrap();
+
+    let style = ttml.paragraph_style(&ttml.paragraphs[0]);
+    assert_eq!(style["tts:color"], "red");
+    assert_eq!(style["tts:fontStyle"], "italic");
+    assert_eq!(ttml.to_ssa().events.len(), 1);
+}
+
+#[test]
+fn display() {
+    let ttml = TTML::parse(TTML_DOCUMENT).unwrap();
+    let written = ttml.to_string();
+
+    assert!(written.starts_with(
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<tt xmlns="http://www.w3.org/ns/ttml" xmlns:ttp="http://www.w3.org/ns/ttml#parameter" xmlns:tts="http://www.w3.org/ns/ttml#styling" xmlns:ttm="http://www.w3.org/ns/ttml#metadata" xml:lang="en" ttp:frameRate="30" ttp:frameRateMultiplier="1000 1001" ttp:tickRate="10000000">"#
+    ));
+    assert!(written.contains(
+        r#"      <p begin="00:00:01.500" end="00:00:03.000" region="bottom">Hello<br/><span tts:fontStyle="italic">world</span> &amp; more</p>"#
+    ));
+    assert!(written.contains(r#"      <style xml:id="yellow" style="base" tts:color="yellow"/>"#));
+    assert_eq!(TTML::parse(&written).unwrap(), ttml);
+}
+
+#[test]
+fn convert_to_vtt() {
+    let vtt = TTML::parse(TTML_DOCUMENT).unwrap().to_vtt();
+
+    assert_eq!(vtt.styles[1].selector.as_deref(), Some(".yellow"));
+    assert_eq!(vtt.styles[1].entries["color"], "#FFFF00");
+    assert_eq!(vtt.styles[1].entries["font-family"], "sans-serif");
+
+    let region = &vtt.regions[0];
+    assert_eq!(region.id.as_deref(), Some("bottom"));
+    assert_eq!(region.width, Some(80.0));
+    assert_eq!(region.lines, Some(2));
+    assert_eq!(region.region_anchor, Some((0.0, 100.0)));
+    assert_eq!(region.viewport_anchor, Some((10.0, 90.0)));
+
+    assert_eq!(vtt.lines[0].text, "Hello\n<i>world</i> &amp; more");
+    assert_eq!(vtt.lines[0].settings["region"].as_deref(), Some("bottom"));
+    assert_eq!(vtt.lines[0].settings["align"].as_deref(), Some("center"));
+    assert_eq!(vtt.lines[1].text, "<c.yellow>Second</c>");
+    assert_eq!(vtt.lines[2].text, "Third <c.yellow>line</c>");
+    assert_eq!(vtt.lines[2].settings["align"].as_deref(), Some("left"));
+}
+
+#[test]
+fn convert_to_ssa() {
+    let ssa = TTML::parse(TTML_DOCUMENT).unwrap().to_ssa();
+
+    assert_eq!(ssa.info.title.as_deref(), Some("Example"));
+    let yellow = ssa.styles.iter().find(|s| s.name == "yellow").unwrap();
+    assert_eq!(yellow.fontname, "Arial");
+    assert_eq!(yellow.fontsize, 72.0);
+    assert_eq!(yellow.primary_color, Some(Color::new(255, 255, 0, 255)));
+    assert_eq!(yellow.alignment, Alignment::BottomCenter);
+
+    let first = &ssa.events[0];
+    assert_eq!(first.style, "Default");
+    assert_eq!(first.text, "Hello\\N{\\i1}world{\\i0} & more");
+    assert_eq!(first.margin_l, 192.0);
+    assert_eq!(first.margin_r, 192.0);
+    assert_eq!(first.margin_v, 108.0);
+
+    assert_eq!(ssa.events[1].style, "yellow");
+    assert_eq!(ssa.events[1].text, "Second");
+    assert_eq!(
+        ssa.events[2].text,
+        "{\\an1}Third {\\c&H00FFFF&}line{\\c&HFFFFFF&}"
+    );
+}
+
+#[test]
+fn convert_hex_colors() {
+    let ttml = TTML::parse(
+        r##"<tt xmlns="http://www.w3.org/ns/ttml" xmlns:tts="http://www.w3.org/ns/ttml#styling">
+  <head>
+    <styling>
+      <style xml:id="red" tts:color="#ff0000"/>
+      <style xml:id="translucent" tts:color="#00ff0080" tts:backgroundColor="rgba(0, 0, 255, 128)"/>
+    </styling>
+  </head>
+  <body>
+    <p begin="0s" end="1s" style="red">Red</p>
+    <p begin="1s" end="2s" style="translucent">Green</p>
+  </body>
+</tt>"##,
+    )
+    .unwrap();
+
+    let vtt = ttml.to_vtt();
+    let style = |name: &str| {
+        vtt.styles
+            .iter()
+            .find(|s| s.selector.as_deref() == Some(name))
+            .unwrap()
+    };
+    assert_eq!(style(".red").entries["color"], "#FF0000");
+    assert_eq!(style(".translucent").entries["color"], "#00FF0080");
+    assert_eq!(
+        style(".translucent").entries["background-color"],
+        "#0000FF80"
+    );
+
+    let ssa = ttml.to_ssa();
+    let style = |name: &str| ssa.styles.iter().find(|s| s.name == name).unwrap();
+    assert_eq!(style("red").primary_color, Some(Color::new(255, 0, 0, 255)));
+    assert_eq!(
+        style("translucent").primary_color,
+        Some(Color::new(0, 255, 0, 128))
+    );
+    assert_eq!(
+        style("translucent").back_color,
+        Some(Color::new(0, 0, 255, 128))
+    );
+}
+
+#[test]
+fn display_frame_rate() {
+    let written = |frame_rate| {
+        TTML {
+            frame_rate: Some(frame_rate),
+            ..Default::default()
+        }
+        .to_string()
+    };
+
+    assert!(written(FrameRate::FPS_25).contains(r#"ttp:frameRate="25">"#));
+    // rates which can't be written are left out instead of writing an invalid document
+    for frame_rate in [
+        FrameRate::new(1, 3),
+        FrameRate::new(u32::MAX, 2),
+        FrameRate::new(25, 0),
+    ] {
+        let written = written(frame_rate);
+        assert!(!written.contains("ttp:frameRate"), "{written}");
+        assert!(TTML::parse(&written).is_ok());
+    }
+}
@@ -1,3 +1,4 @@
+use rsubs_lib::util::Color;
 use rsubs_lib::{VTTErrorKind, VTT};
 
 const SIMPLE: &str = r"WEBVTT
@@ -451,3 +452,33 @@ fn parse_bom_content() {
     let bom = VTT::parse(bom).unwrap();
     assert_eq!(bom, VTT::parse(SIMPLE).unwrap());
 }
+
+#[test]
+fn convert_hex_colors_to_ssa() {
+    let vtt = |color: &str, background: &str| {
+        VTT::parse(format!(
+            "WEBVTT\n\nSTYLE\n::cue {{\n  color: {color};\n  background-color: {background};\n}}\n\n00:01.000 --> 00:02.000\nText"
+        ))
+        .unwrap()
+    };
+
+    let ssa = vtt("#ff8000", "#00000080").to_ssa();
+    assert_eq!(
+        ssa.styles[0].primary_color,
+        Some(Color::new(255, 128, 0, 255))
+    );
+    assert_eq!(ssa.styles[0].back_color, Some(Color::new(0, 0, 0, 128)));
+
+    // css shorthands repeat every digit
+    let ssa = vtt("#f80", "#0008").to_ssa();
+    assert_eq!(
+        ssa.styles[0].primary_color,
+        Some(Color::new(255, 136, 0, 255))
+    );
+    assert_eq!(ssa.styles[0].back_color, Some(Color::new(0, 0, 0, 136)));
+
+    // invalid colors are skipped
+    let ssa = vtt("#12345", "#ggg").to_ssa();
+    assert_eq!(ssa.styles[0].primary_color, None);
+    assert_eq!(ssa.styles[0].back_color, None);
+}